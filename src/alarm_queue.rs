@@ -0,0 +1,173 @@
+//! Ordering of simultaneous alarms.
+//!
+//! Only one alarm plays sound at a time: the one with the highest priority,
+//! oldest first among equals. The rest wait in the queue and take over the
+//! audio device once the ringing alarm is acknowledged. Each source has at
+//! most one alarm in the queue; repeats while it is active are only shown.
+
+/// An alarm waiting for or holding the audio device.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueuedAlarm {
+    pub id: u64,
+    pub priority: i32,
+    /// What raised the alarm, e.g. `channel:123`.
+    pub source: String,
+    pub title: String,
+    pub body: String,
+    /// Whether the alarm has escalated after going unacknowledged.
+    pub escalated: bool,
+}
+
+/// Alarms ordered by priority (highest first), then arrival.
+#[derive(Debug, Default)]
+pub struct AlarmQueue {
+    alarms: Vec<QueuedAlarm>,
+}
+
+impl AlarmQueue {
+    /// Add an alarm, returning how many alarms are ahead of it.
+    pub fn push(&mut self, alarm: QueuedAlarm) -> usize {
+        let position = self
+            .alarms
+            .iter()
+            .position(|a| a.priority < alarm.priority)
+            .unwrap_or(self.alarms.len());
+        self.alarms.insert(position, alarm);
+        position
+    }
+
+    /// The alarm currently allowed to play sound.
+    pub fn head(&self) -> Option<&QueuedAlarm> {
+        self.alarms.first()
+    }
+
+    pub fn contains(&self, id: u64) -> bool {
+        self.alarms.iter().any(|a| a.id == id)
+    }
+
+    /// Whether an alarm from `source` is active.
+    pub fn has_source(&self, source: &str) -> bool {
+        self.alarms.iter().any(|a| a.source == source)
+    }
+
+    /// Mark alarm `id` as escalated.
+    pub fn escalate(&mut self, id: u64) {
+        if let Some(alarm) = self.alarms.iter_mut().find(|a| a.id == id) {
+            alarm.escalated = true;
+        }
+    }
+
+    /// Remove the alarm with `id`, or the head when `None`.
+    pub fn remove(&mut self, id: Option<u64>) -> Option<QueuedAlarm> {
+        let index = match id {
+            Some(id) => self.alarms.iter().position(|a| a.id == id)?,
+            None if self.alarms.is_empty() => return None,
+            None => 0,
+        };
+        Some(self.alarms.remove(index))
+    }
+
+    pub fn clear(&mut self) {
+        self.alarms.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.alarms.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &QueuedAlarm> {
+        self.alarms.iter()
+    }
+}
+
+/// Parse `ALARM_PRIORITY` entries like `channel=10` or `voice=5`.
+pub fn parse_priorities(entries: &[String]) -> Result<Vec<(String, i32)>, String> {
+    entries
+        .iter()
+        .map(|entry| {
+            let (source, priority) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("ALARM_PRIORITY entry '{}' must look like source=priority", entry))?;
+            let priority = priority
+                .trim()
+                .parse()
+                .map_err(|_| format!("ALARM_PRIORITY entry '{}' has a non-integer priority", entry))?;
+            Ok((source.trim().to_string(), priority))
+        })
+        .collect()
+}
+
+/// Priority for an alert source such as `voice:123`.
+///
+/// An exact match wins over a match on the kind before the colon (`voice`);
+/// unlisted sources get priority 0.
+pub fn priority_for(priorities: &[(String, i32)], source: &str) -> i32 {
+    let kind = source.split(':').next().unwrap_or(source);
+    priorities
+        .iter()
+        .find(|(s, _)| s == source)
+        .or_else(|| priorities.iter().find(|(s, _)| s == kind))
+        .map(|&(_, p)| p)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alarm(id: u64, priority: i32) -> QueuedAlarm {
+        QueuedAlarm {
+            id,
+            priority,
+            source: format!("channel:{}", id),
+            title: "CHANNEL OPEN".to_string(),
+            body: format!("alarm {}", id),
+            escalated: false,
+        }
+    }
+
+    #[test]
+    fn test_highest_priority_rings_first() {
+        let mut queue = AlarmQueue::default();
+
+        assert_eq!(queue.push(alarm(1, 0)), 0);
+        assert_eq!(queue.push(alarm(2, 10)), 0);
+        assert_eq!(queue.push(alarm(3, 0)), 2);
+        assert_eq!(queue.push(alarm(4, 10)), 1);
+
+        let order: Vec<u64> = queue.iter().map(|a| a.id).collect();
+        assert_eq!(order, vec![2, 4, 1, 3]);
+    }
+
+    #[test]
+    fn test_remove_head_promotes_next() {
+        let mut queue = AlarmQueue::default();
+        queue.push(alarm(1, 5));
+        queue.push(alarm(2, 1));
+
+        assert!(queue.has_source("channel:1"));
+        assert_eq!(queue.remove(None).map(|a| a.id), Some(1));
+        assert!(!queue.has_source("channel:1"));
+        assert_eq!(queue.head().map(|a| a.id), Some(2));
+        assert_eq!(queue.remove(Some(7)), None);
+        assert_eq!(queue.remove(Some(2)).map(|a| a.id), Some(2));
+        assert!(queue.remove(None).is_none());
+    }
+
+    #[test]
+    fn test_priority_lookup() {
+        let priorities = parse_priorities(&["channel=10".to_string(), "voice = 5".to_string(), "voice:42=7".to_string()])
+            .unwrap();
+
+        assert_eq!(priority_for(&priorities, "channel"), 10);
+        assert_eq!(priority_for(&priorities, "voice:1"), 5);
+        assert_eq!(priority_for(&priorities, "voice:42"), 7);
+        assert_eq!(priority_for(&priorities, "stage"), 0);
+    }
+
+    #[test]
+    fn test_parse_priorities_rejects_malformed_entries() {
+        assert!(parse_priorities(&["channel".to_string()]).is_err());
+        assert!(parse_priorities(&["channel=high".to_string()]).is_err());
+    }
+}
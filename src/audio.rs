@@ -0,0 +1,183 @@
+//! Alarm sound playback.
+//!
+//! The default backend runs `mpv` once per repetition. Builds with the
+//! `native-audio` feature can set `AUDIO_BACKEND=native` to decode and play
+//! sounds in-process with rodio instead, which needs no external player and
+//! starts without a process spawn. Either way playback stops as soon as the
+//! future is dropped, so an acknowledged alarm goes quiet mid-sound.
+
+use tokio::process::Command;
+
+/// Whether this build includes the native backend.
+pub const NATIVE_AVAILABLE: bool = cfg!(feature = "native-audio");
+
+/// How alarm sounds are played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AudioBackend {
+    /// Spawn `mpv` for each repetition.
+    #[default]
+    Mpv,
+    /// Decode and play in-process with rodio.
+    Native,
+}
+
+impl AudioBackend {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "mpv" => Some(AudioBackend::Mpv),
+            "native" => Some(AudioBackend::Native),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AudioBackend::Mpv => "mpv",
+            AudioBackend::Native => "native",
+        }
+    }
+}
+
+/// Build the mpv command arguments for one sound file.
+///
+/// `volume` is a percentage; `None` leaves mpv at its own default.
+pub fn mpv_args(path: &str, volume: Option<u8>) -> Vec<String> {
+    let mut args = vec!["--no-video".to_string(), "--really-quiet".to_string()];
+    if let Some(volume) = volume {
+        args.push(format!("--volume={}", volume));
+    }
+    args.push(path.to_string());
+    args
+}
+
+/// Play a sound file once, returning when it has finished.
+pub async fn play(backend: AudioBackend, path: &str, volume: Option<u8>) -> Result<(), String> {
+    match backend {
+        AudioBackend::Mpv => {
+            let status = Command::new("mpv")
+                .args(mpv_args(path, volume))
+                // Dropping the future (alarm acknowledged) stops the sound
+                .kill_on_drop(true)
+                .status()
+                .await
+                .map_err(|e| format!("Failed to run mpv: {}", e))?;
+            if status.success() {
+                Ok(())
+            } else {
+                Err(format!("mpv exited with {}", status))
+            }
+        }
+        AudioBackend::Native => native::play(path, volume).await,
+    }
+}
+
+/// Check that the backend can play at all, e.g. that mpv is installed.
+pub async fn check_player(backend: AudioBackend) -> Result<(), String> {
+    match backend {
+        AudioBackend::Mpv => match Command::new("mpv").arg("--version").output().await {
+            Ok(output) if output.status.success() => Ok(()),
+            Ok(output) => Err(format!("mpv --version exited with {}", output.status)),
+            Err(e) => Err(format!("mpv could not be started ({}); alarms will be silent", e)),
+        },
+        AudioBackend::Native => Ok(()),
+    }
+}
+
+/// Check that the backend can decode every sound file.
+///
+/// Only the native backend decodes up front; mpv reports bad files when playing.
+pub fn check_sounds<'a>(backend: AudioBackend, sounds: impl IntoIterator<Item = &'a str>) -> Result<(), String> {
+    match backend {
+        AudioBackend::Mpv => Ok(()),
+        AudioBackend::Native => sounds.into_iter().try_for_each(native::check),
+    }
+}
+
+#[cfg(feature = "native-audio")]
+mod native {
+    use rodio::{Decoder, OutputStream, Sink};
+    use std::fs::File;
+    use std::io::BufReader;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// Sets the flag when dropped, telling the playback thread to stop.
+    struct StopOnDrop(Arc<AtomicBool>);
+
+    impl Drop for StopOnDrop {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    fn decode(path: &str) -> Result<Decoder<BufReader<File>>, String> {
+        let file = File::open(path).map_err(|e| format!("Failed to open sound file {}: {}", path, e))?;
+        Decoder::new(BufReader::new(file)).map_err(|e| format!("Failed to decode sound file {}: {}", path, e))
+    }
+
+    pub fn check(path: &str) -> Result<(), String> {
+        decode(path).map(drop)
+    }
+
+    pub async fn play(path: &str, volume: Option<u8>) -> Result<(), String> {
+        let stop = Arc::new(AtomicBool::new(false));
+        let _guard = StopOnDrop(Arc::clone(&stop));
+        let path = path.to_string();
+        // The output stream is not Send, so it lives on a blocking thread
+        tokio::task::spawn_blocking(move || {
+            let source = decode(&path)?;
+            let (_stream, handle) =
+                OutputStream::try_default().map_err(|e| format!("No audio output device: {}", e))?;
+            let sink = Sink::try_new(&handle).map_err(|e| format!("Failed to open audio output: {}", e))?;
+            sink.set_volume(f32::from(volume.unwrap_or(100)) / 100.0);
+            sink.append(source);
+            while !sink.empty() {
+                if stop.load(Ordering::SeqCst) {
+                    sink.stop();
+                    break;
+                }
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            Ok(())
+        })
+        .await
+        .map_err(|e| format!("Audio playback failed: {}", e))?
+    }
+}
+
+#[cfg(not(feature = "native-audio"))]
+mod native {
+    const UNAVAILABLE: &str = "AUDIO_BACKEND=native needs a build with the native-audio feature";
+
+    pub fn check(_path: &str) -> Result<(), String> {
+        Err(UNAVAILABLE.to_string())
+    }
+
+    pub async fn play(_path: &str, _volume: Option<u8>) -> Result<(), String> {
+        Err(UNAVAILABLE.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mpv_args_with_volume() {
+        assert_eq!(mpv_args("/a.mp3", None), vec!["--no-video", "--really-quiet", "/a.mp3"]);
+        assert_eq!(mpv_args("/a.mp3", Some(40)), vec!["--no-video", "--really-quiet", "--volume=40", "/a.mp3"]);
+    }
+
+    #[test]
+    fn test_check_sounds_by_backend() {
+        // mpv only finds out when playing
+        assert!(check_sounds(AudioBackend::Mpv, ["/nonexistent/path.mp3"]).is_ok());
+        let err = check_sounds(AudioBackend::Native, ["/nonexistent/path.mp3"]).unwrap_err();
+        if NATIVE_AVAILABLE {
+            assert!(err.starts_with("Failed to open sound file /nonexistent/path.mp3"));
+        } else {
+            assert!(err.contains("native-audio"));
+        }
+    }
+}
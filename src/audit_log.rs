@@ -0,0 +1,195 @@
+//! Channel renames read from the guild audit log.
+//!
+//! The audit log is a third detection source next to the Gateway and channel
+//! polling. It catches renames that both of those miss or see late, and names
+//! the user who made the change.
+
+use crate::models::{AuditLog, AuditLogEntry};
+
+/// A rename of a monitored channel found in the audit log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditRename {
+    pub channel_id: String,
+    pub name: String,
+    pub previous: Option<String>,
+    /// Display name of the user who renamed the channel, if known.
+    pub changed_by: Option<String>,
+}
+
+impl AuditRename {
+    /// Whether the rename starts from `current`, the name already known for the
+    /// channel, so applying it moves the name forward.
+    ///
+    /// Polling or the Gateway may already be past an entry by the time the
+    /// audit log shows it; such an entry would put back an older name.
+    pub fn follows(&self, current: Option<&str>) -> bool {
+        current.is_none() || self.previous.as_deref() == current
+    }
+}
+
+/// Remembers which audit log entries have been seen.
+#[derive(Debug)]
+pub struct AuditLogWatcher {
+    channel_ids: Vec<String>,
+    /// Newest entry ID seen; `None` until the first page sets the baseline.
+    last_seen: Option<u64>,
+}
+
+impl AuditLogWatcher {
+    pub fn new(channel_ids: &[String]) -> Self {
+        Self {
+            channel_ids: channel_ids.to_vec(),
+            last_seen: None,
+        }
+    }
+
+    /// Replace the channels renames are reported for, keeping the baseline.
+    pub fn set_channel_ids(&mut self, channel_ids: &[String]) {
+        self.channel_ids = channel_ids.to_vec();
+    }
+
+    /// Return renames of the channels in entries newer than the last page, oldest first.
+    ///
+    /// The first page only establishes a baseline, so past renames do not alarm.
+    pub fn process(&mut self, log: &AuditLog) -> Vec<AuditRename> {
+        let newest = log.audit_log_entries.iter().filter_map(entry_id).max();
+        let Some(last_seen) = self.last_seen else {
+            self.last_seen = Some(newest.unwrap_or(0));
+            return Vec::new();
+        };
+        self.last_seen = newest.max(Some(last_seen));
+
+        let mut entries: Vec<&AuditLogEntry> = log
+            .audit_log_entries
+            .iter()
+            .filter(|e| entry_id(e).is_some_and(|id| id > last_seen))
+            .filter(|e| e.target_id.as_ref().is_some_and(|id| self.channel_ids.contains(id)))
+            .collect();
+        entries.sort_by_key(|e| entry_id(e));
+
+        entries
+            .into_iter()
+            .filter_map(|entry| {
+                let change = entry.changes.iter().find(|c| c.key == "name")?;
+                Some(AuditRename {
+                    channel_id: entry.target_id.clone()?,
+                    name: change.new_value.as_ref()?.as_str()?.to_string(),
+                    previous: change.old_value.as_ref().and_then(|v| v.as_str()).map(str::to_string),
+                    changed_by: entry.user_id.as_deref().map(|id| display_name(log, id)),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Entry IDs are snowflakes, so they sort by creation time.
+fn entry_id(entry: &AuditLogEntry) -> Option<u64> {
+    entry.id.parse().ok()
+}
+
+/// A user's global name, falling back to their username, then their ID.
+fn display_name(log: &AuditLog, user_id: &str) -> String {
+    match log.users.iter().find(|u| u.id == user_id) {
+        Some(user) => user.global_name.clone().unwrap_or_else(|| user.username.clone()),
+        None => user_id.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(entries: serde_json::Value) -> AuditLog {
+        serde_json::from_value(serde_json::json!({
+            "audit_log_entries": entries,
+            "users": [
+                { "id": "7", "username": "shopkeep", "global_name": "Shop Keeper" },
+                { "id": "8", "username": "modbot", "global_name": null },
+            ],
+        }))
+        .unwrap()
+    }
+
+    fn rename(id: &str, target: &str, user: &str, old: &str, new: &str) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "target_id": target,
+            "user_id": user,
+            "action_type": 11,
+            "changes": [
+                { "key": "name", "old_value": old, "new_value": new },
+                { "key": "rate_limit_per_user", "old_value": 0, "new_value": 5 },
+            ],
+        })
+    }
+
+    #[test]
+    fn test_first_page_is_baseline() {
+        let mut watcher = AuditLogWatcher::new(&["100".to_string()]);
+        let log = page(serde_json::json!([rename("10", "100", "7", "order-❌", "order-✅")]));
+
+        assert!(watcher.process(&log).is_empty());
+        assert!(watcher.process(&log).is_empty());
+    }
+
+    #[test]
+    fn test_new_renames_are_attributed_oldest_first() {
+        let mut watcher = AuditLogWatcher::new(&["100".to_string()]);
+        watcher.process(&page(serde_json::json!([rename("10", "100", "7", "a", "b")])));
+
+        let log = page(serde_json::json!([
+            rename("13", "100", "9", "c", "d"),
+            rename("12", "200", "7", "x", "y"),
+            rename("11", "100", "8", "b", "c"),
+            rename("10", "100", "7", "a", "b"),
+        ]));
+        let renames = watcher.process(&log);
+
+        assert_eq!(
+            renames,
+            vec![
+                AuditRename {
+                    channel_id: "100".to_string(),
+                    name: "c".to_string(),
+                    previous: Some("b".to_string()),
+                    changed_by: Some("modbot".to_string()),
+                },
+                AuditRename {
+                    channel_id: "100".to_string(),
+                    name: "d".to_string(),
+                    previous: Some("c".to_string()),
+                    changed_by: Some("9".to_string()),
+                },
+            ]
+        );
+        assert!(watcher.process(&log).is_empty());
+    }
+
+    #[test]
+    fn test_renames_follow_only_the_known_name() {
+        let rename = AuditRename {
+            channel_id: "100".to_string(),
+            name: "c".to_string(),
+            previous: Some("b".to_string()),
+            changed_by: None,
+        };
+        assert!(rename.follows(Some("b")));
+        assert!(rename.follows(None));
+        assert!(!rename.follows(Some("d")));
+        assert!(!rename.follows(Some("a")));
+    }
+
+    #[test]
+    fn test_updates_without_a_name_change_are_ignored() {
+        let mut watcher = AuditLogWatcher::new(&["100".to_string()]);
+        watcher.process(&page(serde_json::json!([])));
+
+        let log = page(serde_json::json!([{
+            "id": "20",
+            "target_id": "100",
+            "user_id": "7",
+            "changes": [{ "key": "topic", "old_value": "", "new_value": "open soon" }],
+        }]));
+        assert!(watcher.process(&log).is_empty());
+    }
+}
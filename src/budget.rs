@@ -0,0 +1,146 @@
+//! Audible alarm budget.
+//!
+//! Caps how many audible alarms may fire per window, both per source (e.g. one
+//! channel) and globally, and optionally how soon a source may ring again.
+//! Once a cap is reached further events are downgraded to silent
+//! notifications, so a server renaming a channel in a loop cannot keep the
+//! alarm ringing all night.
+
+use crate::clock::Instant;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Window over which audible alarms are counted.
+pub const ALARM_BUDGET_WINDOW: Duration = Duration::from_secs(3600);
+
+/// Sliding-window counter of audible alarms.
+#[derive(Debug)]
+pub struct AlarmBudget {
+    fired: VecDeque<(Instant, String)>,
+    window: Duration,
+    per_source: Option<usize>,
+    global: Option<usize>,
+    /// Time after an alarm before its source may ring again.
+    cooldown: Option<Duration>,
+}
+
+impl AlarmBudget {
+    /// Create a budget allowing `per_source` alarms per source and `global`
+    /// alarms overall within `window`. `None` leaves that cap unlimited.
+    pub fn new(per_source: Option<usize>, global: Option<usize>, window: Duration) -> Self {
+        Self {
+            fired: VecDeque::new(),
+            window,
+            per_source,
+            global,
+            cooldown: None,
+        }
+    }
+
+    /// Keep a source silent for `cooldown` after each of its audible alarms.
+    pub fn with_cooldown(mut self, cooldown: Option<Duration>) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Whether `source` rang less than the cooldown ago.
+    pub fn cooling_down(&self, source: &str, now: Instant) -> bool {
+        self.cooldown.is_some_and(|cooldown| {
+            self.fired
+                .iter()
+                .rev()
+                .find(|(_, s)| s == source)
+                .is_some_and(|(at, _)| now.duration_since(*at) < cooldown)
+        })
+    }
+
+    /// Spend one audible alarm for `source`, returning false if a cap is reached.
+    pub fn try_spend(&mut self, source: &str, now: Instant) -> bool {
+        // Alarms are kept for the cooldown too, but only count within the window
+        let keep = self.cooldown.map_or(self.window, |cooldown| cooldown.max(self.window));
+        while let Some((at, _)) = self.fired.front() {
+            if now.duration_since(*at) >= keep {
+                self.fired.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        let recent: Vec<&str> = self
+            .fired
+            .iter()
+            .filter(|(at, _)| now.duration_since(*at) < self.window)
+            .map(|(_, s)| s.as_str())
+            .collect();
+        if self.global.is_some_and(|cap| recent.len() >= cap) {
+            return false;
+        }
+        let from_source = recent.iter().filter(|s| **s == source).count();
+        if self.per_source.is_some_and(|cap| from_source >= cap) {
+            return false;
+        }
+
+        self.fired.push_back((now, source.to_string()));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unlimited_budget_always_allows() {
+        let mut budget = AlarmBudget::new(None, None, ALARM_BUDGET_WINDOW);
+        let now = Instant::now();
+
+        assert!((0..100).all(|_| budget.try_spend("channel", now)));
+    }
+
+    #[test]
+    fn test_per_source_cap() {
+        let mut budget = AlarmBudget::new(Some(2), None, ALARM_BUDGET_WINDOW);
+        let now = Instant::now();
+
+        assert!(budget.try_spend("channel", now));
+        assert!(budget.try_spend("channel", now));
+        assert!(!budget.try_spend("channel", now));
+        // Other sources have their own allowance
+        assert!(budget.try_spend("stage", now));
+    }
+
+    #[test]
+    fn test_global_cap() {
+        let mut budget = AlarmBudget::new(Some(5), Some(2), ALARM_BUDGET_WINDOW);
+        let now = Instant::now();
+
+        assert!(budget.try_spend("a", now));
+        assert!(budget.try_spend("b", now));
+        assert!(!budget.try_spend("c", now));
+    }
+
+    #[test]
+    fn test_cooldown_outlasts_window() {
+        let mut budget = AlarmBudget::new(None, None, Duration::from_secs(60)).with_cooldown(Some(Duration::from_secs(300)));
+        let start = Instant::now();
+
+        assert!(!budget.cooling_down("channel", start));
+        assert!(budget.try_spend("channel", start));
+        assert!(budget.cooling_down("channel", start + Duration::from_secs(299)));
+        assert!(!budget.cooling_down("stage", start + Duration::from_secs(10)));
+        // A later spend keeps the earlier alarm, which is past the window
+        assert!(budget.try_spend("stage", start + Duration::from_secs(120)));
+        assert!(budget.cooling_down("channel", start + Duration::from_secs(120)));
+        assert!(!budget.cooling_down("channel", start + Duration::from_secs(300)));
+    }
+
+    #[test]
+    fn test_budget_refills_after_window() {
+        let mut budget = AlarmBudget::new(Some(1), None, Duration::from_secs(60));
+        let start = Instant::now();
+
+        assert!(budget.try_spend("channel", start));
+        assert!(!budget.try_spend("channel", start + Duration::from_secs(59)));
+        assert!(budget.try_spend("channel", start + Duration::from_secs(60)));
+    }
+}
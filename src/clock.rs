@@ -0,0 +1,32 @@
+//! Time source for intervals, cooldowns and timeouts.
+//!
+//! Time-based code reads the clock and sleeps through here instead of
+//! `std::time`, so tests can run under tokio's paused time
+//! (`#[tokio::test(start_paused = true)]`) and step through minutes or hours
+//! deterministically instead of sleeping for real.
+
+use std::time::Duration;
+
+pub use tokio::time::Instant;
+
+/// The current instant, frozen while tokio's clock is paused.
+pub fn now() -> Instant {
+    Instant::now()
+}
+
+/// Sleep on the tokio clock.
+pub async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_paused_clock_advances_only_by_sleeps() {
+        let start = now();
+        sleep(Duration::from_secs(3600)).await;
+        assert_eq!(now().duration_since(start), Duration::from_secs(3600));
+    }
+}
@@ -0,0 +1,175 @@
+//! Compound channel-open trigger.
+//!
+//! Shops sometimes flip the channel name early and only post the order details
+//! a little later. With a compound rule, a rename alone does not alarm: the
+//! channel must be renamed to a matching name AND a message containing the
+//! keyword must arrive within the window, in either order.
+
+use crate::clock::Instant;
+use crate::i18n::Alert;
+use std::time::Duration;
+
+/// Default time allowed between the rename and the keyword message.
+pub const DEFAULT_COMPOUND_WINDOW: Duration = Duration::from_secs(120);
+
+/// Longest message excerpt carried in the alert.
+const MESSAGE_EXCERPT_CHARS: usize = 200;
+
+/// Conditions that must both be met within `window`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompoundRule {
+    /// Substring the new channel name must contain; `None` accepts any rename.
+    pub name_contains: Option<String>,
+    /// Case-insensitive substring a message in the channel must contain.
+    pub keyword: String,
+    pub window: Duration,
+}
+
+impl CompoundRule {
+    fn name_matches(&self, name: &str) -> bool {
+        self.name_contains.as_deref().is_none_or(|part| name.contains(part))
+    }
+
+    fn message_matches(&self, content: &str) -> bool {
+        content.to_lowercase().contains(&self.keyword.to_lowercase())
+    }
+}
+
+#[derive(Debug)]
+struct Rename {
+    at: Instant,
+    name: String,
+    previous: Option<String>,
+    changed_by: Option<String>,
+}
+
+/// Pending halves of a compound rule.
+#[derive(Debug)]
+pub struct CompoundTrigger {
+    rule: CompoundRule,
+    rename: Option<Rename>,
+    message: Option<(Instant, String)>,
+}
+
+impl CompoundTrigger {
+    pub fn new(rule: CompoundRule) -> Self {
+        Self {
+            rule,
+            rename: None,
+            message: None,
+        }
+    }
+
+    /// Record a rename of the channel, returning the alert if this completes the rule.
+    ///
+    /// A rename to a non-matching name (e.g. back to ❌) cancels a pending rename.
+    pub fn on_rename(
+        &mut self,
+        now: Instant,
+        previous: Option<&str>,
+        name: &str,
+        changed_by: Option<&str>,
+    ) -> Option<Alert> {
+        self.rename = self.rule.name_matches(name).then(|| Rename {
+            at: now,
+            name: name.to_string(),
+            previous: previous.map(str::to_string),
+            changed_by: changed_by.map(str::to_string),
+        });
+        self.complete(now)
+    }
+
+    /// Record a message in the channel, returning the alert if this completes the rule.
+    pub fn on_message(&mut self, now: Instant, content: &str) -> Option<Alert> {
+        if !self.rule.message_matches(content) {
+            return None;
+        }
+        self.message = Some((now, content.chars().take(MESSAGE_EXCERPT_CHARS).collect()));
+        self.complete(now)
+    }
+
+    /// Drop halves older than the window and fire once both remain.
+    fn complete(&mut self, now: Instant) -> Option<Alert> {
+        let window = self.rule.window;
+        if self.rename.as_ref().is_some_and(|r| now.duration_since(r.at) > window) {
+            self.rename = None;
+        }
+        if self.message.as_ref().is_some_and(|(at, _)| now.duration_since(*at) > window) {
+            self.message = None;
+        }
+        if self.rename.is_none() || self.message.is_none() {
+            return None;
+        }
+
+        let rename = self.rename.take()?;
+        let (_, message) = self.message.take()?;
+        Some(Alert::ChannelOpenWithMessage {
+            name: rename.name,
+            previous: rename.previous,
+            changed_by: rename.changed_by,
+            message,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trigger() -> CompoundTrigger {
+        CompoundTrigger::new(CompoundRule {
+            name_contains: Some("✅".to_string()),
+            keyword: "password".to_string(),
+            window: DEFAULT_COMPOUND_WINDOW,
+        })
+    }
+
+    #[test]
+    fn test_rename_then_message_fires_with_both() {
+        let mut trigger = trigger();
+        let start = Instant::now();
+
+        assert_eq!(trigger.on_rename(start, Some("order-❌"), "order-✅", Some("Shop Keeper")), None);
+        assert_eq!(trigger.on_message(start + Duration::from_secs(10), "hello"), None);
+        assert_eq!(
+            trigger.on_message(start + Duration::from_secs(60), "Password is hunter2"),
+            Some(Alert::ChannelOpenWithMessage {
+                name: "order-✅".to_string(),
+                previous: Some("order-❌".to_string()),
+                changed_by: Some("Shop Keeper".to_string()),
+                message: "Password is hunter2".to_string(),
+            })
+        );
+
+        // Both halves are consumed
+        assert_eq!(trigger.on_message(start + Duration::from_secs(61), "password again"), None);
+    }
+
+    #[test]
+    fn test_message_then_rename_fires() {
+        let mut trigger = trigger();
+        let start = Instant::now();
+
+        assert_eq!(trigger.on_message(start, "PASSWORD: abc"), None);
+        assert!(trigger.on_rename(start + Duration::from_secs(30), None, "order-✅", None).is_some());
+    }
+
+    #[test]
+    fn test_halves_outside_window_do_not_fire() {
+        let mut trigger = trigger();
+        let start = Instant::now();
+
+        trigger.on_rename(start, None, "order-✅", None);
+        assert_eq!(trigger.on_message(start + Duration::from_secs(121), "password"), None);
+    }
+
+    #[test]
+    fn test_non_matching_rename_cancels() {
+        let mut trigger = trigger();
+        let start = Instant::now();
+
+        trigger.on_rename(start, None, "order-✅", None);
+        trigger.on_rename(start + Duration::from_secs(5), Some("order-✅"), "order-❌", None);
+        assert_eq!(trigger.on_message(start + Duration::from_secs(10), "password"), None);
+    }
+}
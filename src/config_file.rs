@@ -0,0 +1,472 @@
+//! TOML config files, and conversion between TOML and `.env`.
+//!
+//! A TOML config holds the same settings as `.env`, keyed by the lowercase
+//! variable name (`discord_token = "..."`). Only flat string, number and
+//! boolean values are used, so a small writer covers the format.
+//!
+//! Monitored channels can instead be listed as `[[channels]]` tables, which
+//! are read into the per-channel settings (`CHANNEL_ID`, `CHANNEL_TITLES`, ...):
+//!
+//! ```toml
+//! [[channels]]
+//! id = "123456789012345678"
+//! title = "ORDERS OPEN"
+//! sound = "/music/orders.mp3"
+//! sinks = ["telegram", "ntfy"]
+//! trigger = "✅|open"
+//! ```
+//!
+//! [`load`] reads `.env` and the TOML file and layers them under the process
+//! environment for [`config::load_config`].
+
+use crate::schema;
+use clap::ValueEnum;
+use ollie_scraper::config::{self, Config, Layer};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use toml::Value;
+
+/// Default name of the TOML config file, looked up in the working directory.
+pub const DEFAULT_TOML_FILE: &str = "ollie-scraper.toml";
+
+/// Config file formats that can be converted between.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Format {
+    Env,
+    Toml,
+}
+
+impl Format {
+    /// Guess the format from a file name: `.toml` files are TOML, anything else `.env`.
+    pub fn from_path(path: &std::path::Path) -> Self {
+        match path.extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => Format::Toml,
+            _ => Format::Env,
+        }
+    }
+
+    /// The format a file is converted to on import.
+    pub fn other(self) -> Self {
+        match self {
+            Format::Env => Format::Toml,
+            Format::Toml => Format::Env,
+        }
+    }
+
+    /// Default file written in this format.
+    pub fn default_path(self) -> &'static str {
+        match self {
+            Format::Env => ".env",
+            Format::Toml => DEFAULT_TOML_FILE,
+        }
+    }
+}
+
+/// Config file chosen with `run --config`, replacing the default TOML file.
+static CONFIG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Paths of the `.env` and TOML files found by the last [`load`].
+static LOADED: Mutex<(Option<PathBuf>, Option<PathBuf>)> = Mutex::new((None, None));
+
+/// Read settings from `path` instead of `ollie-scraper.toml`.
+///
+/// Must be called before [`load`]; later calls are ignored.
+pub fn set_config_path(path: PathBuf) {
+    let _ = CONFIG_PATH.set(path);
+}
+
+/// Path of the config file chosen with `run --config`, if any.
+pub fn config_path() -> Option<&'static Path> {
+    CONFIG_PATH.get().map(PathBuf::as_path)
+}
+
+/// Read a config file, in TOML or `.env` format by its extension.
+///
+/// A missing file is only an error when it was asked for explicitly.
+fn read_config_file(path: &Path, explicit: bool) -> Result<Option<Vec<(String, String)>>, String> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => parse(&contents, Format::from_path(path)).map(Some),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound && !explicit => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Find `.env` in the current directory or one of its parents.
+fn find_dotenv() -> Option<PathBuf> {
+    let dir = std::env::current_dir().ok()?;
+    dir.ancestors().map(|d| d.join(".env")).find(|path| path.is_file())
+}
+
+/// Collect `entries` into a map, keeping the first value of a repeated key as `.env` loading does.
+fn first_wins(entries: Vec<(String, String)>) -> HashMap<String, String> {
+    let mut map = HashMap::new();
+    for (key, value) in entries {
+        map.entry(key).or_insert(value);
+    }
+    map
+}
+
+/// Read the `.env` and config files and layer them under the process environment.
+///
+/// The process environment wins over `.env`, which wins over the TOML file.
+/// Neither file touches the process environment.
+pub fn load() {
+    // A `.env` that fails to parse is ignored as a whole
+    let (dotenv, dotenv_path) = find_dotenv()
+        .and_then(|path| {
+            let entries = dotenvy::from_path_iter(&path).ok()?.collect::<Result<Vec<_>, _>>().ok()?;
+            Some((first_wins(entries), Some(path)))
+        })
+        .unwrap_or_default();
+
+    let path = CONFIG_PATH.get().cloned().unwrap_or_else(|| PathBuf::from(DEFAULT_TOML_FILE));
+    let (toml, toml_path, toml_error) = match read_config_file(&path, CONFIG_PATH.get().is_some()) {
+        Ok(Some(values)) => (first_wins(values), Some(path), None),
+        Ok(None) => (HashMap::new(), None, None),
+        Err(e) => (HashMap::new(), None, Some(format!("Failed to load {}: {}", path.display(), e))),
+    };
+
+    *LOADED.lock().unwrap_or_else(|e| e.into_inner()) = (dotenv_path, toml_path);
+    config::set_layers(vec![
+        Layer {
+            source: ".env",
+            values: dotenv,
+            error: None,
+        },
+        Layer {
+            source: "toml",
+            values: toml,
+            error: toml_error,
+        },
+    ]);
+}
+
+/// Re-read the `.env` and config files, then load the configuration again.
+///
+/// Settings set in the process environment keep their values.
+pub fn reload_config() -> Result<Config, String> {
+    load();
+    config::load_config()
+}
+
+/// Path of the loaded `.env` file, if one was found.
+pub fn dotenv_path() -> Option<PathBuf> {
+    LOADED.lock().unwrap_or_else(|e| e.into_inner()).0.clone()
+}
+
+/// Path of the loaded TOML config file, if one was found.
+pub fn toml_path() -> Option<PathBuf> {
+    LOADED.lock().unwrap_or_else(|e| e.into_inner()).1.clone()
+}
+
+/// Current value of every known setting that is set, from any source.
+pub fn current_values() -> Vec<(String, String)> {
+    schema::settings()
+        .filter_map(|(name, _)| config::setting(name).map(|value| (name.to_string(), value)))
+        .collect()
+}
+
+/// Environment variable name for a TOML key, if it is a known setting.
+fn setting_name(key: &str) -> Option<&'static str> {
+    schema::settings().map(|(name, _)| name).find(|name| name.eq_ignore_ascii_case(key))
+}
+
+/// Read settings from a file in the given format, in file order.
+pub fn parse(contents: &str, format: Format) -> Result<Vec<(String, String)>, String> {
+    match format {
+        Format::Env => parse_env(contents),
+        Format::Toml => parse_toml(contents),
+    }
+}
+
+/// Write settings in the given format.
+pub fn render(values: &[(String, String)], format: Format) -> String {
+    match format {
+        Format::Env => render_env(values),
+        Format::Toml => render_toml(values),
+    }
+}
+
+/// Read `KEY=value` lines, rejecting unknown settings.
+fn parse_env(contents: &str) -> Result<Vec<(String, String)>, String> {
+    let mut values = Vec::new();
+    for (index, raw) in contents.lines().enumerate() {
+        let (key, value) = match schema::parse_env_line(raw) {
+            None => continue,
+            Some(entry) => entry.map_err(|e| format!("line {}: {}", index + 1, e))?,
+        };
+        let name = setting_name(key).ok_or_else(|| format!("line {}: unknown setting {}", index + 1, key))?;
+        values.push((name.to_string(), value.into_owned()));
+    }
+    Ok(values)
+}
+
+/// A TOML config file.
+#[derive(Debug, Deserialize)]
+struct TomlConfig {
+    #[serde(default)]
+    channels: Vec<ChannelTable>,
+    /// Settings by lowercase variable name, in file order.
+    #[serde(flatten)]
+    settings: toml::Table,
+}
+
+/// One `[[channels]]` table.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ChannelTable {
+    /// Channel ID, as a string or an integer.
+    id: Value,
+    title: Option<String>,
+    sound: Option<String>,
+    /// Remote sinks for the channel's alerts; empty sends none.
+    sinks: Option<Vec<String>>,
+    trigger: Option<String>,
+}
+
+/// Settings that `[[channels]]` tables are read into.
+pub const CHANNEL_SETTINGS: [&str; 5] = ["CHANNEL_ID", "CHANNEL_TITLES", "CHANNEL_SOUNDS", "CHANNEL_SINKS", "CHANNEL_TRIGGERS"];
+
+/// Turn `[[channels]]` tables into the comma-separated per-channel settings.
+fn channel_settings(channels: Vec<ChannelTable>) -> Result<Vec<(String, String)>, String> {
+    let mut lists: [Vec<String>; 5] = Default::default();
+    for (index, channel) in channels.into_iter().enumerate() {
+        let context = |e: String| format!("channels[{}]: {}", index, e);
+        let id = match channel.id {
+            Value::String(id) => id,
+            Value::Integer(id) => id.to_string(),
+            other => return Err(context(format!("id must be a channel ID, got {}", other.type_str()))),
+        };
+        // The settings are comma-separated lists
+        let texts = [&channel.title, &channel.sound, &channel.trigger];
+        if let Some(value) = texts.into_iter().flatten().find(|value| value.contains(',')) {
+            return Err(context(format!("'{}' cannot contain a comma", value)));
+        }
+        let sinks = channel.sinks.map(|sinks| if sinks.is_empty() { "none".to_string() } else { sinks.join("+") });
+        let [ids, rest @ ..] = &mut lists;
+        for (list, value) in rest.iter_mut().zip([channel.title, channel.sound, sinks, channel.trigger]) {
+            if let Some(value) = value {
+                list.push(format!("{}={}", id, value));
+            }
+        }
+        ids.push(id);
+    }
+    Ok(CHANNEL_SETTINGS
+        .iter()
+        .zip(lists)
+        .filter(|(_, list)| !list.is_empty())
+        .map(|(name, list)| (name.to_string(), list.join(",")))
+        .collect())
+}
+
+/// Read top-level `key = value` pairs, rejecting tables and unknown settings.
+fn parse_toml(contents: &str) -> Result<Vec<(String, String)>, String> {
+    let config: TomlConfig = toml::from_str(contents).map_err(|e| match e.span() {
+        Some(span) => format!("line {}: {}", contents[..span.start].matches('\n').count() + 1, e.message()),
+        None => e.message().to_string(),
+    })?;
+    let mut values = Vec::new();
+    for (key, value) in config.settings {
+        let value = match value {
+            Value::String(value) => value,
+            Value::Integer(value) => value.to_string(),
+            Value::Float(value) => value.to_string(),
+            Value::Boolean(value) => value.to_string(),
+            Value::Table(_) => return Err(format!("{}: tables are not supported; put settings at the top level", key)),
+            other => return Err(format!("{}: expected a string, number or boolean, got {}", key, other.type_str())),
+        };
+        let name = setting_name(&key).ok_or_else(|| format!("unknown setting {}", key))?;
+        if !config.channels.is_empty() && CHANNEL_SETTINGS.contains(&name) {
+            return Err(format!("{} cannot be combined with [[channels]] tables", key));
+        }
+        values.push((name.to_string(), value));
+    }
+    values.extend(channel_settings(config.channels)?);
+    Ok(values)
+}
+
+/// Quote a value as a TOML basic string.
+fn quote_toml(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04X}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Write every setting with its description; unset settings are left commented out.
+fn render_toml(values: &[(String, String)]) -> String {
+    let mut out = String::from("# ollie-scraper settings\n");
+    out.push_str("# Environment variables override values set here.\n");
+    for (name, description) in schema::settings() {
+        out.push_str(&format!("\n# {}\n", description));
+        let key = name.to_lowercase();
+        match values.iter().find(|(k, _)| k == name) {
+            Some((_, value)) => out.push_str(&format!("{} = {}\n", key, quote_toml(value))),
+            None => out.push_str(&format!("# {} = \"\"\n", key)),
+        }
+    }
+    out
+}
+
+/// Quote a value that dotenv would otherwise split, trim, expand or unescape.
+///
+/// Single quotes keep everything literally but cannot hold a `'` or a line
+/// break, so such values are escaped inside double quotes instead.
+fn quote_env(value: &str) -> String {
+    if value.contains(['\'', '\n', '\r']) {
+        let mut quoted = String::from("\"");
+        for c in value.chars() {
+            match c {
+                '\\' | '"' | '$' => {
+                    quoted.push('\\');
+                    quoted.push(c);
+                }
+                '\n' => quoted.push_str("\\n"),
+                '\r' => quoted.push_str("\\r"),
+                c => quoted.push(c),
+            }
+        }
+        quoted.push('"');
+        quoted
+    } else if value.contains(|c: char| c.is_whitespace() || matches!(c, '#' | '"' | '$' | '\\')) {
+        format!("'{}'", value)
+    } else {
+        value.to_string()
+    }
+}
+
+/// Write `KEY=value` lines in schema order, each preceded by its description.
+fn render_env(values: &[(String, String)]) -> String {
+    let mut out = String::new();
+    for (name, description) in schema::settings() {
+        if let Some((_, value)) = values.iter().find(|(k, _)| k == name) {
+            out.push_str(&format!("# {}\n{}={}\n", description, name, quote_env(value)));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, value: &str) -> (String, String) {
+        (name.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn test_explicit_config_file_must_exist() {
+        let path = std::env::temp_dir().join(format!("ollie-missing-{}.toml", std::process::id()));
+        assert_eq!(read_config_file(&path, false), Ok(None));
+        assert!(read_config_file(&path, true).is_err());
+
+        std::fs::write(&path, "poll_interval = 2.5\nlog_path = \"/var/log/ollie.log\"\n").unwrap();
+        let values = read_config_file(&path, true).unwrap().unwrap();
+        assert_eq!(values[0], ("POLL_INTERVAL".to_string(), "2.5".to_string()));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_env_round_trips_through_toml() {
+        let env = "DISCORD_TOKEN=\"abc.def\"\nexport CHANNEL_ID=123\nSOUND_PATH='/music/alarm one.mp3'\nCOMPOUND_NAME=order-✅\n";
+        let values = parse(env, Format::Env).unwrap();
+
+        let toml = render(&values, Format::Toml);
+        assert!(toml.contains("# The channel ID to monitor, or a comma-separated list\nchannel_id = \"123\"\n"));
+        assert!(toml.contains("# webhook_url = \"\"\n"));
+        assert_eq!(parse(&toml, Format::Toml).unwrap(), values);
+
+        let back = render(&values, Format::Env);
+        assert!(back.contains("SOUND_PATH='/music/alarm one.mp3'\n"));
+        assert_eq!(parse(&back, Format::Env).unwrap(), values);
+    }
+
+    #[test]
+    fn test_env_quoting_round_trips() {
+        let values = vec![
+            entry("DISCORD_TOKEN", "a'b c"),
+            entry("SOUND_PATH", "C:\\alarms\\$HOME \"loud\".mp3"),
+            entry("WEBHOOK_URL", "https://example.com/#x"),
+            entry("COMPOUND_KEYWORD", "it's\nlive"),
+        ];
+        let env = render(&values, Format::Env);
+        assert!(env.contains("DISCORD_TOKEN=\"a'b c\"\n"));
+        assert_eq!(parse(&env, Format::Env).unwrap(), values);
+
+        // The loader reads `.env` with dotenvy, which must agree
+        let loaded: Vec<(String, String)> = dotenvy::from_read_iter(env.as_bytes()).map(Result::unwrap).collect();
+        assert_eq!(loaded, values);
+    }
+
+    #[test]
+    fn test_parse_toml_values() {
+        let toml = "alarm_timeout = 60 # seconds\nDISCORD_TOKEN = 'lit\\eral'\ncompound_keyword = \"say \\\"hi\\\"\\u00e9\"\n";
+        assert_eq!(
+            parse(toml, Format::Toml).unwrap(),
+            vec![
+                entry("ALARM_TIMEOUT", "60"),
+                entry("DISCORD_TOKEN", "lit\\eral"),
+                entry("COMPOUND_KEYWORD", "say \"hi\"é"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_toml_channel_tables() {
+        let toml = r#"
+poll_interval = 2
+[[channels]]
+id = 123
+title = "ORDERS OPEN"
+sinks = ["telegram", "ntfy"]
+[[channels]]
+id = "456"
+sound = "/music/alarm.mp3"
+sinks = []
+trigger = "✅|open"
+"#;
+        assert_eq!(
+            parse(toml, Format::Toml).unwrap(),
+            vec![
+                entry("POLL_INTERVAL", "2"),
+                entry("CHANNEL_ID", "123,456"),
+                entry("CHANNEL_TITLES", "123=ORDERS OPEN"),
+                entry("CHANNEL_SOUNDS", "456=/music/alarm.mp3"),
+                entry("CHANNEL_SINKS", "123=telegram+ntfy,456=none"),
+                entry("CHANNEL_TRIGGERS", "456=✅|open"),
+            ]
+        );
+
+        let err = parse("channel_id = \"1\"\n[[channels]]\nid = 2\n", Format::Toml).unwrap_err();
+        assert_eq!(err, "channel_id cannot be combined with [[channels]] tables");
+        let err = parse("[[channels]]\nid = 2\ntrigger = \"a{1,3}\"\n", Format::Toml).unwrap_err();
+        assert_eq!(err, "channels[0]: 'a{1,3}' cannot contain a comma");
+        let err = parse("[[channels]]\nid = 2\ntitel = \"x\"\n", Format::Toml).unwrap_err();
+        assert!(err.starts_with("line 3: unknown field `titel`"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_toml_reports_line() {
+        assert_eq!(parse("\n[discord]\n", Format::Toml).unwrap_err(), "discord: tables are not supported; put settings at the top level");
+        assert_eq!(parse("chanel_id = \"1\"", Format::Toml).unwrap_err(), "unknown setting chanel_id");
+        assert_eq!(parse("channel_id = 1 2", Format::Toml).unwrap_err(), "line 1: expected newline, `#`");
+        assert_eq!(parse("webhook_url = \"https://x", Format::Toml).unwrap_err(), "line 1: invalid basic string");
+        assert_eq!(parse("channel_id = 1\nchannel_id = 2", Format::Toml).unwrap_err(), "line 2: duplicate key `channel_id` in document root");
+        assert_eq!(
+            parse("role_patterns = [\"a\"]", Format::Toml).unwrap_err(),
+            "role_patterns: expected a string, number or boolean, got array"
+        );
+    }
+}
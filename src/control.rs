@@ -0,0 +1,537 @@
+//! Control socket between the CLI and a running monitor.
+//!
+//! `run` listens on a Unix domain socket next to the executable (loopback TCP
+//! on Windows) and answers through the monitor's [`MonitorHandle`]. Each
+//! connection carries one newline-delimited JSON request and one JSON response.
+
+use crate::config_file;
+use crate::logging::warn;
+use ollie_scraper::monitor::history::AckSource;
+use ollie_scraper::monitor::{MonitorHandle, Stats, StatusSnapshot};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+
+const SOCKET_FILE: &str = "scraper.sock";
+
+/// How long a client has to send its request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Most bytes read from a client, token included.
+const MAX_REQUEST_BYTES: u64 = 64 * 1024;
+
+/// A request sent from the CLI to the running monitor.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum ControlRequest {
+    /// Inject a synthetic CHANNEL_UPDATE into the change pipeline.
+    Simulate {
+        /// Channel to update; defaults to the monitored channel.
+        channel_id: Option<String>,
+        name: String,
+    },
+    /// Report the monitor's live state.
+    Status,
+    /// Silence the ringing alarm.
+    Ack,
+    /// Stop polling and ignore dispatches, for `secs` or until resumed.
+    Pause {
+        #[serde(default)]
+        secs: Option<u64>,
+    },
+    /// End a pause.
+    Resume,
+    /// Stop alarming for a channel (by ID or name) until it is unmuted.
+    Mute { channel: String },
+    /// Resume alarming for a muted channel.
+    Unmute { channel: String },
+    /// Re-read the configuration and restart monitoring with it.
+    Reload,
+    /// Report counters since the monitor started.
+    Stats,
+    /// Shut the monitor down, like SIGTERM.
+    Stop,
+}
+
+/// The monitor's reply to a control request.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ControlResponse {
+    pub ok: bool,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<StatusSnapshot>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<Stats>,
+}
+
+impl ControlResponse {
+    pub fn ok(message: impl Into<String>) -> Self {
+        Self {
+            ok: true,
+            message: message.into(),
+            status: None,
+            stats: None,
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            message: message.into(),
+            status: None,
+            stats: None,
+        }
+    }
+
+    pub fn with_status(status: StatusSnapshot) -> Self {
+        Self {
+            ok: true,
+            message: String::new(),
+            status: Some(status),
+            stats: None,
+        }
+    }
+
+    pub fn with_stats(stats: Stats) -> Self {
+        Self {
+            ok: true,
+            message: String::new(),
+            status: None,
+            stats: Some(stats),
+        }
+    }
+}
+
+/// Get the path to the control socket (in the same directory as the executable).
+pub fn get_socket_path() -> PathBuf {
+    std::env::current_exe()
+        .ok()
+        .and_then(|p| p.parent().map(|p| p.to_path_buf()))
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(SOCKET_FILE)
+}
+
+/// Answer `request` by driving the monitor through `monitor`.
+pub async fn answer(request: ControlRequest, monitor: &MonitorHandle) -> ControlResponse {
+    match request {
+        ControlRequest::Status => match monitor.status().await {
+            Ok(status) => ControlResponse::with_status(status),
+            Err(e) => ControlResponse::error(e),
+        },
+        ControlRequest::Stats => ControlResponse::with_stats(monitor.stats()),
+        ControlRequest::Stop => {
+            monitor.shutdown();
+            ControlResponse::ok("Monitor stopping")
+        }
+        ControlRequest::Reload => match config_file::reload_config() {
+            Ok(mut config) => {
+                // Set from the command line rather than the configuration
+                if let Ok(current) = monitor.config() {
+                    config.health_interval = current.health_interval;
+                }
+                match monitor.reload(config) {
+                    Ok(()) => ControlResponse::ok("Configuration reloaded, monitoring restarts with it"),
+                    Err(e) => ControlResponse::error(e),
+                }
+            }
+            Err(e) => ControlResponse::error(format!("Configuration error, keeping the current one: {}", e)),
+        },
+        ControlRequest::Ack => match monitor.acknowledge(AckSource::Cli) {
+            Ok(true) => ControlResponse::ok("Alarm silenced"),
+            Ok(false) => ControlResponse::ok("No alarm is ringing"),
+            Err(e) => ControlResponse::error(e),
+        },
+        ControlRequest::Pause { secs } => {
+            let duration = secs.map(Duration::from_secs);
+            monitor.pause(duration);
+            match duration {
+                Some(duration) => ControlResponse::ok(format!("Monitoring paused for {}s", duration.as_secs())),
+                None => ControlResponse::ok("Monitoring paused until resumed"),
+            }
+        }
+        ControlRequest::Resume => {
+            if monitor.resume() {
+                ControlResponse::ok("Monitoring resumed")
+            } else {
+                ControlResponse::ok("Monitoring was not paused")
+            }
+        }
+        ControlRequest::Mute { channel } => match monitor.mute(&channel) {
+            Ok(true) => ControlResponse::ok(format!("Muted {}", channel)),
+            Ok(false) => ControlResponse::ok(format!("{} is already muted", channel)),
+            Err(e) => ControlResponse::error(e),
+        },
+        ControlRequest::Unmute { channel } => match monitor.unmute(&channel) {
+            Ok(true) => ControlResponse::ok(format!("Unmuted {}", channel)),
+            Ok(false) => ControlResponse::error(format!("{} is not muted", channel)),
+            Err(e) => ControlResponse::error(e),
+        },
+        ControlRequest::Simulate { channel_id, name } => match monitor.simulate(channel_id, &name).await {
+            Ok(channel_id) => ControlResponse::ok(format!("Injected CHANNEL_UPDATE for {}: {}", channel_id, name)),
+            Err(e) => ControlResponse::error(e),
+        },
+    }
+}
+
+/// Serve the control socket for `monitor` until the task is aborted.
+pub async fn serve_monitor(path: PathBuf, monitor: MonitorHandle) {
+    let result = serve(&path, move |request| {
+        let monitor = monitor.clone();
+        async move { answer(request, &monitor).await }
+    })
+    .await;
+    if let Err(e) = result {
+        warn!("[CTL] Control socket unavailable at {:?}: {}", path, e);
+    }
+}
+
+/// Listen on the control socket, answering each request with `handler`.
+///
+/// A stale socket file left by a previous run is replaced.
+#[cfg(unix)]
+pub async fn serve<F, Fut>(path: &Path, handler: F) -> std::io::Result<()>
+where
+    F: Fn(ControlRequest) -> Fut + Clone + Send + 'static,
+    Fut: Future<Output = ControlResponse> + Send,
+{
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    let listener = tokio::net::UnixListener::bind(path)?;
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let handler = handler.clone();
+        tokio::spawn(async move { answer_connection(stream, None, handler).await });
+    }
+}
+
+#[cfg(not(unix))]
+pub async fn serve<F, Fut>(path: &Path, handler: F) -> std::io::Result<()>
+where
+    F: Fn(ControlRequest) -> Fut + Clone + Send + 'static,
+    Fut: Future<Output = ControlResponse> + Send,
+{
+    tcp::serve(path, handler).await
+}
+
+/// Send a request to the running monitor and wait for its response.
+#[cfg(unix)]
+pub async fn send_request(path: &Path, request: &ControlRequest) -> Result<ControlResponse, String> {
+    let stream = tokio::net::UnixStream::connect(path)
+        .await
+        .map_err(|e| format!("Failed to connect to {:?} (is the daemon running?): {}", path, e))?;
+    exchange(BufReader::new(stream), request).await
+}
+
+#[cfg(not(unix))]
+pub async fn send_request(path: &Path, request: &ControlRequest) -> Result<ControlResponse, String> {
+    tcp::send_request(path, request).await
+}
+
+/// Read one request from `stream`, after checking `token` if set, and write
+/// `handler`'s response.
+///
+/// A client gets [`REQUEST_TIMEOUT`] to send its lines, and nothing past
+/// [`MAX_REQUEST_BYTES`] is read.
+async fn answer_connection<S, F, Fut>(stream: S, token: Option<String>, handler: F)
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    F: Fn(ControlRequest) -> Fut,
+    Fut: Future<Output = ControlResponse>,
+{
+    let (read, mut write) = tokio::io::split(stream);
+    let mut reader = BufReader::new(read.take(MAX_REQUEST_BYTES));
+    let request = match tokio::time::timeout(REQUEST_TIMEOUT, read_request(&mut reader, token.as_deref())).await {
+        Ok(Ok(request)) => request,
+        Ok(Err(response)) => {
+            let _ = write_line(&mut write, &response).await;
+            return;
+        }
+        Err(_) => return,
+    };
+    let response = match serde_json::from_str::<ControlRequest>(&request) {
+        Ok(request) => handler(request).await,
+        Err(e) => ControlResponse::error(format!("Invalid request: {}", e)),
+    };
+    let _ = write_line(&mut write, &response).await;
+}
+
+/// The request line, once the token line matched `token` if set.
+async fn read_request<R>(reader: &mut R, token: Option<&str>) -> Result<String, ControlResponse>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let mut line = String::new();
+    if let Some(token) = token {
+        read_full_line(reader, &mut line).await?;
+        if line.trim_end() != token {
+            return Err(ControlResponse::error("Invalid control token"));
+        }
+        line.clear();
+    }
+    read_full_line(reader, &mut line).await?;
+    Ok(line)
+}
+
+/// Read a line, failing if the connection or the byte limit ends it first.
+async fn read_full_line<R>(reader: &mut R, line: &mut String) -> Result<(), ControlResponse>
+where
+    R: AsyncBufRead + Unpin,
+{
+    match reader.read_line(line).await {
+        Ok(_) if line.ends_with('\n') => Ok(()),
+        Ok(_) => Err(ControlResponse::error(format!("Request must be one line of at most {} bytes", MAX_REQUEST_BYTES))),
+        Err(e) => Err(ControlResponse::error(format!("Failed to read request: {}", e))),
+    }
+}
+
+/// Write `request` to `stream` and read the response.
+async fn exchange<S>(mut stream: BufReader<S>, request: &ControlRequest) -> Result<ControlResponse, String>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    write_line(&mut stream, request).await.map_err(|e| format!("Failed to send request: {}", e))?;
+
+    let mut line = String::new();
+    stream
+        .read_line(&mut line)
+        .await
+        .map_err(|e| format!("Failed to read response: {}", e))?;
+
+    serde_json::from_str(&line).map_err(|e| format!("Invalid response from daemon: {}", e))
+}
+
+/// Write `value` as one line of JSON.
+async fn write_line<S, T>(stream: &mut S, value: &T) -> std::io::Result<()>
+where
+    S: AsyncWrite + Unpin,
+    T: Serialize,
+{
+    let mut json = serde_json::to_string(value).map_err(std::io::Error::other)?;
+    json.push('\n');
+    stream.write_all(json.as_bytes()).await
+}
+
+/// Loopback TCP in place of the Unix socket, for Windows.
+///
+/// The socket file holds the listening address and a random token on two
+/// lines. A client sends the token before its request, so only users who can
+/// read the file can drive the monitor.
+#[cfg(any(not(unix), test))]
+mod tcp {
+    use super::*;
+    use rand::distributions::{Alphanumeric, DistString};
+    use tokio::net::{TcpListener, TcpStream};
+
+    const TOKEN_LEN: usize = 32;
+
+    pub async fn serve<F, Fut>(path: &Path, handler: F) -> std::io::Result<()>
+    where
+        F: Fn(ControlRequest) -> Fut + Clone + Send + 'static,
+        Fut: Future<Output = ControlResponse> + Send,
+    {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let token = Alphanumeric.sample_string(&mut rand::thread_rng(), TOKEN_LEN);
+        // Replaces a stale file left by a previous run
+        std::fs::write(path, format!("{}\n{}\n", listener.local_addr()?, token))?;
+
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let handler = handler.clone();
+            let token = token.clone();
+            tokio::spawn(async move { answer_connection(stream, Some(token), handler).await });
+        }
+    }
+
+    pub async fn send_request(path: &Path, request: &ControlRequest) -> Result<ControlResponse, String> {
+        let not_running = |e: String| format!("Failed to connect to {:?} (is the daemon running?): {}", path, e);
+        let contents = std::fs::read_to_string(path).map_err(|e| not_running(e.to_string()))?;
+        let mut lines = contents.lines();
+        let (addr, token) = match (lines.next(), lines.next()) {
+            (Some(addr), Some(token)) => (addr, token),
+            _ => return Err(not_running("the socket file is incomplete".to_string())),
+        };
+        let mut stream = TcpStream::connect(addr).await.map_err(|e| not_running(e.to_string()))?;
+        stream
+            .write_all(format!("{}\n", token).as_bytes())
+            .await
+            .map_err(|e| format!("Failed to send request: {}", e))?;
+        exchange(BufReader::new(stream), request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_request_wire_format() {
+        let request = ControlRequest::Simulate {
+            channel_id: None,
+            name: "start-order-✅".to_string(),
+        };
+
+        let json = serde_json::to_string(&request).expect("Failed to serialize request");
+        let value: serde_json::Value = serde_json::from_str(&json).expect("Invalid JSON");
+        assert_eq!(value["command"], "simulate");
+        assert_eq!(value["name"], "start-order-✅");
+
+        let parsed: ControlRequest = serde_json::from_str(&json).expect("Failed to parse request");
+        assert_eq!(parsed, request);
+
+        assert_eq!(serde_json::to_string(&ControlRequest::Stop).unwrap(), r#"{"command":"stop"}"#);
+    }
+
+    #[test]
+    fn test_status_response_wire_format() {
+        let response = ControlResponse::with_status(StatusSnapshot {
+            channel_name: Some("start-order-❌".to_string()),
+            channels: BTreeMap::from([
+                ("100".to_string(), "start-order-❌".to_string()),
+                ("200".to_string(), "drops-❌".to_string()),
+            ]),
+            ws_connected: true,
+            heartbeat_latency_ms: Some(42),
+            last_poll_secs: Some(1),
+            alarm_active: true,
+            alarms: vec!["CHANNEL OPEN: Channel is now: start-order-✅".to_string()],
+            alarm_ids: vec![1700000000000],
+            initial_fetch_done: true,
+            initial_fetch_error: None,
+            ws_error: Some("closed with 4004: Authentication failed.".to_string()),
+            muted: vec!["100".to_string()],
+            paused: true,
+            paused_secs_left: Some(3600),
+        });
+
+        let json = serde_json::to_string(&response).expect("Failed to serialize response");
+        let parsed: ControlResponse = serde_json::from_str(&json).expect("Failed to parse response");
+        assert_eq!(parsed, response);
+
+        // Plain responses omit the status field entirely
+        let json = serde_json::to_string(&ControlResponse::ok("done")).expect("Failed to serialize response");
+        assert!(!json.contains("status"));
+
+        let response = ControlResponse::with_stats(Stats {
+            polls: 40,
+            renames: 2,
+            ..Default::default()
+        });
+        let json = serde_json::to_string(&response).expect("Failed to serialize response");
+        assert!(!json.contains("status"));
+        assert_eq!(serde_json::from_str::<ControlResponse>(&json).expect("Failed to parse response"), response);
+
+        // Snapshots from an older daemon still parse
+        let old = r#"{"channel_name":null,"ws_connected":false,"last_poll_secs":null,"alarm_active":false}"#;
+        let snapshot: StatusSnapshot = serde_json::from_str(old).expect("Failed to parse old snapshot");
+        assert!(!snapshot.initial_fetch_done);
+        assert_eq!(snapshot.ws_error, None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_request_round_trip_over_socket() {
+        let path = std::env::temp_dir().join(format!("ollie-control-test-{}.sock", std::process::id()));
+
+        let server_path = path.clone();
+        let server = tokio::spawn(async move {
+            serve(&server_path, |request| async move {
+                match request {
+                    ControlRequest::Simulate { name, .. } => ControlResponse::ok(format!("got {}", name)),
+                    _ => ControlResponse::error("unexpected"),
+                }
+            })
+            .await
+        });
+
+        // Wait for the listener to bind
+        for _ in 0..50 {
+            if path.exists() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+
+        let request = ControlRequest::Simulate {
+            channel_id: Some("1".to_string()),
+            name: "open".to_string(),
+        };
+        let response = send_request(&path, &request).await.expect("Request failed");
+        assert_eq!(response, ControlResponse::ok("got open"));
+
+        server.abort();
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_connection_caps_request_size_and_time() {
+        let handler = |_request| async { ControlResponse::ok("handled") };
+
+        let (client, server) = tokio::io::duplex(4096);
+        let server = tokio::spawn(answer_connection(server, None, handler));
+        let (read, mut write) = tokio::io::split(client);
+        let flood = tokio::spawn(async move {
+            let chunk = [b'x'; 1024];
+            while write.write_all(&chunk).await.is_ok() {}
+        });
+        let mut line = String::new();
+        BufReader::new(read).read_line(&mut line).await.unwrap();
+        let response: ControlResponse = serde_json::from_str(&line).unwrap();
+        assert_eq!(response, ControlResponse::error("Request must be one line of at most 65536 bytes"));
+        server.await.unwrap();
+        flood.abort();
+
+        // A client that never sends its request is dropped
+        let (_client, server) = tokio::io::duplex(4096);
+        let started = tokio::time::Instant::now();
+        answer_connection(server, None, handler).await;
+        assert!(started.elapsed() >= REQUEST_TIMEOUT);
+    }
+
+    #[tokio::test]
+    async fn test_request_round_trip_over_tcp() {
+        let path = std::env::temp_dir().join(format!("ollie-control-tcp-test-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let server_path = path.clone();
+        let server = tokio::spawn(async move {
+            tcp::serve(&server_path, |request| async move {
+                match request {
+                    ControlRequest::Stop => ControlResponse::ok("stopping"),
+                    _ => ControlResponse::error("unexpected"),
+                }
+            })
+            .await
+        });
+
+        // Wait for the listener to write its address and token
+        for _ in 0..50 {
+            if std::fs::read_to_string(&path).is_ok_and(|contents| contents.lines().count() == 2) {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+
+        let response = tcp::send_request(&path, &ControlRequest::Stop).await.expect("Request failed");
+        assert_eq!(response, ControlResponse::ok("stopping"));
+
+        // A client that cannot read the token is turned away
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let addr = contents.lines().next().unwrap();
+        let mut stream = BufReader::new(tokio::net::TcpStream::connect(addr).await.unwrap());
+        stream.write_all(b"guess\n").await.unwrap();
+        let mut line = String::new();
+        stream.read_line(&mut line).await.unwrap();
+        let response: ControlResponse = serde_json::from_str(&line).unwrap();
+        assert_eq!(response, ControlResponse::error("Invalid control token"));
+
+        server.abort();
+        std::fs::remove_file(&path).unwrap();
+        let err = tcp::send_request(&path, &ControlRequest::Stop).await.unwrap_err();
+        assert!(err.contains("is the daemon running?"), "{}", err);
+    }
+}
@@ -0,0 +1,106 @@
+//! Guild-wide channel discovery.
+//!
+//! With `DISCOVER_PATTERN` and `GUILD_ID` set, every channel of the guild
+//! whose name matches the pattern is monitored alongside `CHANNEL_ID`, under
+//! the default trigger and message rules. The guild's channels are listed over
+//! REST when monitoring starts and rescanned every few minutes, and the
+//! Gateway's CHANNEL_CREATE, CHANNEL_UPDATE and CHANNEL_DELETE keep the set
+//! current in between, so a channel deleted and recreated under a new ID is
+//! picked up again. A matching channel that appears later counts as a rename
+//! from nothing, so it alarms if its name passes the trigger rule.
+
+use crate::models::MonitorTarget;
+use regex::Regex;
+use std::sync::{Arc, RwLock};
+
+/// The discovery pattern and the channels it has found, shared by every loop
+/// of a monitoring session.
+#[derive(Debug, Clone)]
+pub struct Discovery {
+    pattern: Regex,
+    /// Rules given to each discovered channel.
+    template: MonitorTarget,
+    found: Arc<RwLock<Vec<MonitorTarget>>>,
+}
+
+impl Discovery {
+    /// Discover channels matching `pattern`, monitoring them like `template`.
+    pub fn new(pattern: Regex, template: MonitorTarget) -> Self {
+        Self {
+            pattern,
+            template,
+            found: Arc::default(),
+        }
+    }
+
+    pub fn pattern(&self) -> &str {
+        self.pattern.as_str()
+    }
+
+    /// Whether a channel called `name` should be monitored.
+    pub fn matches(&self, name: &str) -> bool {
+        self.pattern.is_match(name)
+    }
+
+    /// Channels found so far, in the order they were found.
+    pub fn targets(&self) -> Vec<MonitorTarget> {
+        self.found.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn contains(&self, channel_id: &str) -> bool {
+        self.found.read().unwrap_or_else(|e| e.into_inner()).iter().any(|t| t.channel_id == channel_id)
+    }
+
+    /// Start monitoring `channel_id`; returns its target unless it was already found.
+    pub fn add(&self, channel_id: &str) -> Option<MonitorTarget> {
+        let mut found = self.found.write().unwrap_or_else(|e| e.into_inner());
+        if found.iter().any(|t| t.channel_id == channel_id) {
+            return None;
+        }
+        let target = MonitorTarget {
+            channel_id: channel_id.to_string(),
+            ..self.template.clone()
+        };
+        found.push(target.clone());
+        Some(target)
+    }
+
+    /// Stop monitoring `channel_id`; returns whether it had been found.
+    pub fn remove(&self, channel_id: &str) -> bool {
+        let mut found = self.found.write().unwrap_or_else(|e| e.into_inner());
+        let before = found.len();
+        found.retain(|t| t.channel_id != channel_id);
+        found.len() != before
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trigger::TriggerRule;
+
+    #[test]
+    fn test_discovered_channels_share_the_template_rules() {
+        let template = MonitorTarget {
+            rule: TriggerRule {
+                trigger: Some(Regex::new("✅").unwrap()),
+                ignore: None,
+            },
+            ..Default::default()
+        };
+        let discovery = Discovery::new(Regex::new("(?i)order").unwrap(), template);
+        assert!(discovery.matches("start-ORDER-❌"));
+        assert!(!discovery.matches("general"));
+
+        let shared = discovery.clone();
+        let target = discovery.add("100").unwrap();
+        assert_eq!(target.channel_id, "100");
+        assert_eq!(target.rule.trigger_pattern(), Some("✅"));
+        assert!(discovery.add("100").is_none());
+        assert!(shared.contains("100"));
+
+        assert!(shared.remove("100"));
+        assert!(!shared.remove("100"));
+        assert!(discovery.targets().is_empty());
+    }
+}
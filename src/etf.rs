@@ -0,0 +1,298 @@
+//! Erlang External Term Format, the Gateway's binary encoding.
+//!
+//! Only the terms Discord sends are supported, and they are mapped onto
+//! `serde_json::Value` so the rest of the monitor handles both encodings
+//! alike: atoms `nil`, `true` and `false` become null and booleans, other
+//! atoms and binaries become strings, tuples and lists become arrays, and maps
+//! become objects. Integers too large for 32 bits, which is how Discord sends
+//! snowflakes, become decimal strings as in the JSON encoding.
+
+use serde_json::{Map, Number, Value};
+
+const VERSION: u8 = 131;
+const NEW_FLOAT_EXT: u8 = 70;
+const SMALL_INTEGER_EXT: u8 = 97;
+const INTEGER_EXT: u8 = 98;
+const FLOAT_EXT: u8 = 99;
+const ATOM_EXT: u8 = 100;
+const SMALL_TUPLE_EXT: u8 = 104;
+const LARGE_TUPLE_EXT: u8 = 105;
+const NIL_EXT: u8 = 106;
+const STRING_EXT: u8 = 107;
+const LIST_EXT: u8 = 108;
+const BINARY_EXT: u8 = 109;
+const SMALL_BIG_EXT: u8 = 110;
+const LARGE_BIG_EXT: u8 = 111;
+const SMALL_ATOM_EXT: u8 = 115;
+const MAP_EXT: u8 = 116;
+const ATOM_UTF8_EXT: u8 = 118;
+const SMALL_ATOM_UTF8_EXT: u8 = 119;
+
+/// Decode one term, e.g. a Gateway payload.
+pub fn decode(bytes: &[u8]) -> Result<Value, String> {
+    let mut reader = Reader { bytes, pos: 0 };
+    if reader.u8()? != VERSION {
+        return Err("ETF payload does not start with version 131".to_string());
+    }
+    let value = reader.term()?;
+    if reader.pos != bytes.len() {
+        return Err(format!("{} trailing byte(s) after ETF term", bytes.len() - reader.pos));
+    }
+    Ok(value)
+}
+
+/// Encode `value` as one term, with object keys as binaries.
+pub fn encode(value: &Value) -> Vec<u8> {
+    let mut out = vec![VERSION];
+    write_term(&mut out, value);
+    out
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8], String> {
+        let end = self.pos.checked_add(len).filter(|&end| end <= self.bytes.len());
+        let end = end.ok_or_else(|| format!("ETF term truncated at byte {}", self.pos))?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<usize, String> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]) as usize)
+    }
+
+    fn u32(&mut self) -> Result<usize, String> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+    }
+
+    fn text(&mut self, len: usize) -> Result<String, String> {
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| "ETF text is not valid UTF-8".to_string())
+    }
+
+    fn terms(&mut self, len: usize) -> Result<Vec<Value>, String> {
+        // Each term takes at least a byte, so a bogus length fails instead of allocating
+        let mut values = Vec::with_capacity(len.min(self.bytes.len() - self.pos));
+        for _ in 0..len {
+            values.push(self.term()?);
+        }
+        Ok(values)
+    }
+
+    fn term(&mut self) -> Result<Value, String> {
+        match self.u8()? {
+            SMALL_INTEGER_EXT => Ok(Value::from(self.u8()?)),
+            INTEGER_EXT => {
+                let bytes = self.take(4)?;
+                Ok(Value::from(i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])))
+            }
+            NEW_FLOAT_EXT => {
+                let bytes: [u8; 8] = self.take(8)?.try_into().expect("took 8 bytes");
+                Ok(Number::from_f64(f64::from_be_bytes(bytes)).map_or(Value::Null, Value::Number))
+            }
+            FLOAT_EXT => {
+                let text = String::from_utf8_lossy(self.take(31)?).trim_end_matches('\0').to_string();
+                let float: f64 = text.trim().parse().map_err(|_| format!("Invalid ETF float '{}'", text))?;
+                Ok(Number::from_f64(float).map_or(Value::Null, Value::Number))
+            }
+            ATOM_EXT | ATOM_UTF8_EXT => {
+                let len = self.u16()?;
+                Ok(atom(self.text(len)?))
+            }
+            SMALL_ATOM_EXT | SMALL_ATOM_UTF8_EXT => {
+                let len = self.u8()? as usize;
+                Ok(atom(self.text(len)?))
+            }
+            SMALL_TUPLE_EXT => {
+                let len = self.u8()? as usize;
+                Ok(Value::Array(self.terms(len)?))
+            }
+            LARGE_TUPLE_EXT => {
+                let len = self.u32()?;
+                Ok(Value::Array(self.terms(len)?))
+            }
+            NIL_EXT => Ok(Value::Array(Vec::new())),
+            STRING_EXT => {
+                let len = self.u16()?;
+                Ok(Value::Array(self.take(len)?.iter().map(|&b| Value::from(b)).collect()))
+            }
+            LIST_EXT => {
+                let len = self.u32()?;
+                let values = self.terms(len)?;
+                // Proper lists end in an empty list
+                match self.term()? {
+                    Value::Array(tail) if tail.is_empty() => Ok(Value::Array(values)),
+                    _ => Err("Improper ETF lists are not supported".to_string()),
+                }
+            }
+            BINARY_EXT => {
+                let len = self.u32()?;
+                Ok(Value::String(self.text(len)?))
+            }
+            SMALL_BIG_EXT => {
+                let len = self.u8()? as usize;
+                self.big(len)
+            }
+            LARGE_BIG_EXT => {
+                let len = self.u32()?;
+                self.big(len)
+            }
+            MAP_EXT => {
+                let len = self.u32()?;
+                let mut map = Map::new();
+                for _ in 0..len {
+                    let key = match self.term()? {
+                        Value::String(key) => key,
+                        key => key.to_string(),
+                    };
+                    map.insert(key, self.term()?);
+                }
+                Ok(Value::Object(map))
+            }
+            tag => Err(format!("Unsupported ETF tag {}", tag)),
+        }
+    }
+
+    /// A sign byte and `len` little-endian digits, as a decimal string.
+    fn big(&mut self, len: usize) -> Result<Value, String> {
+        let negative = self.u8()? != 0;
+        let digits = self.take(len)?;
+        if digits.iter().skip(16).any(|&b| b != 0) {
+            return Err("ETF integer does not fit in 128 bits".to_string());
+        }
+        let magnitude = digits.iter().take(16).rev().fold(0u128, |acc, &b| (acc << 8) | u128::from(b));
+        Ok(Value::String(if negative { format!("-{}", magnitude) } else { magnitude.to_string() }))
+    }
+}
+
+/// Atoms the Gateway uses for JSON's literals, any other atom as a string.
+fn atom(name: String) -> Value {
+    match name.as_str() {
+        "nil" | "null" => Value::Null,
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ => Value::String(name),
+    }
+}
+
+fn write_atom(out: &mut Vec<u8>, name: &str) {
+    out.push(SMALL_ATOM_UTF8_EXT);
+    out.push(name.len() as u8);
+    out.extend_from_slice(name.as_bytes());
+}
+
+fn write_binary(out: &mut Vec<u8>, text: &str) {
+    out.push(BINARY_EXT);
+    out.extend_from_slice(&(text.len() as u32).to_be_bytes());
+    out.extend_from_slice(text.as_bytes());
+}
+
+fn write_term(out: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Null => write_atom(out, "nil"),
+        Value::Bool(b) => write_atom(out, if *b { "true" } else { "false" }),
+        Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            (Some(i @ 0..=255), _) => out.extend_from_slice(&[SMALL_INTEGER_EXT, i as u8]),
+            (Some(i), _) if i32::try_from(i).is_ok() => {
+                out.push(INTEGER_EXT);
+                out.extend_from_slice(&(i as i32).to_be_bytes());
+            }
+            (i, u) if i.is_some() || u.is_some() => {
+                let negative = i.is_some_and(|i| i < 0);
+                let magnitude = u.unwrap_or_else(|| i.unwrap_or_default().unsigned_abs());
+                let digits: Vec<u8> = magnitude.to_le_bytes().into_iter().collect();
+                let len = digits.iter().rposition(|&b| b != 0).map_or(0, |last| last + 1);
+                out.extend_from_slice(&[SMALL_BIG_EXT, len as u8, u8::from(negative)]);
+                out.extend_from_slice(&digits[..len]);
+            }
+            _ => {
+                out.push(NEW_FLOAT_EXT);
+                out.extend_from_slice(&n.as_f64().unwrap_or_default().to_be_bytes());
+            }
+        },
+        Value::String(s) => write_binary(out, s),
+        Value::Array(values) => {
+            if !values.is_empty() {
+                out.push(LIST_EXT);
+                out.extend_from_slice(&(values.len() as u32).to_be_bytes());
+                for value in values {
+                    write_term(out, value);
+                }
+            }
+            out.push(NIL_EXT);
+        }
+        Value::Object(map) => {
+            out.push(MAP_EXT);
+            out.extend_from_slice(&(map.len() as u32).to_be_bytes());
+            for (key, value) in map {
+                write_binary(out, key);
+                write_term(out, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_decodes_erlang_terms() {
+        // term_to_binary(#{op => 0, t => 'CHANNEL_UPDATE', d => #{id => 1234567890123456789, name => <<"a">>}})
+        let mut bytes = vec![VERSION, MAP_EXT, 0, 0, 0, 3];
+        bytes.extend([SMALL_ATOM_UTF8_EXT, 2, b'o', b'p', SMALL_INTEGER_EXT, 0]);
+        bytes.extend([SMALL_ATOM_UTF8_EXT, 1, b't', SMALL_ATOM_UTF8_EXT, 14]);
+        bytes.extend(b"CHANNEL_UPDATE");
+        bytes.extend([SMALL_ATOM_UTF8_EXT, 1, b'd', MAP_EXT, 0, 0, 0, 2]);
+        bytes.extend([SMALL_ATOM_UTF8_EXT, 2, b'i', b'd', SMALL_BIG_EXT, 8, 0]);
+        bytes.extend(1234567890123456789u64.to_le_bytes());
+        bytes.extend([BINARY_EXT, 0, 0, 0, 4, b'n', b'a', b'm', b'e', BINARY_EXT, 0, 0, 0, 1, b'a']);
+
+        assert_eq!(
+            decode(&bytes).unwrap(),
+            json!({ "op": 0, "t": "CHANNEL_UPDATE", "d": { "id": "1234567890123456789", "name": "a" } })
+        );
+    }
+
+    #[test]
+    fn test_round_trips_json_values() {
+        let value = json!({
+            "op": 2,
+            "d": {
+                "token": "secret",
+                "seq": 70000,
+                "big": 5_000_000_000u64,
+                "negative": -3,
+                "ratio": 0.5,
+                "compress": false,
+                "presence": null,
+                "list": ["a", 1],
+                "empty": []
+            }
+        });
+        let mut expected = value.clone();
+        // Integers beyond 32 bits come back as strings, like snowflakes
+        expected["d"]["big"] = json!("5000000000");
+        assert_eq!(decode(&encode(&value)).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_rejects_malformed_terms() {
+        assert!(decode(&[VERSION, BINARY_EXT, 0, 0, 0, 9, b'a']).unwrap_err().contains("truncated"));
+        assert!(decode(&[VERSION, SMALL_INTEGER_EXT, 1, 2]).unwrap_err().contains("trailing"));
+        assert!(decode(&[0, NIL_EXT]).is_err());
+        assert!(decode(&[VERSION, 77]).unwrap_err().contains("Unsupported"));
+    }
+}
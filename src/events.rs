@@ -0,0 +1,97 @@
+//! Typed events published by a running monitor.
+//!
+//! The polling and Gateway loops only detect changes; everything they find
+//! is broadcast as a [`MonitorEvent`]. The notifier is one subscriber, and an
+//! embedding application can subscribe next to it through
+//! [`Monitor::subscribe`](crate::monitor::Monitor::subscribe).
+
+use crate::i18n::Alert;
+use tokio::sync::broadcast;
+
+pub use crate::rest::RestError;
+pub use crate::trigger::TriggerMatch;
+
+/// Events buffered per subscriber before a slow one starts missing them.
+pub const EVENT_CAPACITY: usize = 256;
+
+/// Sending half of the event channel, cloned into every loop.
+pub type EventSender = broadcast::Sender<MonitorEvent>;
+
+/// A fresh event channel with room for [`EVENT_CAPACITY`] events per subscriber.
+pub fn channel() -> EventSender {
+    broadcast::channel(EVENT_CAPACITY).0
+}
+
+/// Something the monitor noticed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MonitorEvent {
+    /// A monitored channel was renamed.
+    NameChanged {
+        channel_id: String,
+        old_name: Option<String>,
+        new_name: String,
+        /// Who renamed the channel, when the source knows it.
+        changed_by: Option<String>,
+        /// Where the change was seen: `POLL`, `WS`, `AUDIT`, `DISCOVER` or `SIM`.
+        source: String,
+        /// Whether the new name passed the channel's trigger rule.
+        alarm: bool,
+        /// What the trigger rule matched, if it has a pattern.
+        trigger: Option<TriggerMatch>,
+    },
+    /// A message arrived in a monitored channel.
+    MessageReceived {
+        channel_id: String,
+        author: String,
+        content: String,
+    },
+    /// A message passed its channel's message filters.
+    MessageMatched {
+        channel_id: String,
+        channel_name: String,
+        author: String,
+        content: String,
+        trigger: Option<TriggerMatch>,
+    },
+    /// A guild-level alert, e.g. a stage going live or a member surge.
+    Alert(Alert),
+    /// The Gateway session became usable.
+    GatewayConnected { resumed: bool },
+    /// The Gateway connection failed or closed.
+    GatewayDisconnected { reason: String },
+    /// A REST poll of a channel failed.
+    PollError { channel_id: String, error: RestError },
+    /// Monitoring restarted with a reloaded configuration.
+    Reloaded,
+    /// The notifier raised an alert that got past the mutes and the compound
+    /// rule, whether it rang or was sent silently.
+    AlarmRaised {
+        channel_id: Option<String>,
+        alert: Alert,
+        /// Where a rename alert's change was seen, when known.
+        source: Option<String>,
+    },
+}
+
+/// Publish `event`; having no subscribers is fine.
+pub fn emit(events: &EventSender, event: MonitorEvent) {
+    let _ = events.send(event);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_every_subscriber_sees_events() {
+        let events = channel();
+        // Nobody listens yet
+        emit(&events, MonitorEvent::Reloaded);
+
+        let mut first = events.subscribe();
+        let mut second = events.subscribe();
+        emit(&events, MonitorEvent::GatewayConnected { resumed: false });
+        assert_eq!(first.recv().await.unwrap(), MonitorEvent::GatewayConnected { resumed: false });
+        assert_eq!(second.recv().await.unwrap(), MonitorEvent::GatewayConnected { resumed: false });
+    }
+}
@@ -0,0 +1,281 @@
+//! Gateway wire format: payload encoding and transport compression.
+//!
+//! By default payloads are plain JSON text frames. `GATEWAY_ENCODING=etf`
+//! switches to Erlang terms in binary frames, and
+//! `GATEWAY_COMPRESSION=zlib-stream` makes Discord compress everything it
+//! sends with one zlib stream per connection, as the official clients do. What
+//! we send is never compressed.
+
+use crate::etf;
+use crate::models::GatewayMessage;
+use flate2::{Decompress, FlushDecompress};
+use tokio_tungstenite::tungstenite::Message;
+
+/// Every complete zlib-stream message ends with a sync flush marker.
+const ZLIB_SUFFIX: [u8; 4] = [0x00, 0x00, 0xff, 0xff];
+
+/// How payloads are encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    #[default]
+    Json,
+    /// Erlang External Term Format.
+    Etf,
+}
+
+impl Encoding {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "json" => Some(Encoding::Json),
+            "etf" => Some(Encoding::Etf),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Encoding::Json => "json",
+            Encoding::Etf => "etf",
+        }
+    }
+}
+
+/// How the Gateway compresses what it sends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    #[default]
+    None,
+    /// One zlib stream for the whole connection.
+    ZlibStream,
+}
+
+impl Compression {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "none" => Some(Compression::None),
+            "zlib-stream" => Some(Compression::ZlibStream),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Compression::None => "none",
+            Compression::ZlibStream => "zlib-stream",
+        }
+    }
+}
+
+/// Encoding and compression asked for when connecting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Transport {
+    pub encoding: Encoding,
+    pub compression: Compression,
+}
+
+impl Transport {
+    /// `url` with the transport's `encoding` and `compress` query parameters;
+    /// unchanged for the default of uncompressed JSON.
+    pub(crate) fn apply(&self, url: &str) -> String {
+        if *self == Transport::default() {
+            return url.to_string();
+        }
+        let Ok(mut parsed) = reqwest::Url::parse(url) else {
+            return url.to_string();
+        };
+        let kept: Vec<(String, String)> = parsed
+            .query_pairs()
+            .filter(|(key, _)| key != "encoding" && key != "compress")
+            .map(|(key, value)| (key.into_owned(), value.into_owned()))
+            .collect();
+        {
+            let mut query = parsed.query_pairs_mut();
+            query.clear().extend_pairs(kept).append_pair("encoding", self.encoding.as_str());
+            if self.compression == Compression::ZlibStream {
+                query.append_pair("compress", "zlib-stream");
+            }
+        }
+        parsed.to_string()
+    }
+
+    /// A codec for one connection.
+    pub(crate) fn codec(&self) -> Codec {
+        Codec {
+            encoding: self.encoding,
+            inflater: (self.compression == Compression::ZlibStream).then(Inflater::new),
+        }
+    }
+}
+
+/// Inflates a zlib-stream, one Gateway message at a time.
+pub struct Inflater {
+    inflate: Decompress,
+    buffer: Vec<u8>,
+}
+
+impl Default for Inflater {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Inflater {
+    pub fn new() -> Self {
+        Self {
+            inflate: Decompress::new(true),
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Add a frame; returns the inflated message once its last frame has arrived.
+    pub fn push(&mut self, frame: &[u8]) -> Result<Option<Vec<u8>>, String> {
+        self.buffer.extend_from_slice(frame);
+        if !self.buffer.ends_with(&ZLIB_SUFFIX) {
+            return Ok(None);
+        }
+        let input = std::mem::take(&mut self.buffer);
+        let mut output = Vec::with_capacity(input.len() * 4);
+        let mut consumed = 0;
+        loop {
+            if output.len() == output.capacity() {
+                output.reserve(output.capacity().max(1024));
+            }
+            let before = self.inflate.total_in();
+            self.inflate
+                .decompress_vec(&input[consumed..], &mut output, FlushDecompress::Sync)
+                .map_err(|e| format!("Failed to inflate Gateway message: {}", e))?;
+            consumed += (self.inflate.total_in() - before) as usize;
+            // Done once all input is in and the output did not fill up
+            if consumed >= input.len() && output.len() < output.capacity() {
+                return Ok(Some(output));
+            }
+        }
+    }
+}
+
+/// Turns frames into Gateway messages and back for one connection.
+pub struct Codec {
+    encoding: Encoding,
+    inflater: Option<Inflater>,
+}
+
+impl Codec {
+    /// The payload of a data frame; `Ok(None)` while a compressed message is incomplete.
+    ///
+    /// Fails if the compressed stream is corrupt, after which the connection is useless.
+    pub fn inflate(&mut self, message: &Message) -> Result<Option<Vec<u8>>, String> {
+        match (message, &mut self.inflater) {
+            (Message::Binary(frame), Some(inflater)) => inflater.push(frame),
+            (Message::Binary(frame), None) => Ok(Some(frame.clone())),
+            (Message::Text(text), _) => Ok(Some(text.as_bytes().to_vec())),
+            _ => Err("Not a data frame".to_string()),
+        }
+    }
+
+    /// Parse a payload from [`inflate`](Self::inflate).
+    pub fn parse(&self, payload: &[u8]) -> Result<GatewayMessage, String> {
+        match self.encoding {
+            Encoding::Json => serde_json::from_slice(payload).map_err(|e| e.to_string()),
+            Encoding::Etf => serde_json::from_value(etf::decode(payload)?).map_err(|e| e.to_string()),
+        }
+    }
+
+    /// Inflate and parse a data frame; `Ok(None)` while a compressed message is incomplete.
+    pub fn decode(&mut self, message: &Message) -> Result<Option<GatewayMessage>, String> {
+        match self.inflate(message)? {
+            Some(payload) => self.parse(&payload).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Encode a message to send.
+    pub fn encode(&self, message: &GatewayMessage) -> Message {
+        match self.encoding {
+            Encoding::Json => {
+                Message::Text(serde_json::to_string(message).expect("Failed to serialize Gateway payload"))
+            }
+            Encoding::Etf => Message::Binary(etf::encode(
+                &serde_json::to_value(message).expect("Failed to serialize Gateway payload"),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{Compress, FlushCompress};
+    use serde_json::json;
+
+    /// Compress `payloads` as Discord does, one sync-flushed chunk each.
+    fn zlib_stream(payloads: &[&[u8]]) -> Vec<Vec<u8>> {
+        let mut compress = Compress::new(flate2::Compression::default(), true);
+        payloads
+            .iter()
+            .map(|payload| {
+                let mut out = Vec::with_capacity(payload.len() + 64);
+                compress.compress_vec(payload, &mut out, FlushCompress::Sync).unwrap();
+                out
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_transport_query() {
+        let url = "wss://gateway.discord.gg/?v=9&encoding=json";
+        assert_eq!(Transport::default().apply(url), url);
+        let transport = Transport {
+            encoding: Encoding::Etf,
+            compression: Compression::ZlibStream,
+        };
+        assert_eq!(transport.apply(url), "wss://gateway.discord.gg/?v=9&encoding=etf&compress=zlib-stream");
+        assert_eq!(
+            Transport { compression: Compression::ZlibStream, ..Default::default() }.apply("ws://127.0.0.1:9001"),
+            "ws://127.0.0.1:9001/?encoding=json&compress=zlib-stream"
+        );
+        assert_eq!(Compression::parse("ZLIB-STREAM"), Some(Compression::ZlibStream));
+        assert_eq!(Encoding::parse("xml"), None);
+    }
+
+    #[test]
+    fn test_inflates_messages_split_across_frames() {
+        let hello = br#"{"op":10,"d":{"heartbeat_interval":41250}}"#;
+        let ack = br#"{"op":11}"#;
+        let chunks = zlib_stream(&[hello, ack]);
+        let mut codec = Transport { compression: Compression::ZlibStream, ..Default::default() }.codec();
+
+        // The first message arrives in two frames
+        let (head, tail) = chunks[0].split_at(5);
+        assert!(codec.decode(&Message::Binary(head.to_vec())).unwrap().is_none());
+        let message = codec.decode(&Message::Binary(tail.to_vec())).unwrap().unwrap();
+        assert_eq!(message.op, 10);
+        assert_eq!(message.d.unwrap()["heartbeat_interval"], 41250);
+
+        // Later messages continue the same stream
+        assert_eq!(codec.decode(&Message::Binary(chunks[1].clone())).unwrap().unwrap().op, 11);
+    }
+
+    #[test]
+    fn test_etf_codec_round_trip() {
+        let codec = Transport { encoding: Encoding::Etf, ..Default::default() }.codec();
+        let heartbeat = GatewayMessage { op: 1, s: None, t: None, d: Some(json!(42)) };
+        let Message::Binary(bytes) = codec.encode(&heartbeat) else {
+            panic!("ETF is sent in binary frames");
+        };
+        assert_eq!(etf::decode(&bytes).unwrap(), json!({ "op": 1, "d": 42 }));
+
+        let mut codec = codec;
+        let decoded = codec.decode(&Message::Binary(bytes)).unwrap().unwrap();
+        assert_eq!((decoded.op, decoded.d), (1, Some(json!(42))));
+    }
+
+    #[test]
+    fn test_inflates_payloads_many_times_their_compressed_size() {
+        let name = "x".repeat(200_000);
+        let payload = json!({ "op": 0, "s": 1, "t": "CHANNEL_UPDATE", "d": { "id": "100", "name": name } }).to_string();
+        let chunks = zlib_stream(&[payload.as_bytes()]);
+        let mut codec = Transport { compression: Compression::ZlibStream, ..Default::default() }.codec();
+        let message = codec.decode(&Message::Binary(chunks[0].clone())).unwrap().unwrap();
+        assert_eq!(message.d.unwrap()["name"].as_str().map(str::len), Some(200_000));
+    }
+}
@@ -0,0 +1,196 @@
+//! Coalescing of notification popups during bursts.
+//!
+//! When a server renames many channels or roles in one sweep, each change would
+//! otherwise produce its own popup and remote message. The first alert in a
+//! burst is shown right away; the rest are collected until the window closes
+//! and sent as a single summary.
+//!
+//! What is held for the summary is up to the caller; the notifier keeps each
+//! alert's text along with where it still has to go. Channel renames are
+//! tallied too, matched by a rule or not, so a sweep over many channels is
+//! summarized as e.g. "7 channels changed, 1 matched" even when only one of
+//! them alerted.
+
+use crate::clock::Instant;
+use std::time::Duration;
+
+/// Default window during which further popups are grouped.
+pub const DEFAULT_GROUP_WINDOW: Duration = Duration::from_secs(5);
+
+/// What to do with a popup offered to the group.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Popup {
+    /// Show it immediately.
+    Now,
+    /// Held for the summary. `flush_after` is set for the first held popup,
+    /// whose caller is responsible for flushing once it elapses.
+    Grouped { flush_after: Option<Duration> },
+}
+
+/// Channel renames seen during a burst.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RenameTally {
+    pub changed: usize,
+    /// Renames that passed their channel's trigger rule.
+    pub matched: usize,
+}
+
+/// The popups and renames of a burst, taken for its summary.
+#[derive(Debug, PartialEq)]
+pub struct Burst<T> {
+    pub held: Vec<T>,
+    pub renames: RenameTally,
+}
+
+/// Popups held back during a burst.
+#[derive(Debug)]
+pub struct PopupGroup<T> {
+    window: Duration,
+    last_shown: Option<Instant>,
+    pending: Vec<T>,
+    renames: RenameTally,
+    /// When the first rename of the current tally was seen.
+    first_rename: Option<Instant>,
+    /// Whether a caller has been told to flush.
+    flush_scheduled: bool,
+}
+
+impl<T> PopupGroup<T> {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            last_shown: None,
+            pending: Vec::new(),
+            renames: RenameTally::default(),
+            first_rename: None,
+            flush_scheduled: false,
+        }
+    }
+
+    /// Decide whether `popup` is shown now or held for the next summary.
+    pub fn offer(&mut self, now: Instant, popup: T) -> Popup {
+        let window_open = self
+            .last_shown
+            .is_some_and(|at| now.duration_since(at) < self.window);
+        if !window_open && self.pending.is_empty() {
+            self.last_shown = Some(now);
+            return Popup::Now;
+        }
+
+        self.pending.push(popup);
+        let since = self.last_shown.map(|at| now.duration_since(at)).unwrap_or_default();
+        Popup::Grouped {
+            flush_after: self.schedule_flush(since),
+        }
+    }
+
+    /// Count a channel rename, `matched` if it passed its trigger rule.
+    ///
+    /// Returns when to flush once a second rename makes the tally worth a
+    /// summary, if no flush is scheduled yet.
+    pub fn count_rename(&mut self, now: Instant, matched: bool) -> Option<Duration> {
+        let since = match self.first_rename {
+            Some(at) if self.flush_scheduled || now.duration_since(at) < self.window => now.duration_since(at),
+            // A lone rename from an earlier window is not a burst
+            _ => {
+                self.first_rename = Some(now);
+                self.renames = RenameTally::default();
+                Duration::ZERO
+            }
+        };
+        self.renames.changed += 1;
+        self.renames.matched += usize::from(matched);
+        if self.renames.changed < 2 {
+            return None;
+        }
+        self.schedule_flush(since)
+    }
+
+    /// The delay before flushing, unless a flush is already scheduled.
+    fn schedule_flush(&mut self, since: Duration) -> Option<Duration> {
+        if self.flush_scheduled {
+            return None;
+        }
+        self.flush_scheduled = true;
+        Some(self.window.saturating_sub(since))
+    }
+
+    /// Take the held popups and rename tally; the window restarts from `now`.
+    pub fn flush(&mut self, now: Instant) -> Burst<T> {
+        self.last_shown = Some(now);
+        self.first_rename = None;
+        self.flush_scheduled = false;
+        Burst {
+            held: std::mem::take(&mut self.pending),
+            renames: std::mem::take(&mut self.renames),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_popup_is_immediate() {
+        let mut group = PopupGroup::new(Duration::from_secs(5));
+        assert_eq!(group.offer(Instant::now(), "a".to_string()), Popup::Now);
+    }
+
+    #[test]
+    fn test_burst_is_grouped_and_flushed_once() {
+        let mut group = PopupGroup::new(Duration::from_secs(5));
+        let start = Instant::now();
+
+        assert_eq!(group.offer(start, "a".to_string()), Popup::Now);
+        assert_eq!(
+            group.offer(start + Duration::from_secs(1), "b".to_string()),
+            Popup::Grouped {
+                flush_after: Some(Duration::from_secs(4))
+            }
+        );
+        assert_eq!(
+            group.offer(start + Duration::from_secs(2), "c".to_string()),
+            Popup::Grouped { flush_after: None }
+        );
+
+        assert_eq!(group.flush(start + Duration::from_secs(5)).held, vec!["b", "c"]);
+        assert!(group.flush(start + Duration::from_secs(5)).held.is_empty());
+    }
+
+    #[test]
+    fn test_renames_in_a_burst_are_tallied() {
+        let mut group: PopupGroup<String> = PopupGroup::new(Duration::from_secs(5));
+        let start = Instant::now();
+
+        // A lone rename is no burst
+        assert_eq!(group.count_rename(start, false), None);
+        assert_eq!(group.count_rename(start + Duration::from_secs(10), false), None);
+        assert_eq!(group.count_rename(start + Duration::from_secs(11), true), Some(Duration::from_secs(4)));
+        assert_eq!(group.offer(start + Duration::from_secs(11), "a".to_string()), Popup::Now);
+        assert_eq!(group.count_rename(start + Duration::from_secs(12), false), None);
+
+        let burst = group.flush(start + Duration::from_secs(15));
+        assert!(burst.held.is_empty());
+        assert_eq!(burst.renames, RenameTally { changed: 3, matched: 1 });
+        assert_eq!(group.flush(start + Duration::from_secs(15)).renames, RenameTally::default());
+    }
+
+    #[test]
+    fn test_popup_after_quiet_window_is_immediate() {
+        let mut group = PopupGroup::new(Duration::from_secs(5));
+        let start = Instant::now();
+
+        assert_eq!(group.offer(start, "a".to_string()), Popup::Now);
+        assert_eq!(group.offer(start + Duration::from_secs(5), "b".to_string()), Popup::Now);
+    }
+
+    #[test]
+    fn test_zero_window_never_groups() {
+        let mut group = PopupGroup::new(Duration::ZERO);
+        let now = Instant::now();
+
+        assert_eq!(group.offer(now, "a".to_string()), Popup::Now);
+        assert_eq!(group.offer(now, "b".to_string()), Popup::Now);
+    }
+}
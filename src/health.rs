@@ -0,0 +1,240 @@
+//! Liveness tracking for the monitoring loops.
+//!
+//! The poll and WebSocket loops record their progress here so the foreground
+//! health line, `status`, `stats`, the metrics endpoint and
+//! `run --daemon --wait-ready` can report on them.
+
+use crate::clock::Instant;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// Counters since the monitor started.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Stats {
+    pub uptime_secs: u64,
+    /// Successful REST polls.
+    pub polls: u64,
+    pub poll_failures: u64,
+    /// Gateway connections that reached READY or RESUMED.
+    pub gateway_sessions: u64,
+    /// Channel renames detected by any source.
+    pub renames: u64,
+    pub reloads: u64,
+    /// REST requests answered with 429 Too Many Requests.
+    #[serde(default)]
+    pub rate_limits: u64,
+    /// Alarms that rang, escalations excluded.
+    #[serde(default)]
+    pub alarms: u64,
+}
+
+/// Shared health state updated by the monitoring loops.
+#[derive(Debug, Default)]
+pub struct Health {
+    ws_connected: AtomicBool,
+    last_poll: Mutex<Option<Instant>>,
+    /// Outcome of the initial channel fetch, once it has finished.
+    initial_fetch: Mutex<Option<Result<(), String>>>,
+    last_ws_error: Mutex<Option<String>>,
+    started: OnceLock<Instant>,
+    polls: AtomicU64,
+    poll_failures: AtomicU64,
+    gateway_sessions: AtomicU64,
+    renames: AtomicU64,
+    reloads: AtomicU64,
+    rate_limits: AtomicU64,
+    alarms: AtomicU64,
+    /// Time between the last heartbeat and its ACK.
+    heartbeat_latency: Mutex<Option<Duration>>,
+}
+
+impl Health {
+    /// Record whether the Gateway connection is currently identified.
+    pub fn set_ws_connected(&self, connected: bool) {
+        if connected {
+            self.gateway_sessions.fetch_add(1, Ordering::Relaxed);
+        }
+        self.ws_connected.store(connected, Ordering::SeqCst);
+    }
+
+    /// Check whether the Gateway connection is up.
+    pub fn ws_connected(&self) -> bool {
+        self.ws_connected.load(Ordering::SeqCst)
+    }
+
+    /// Record a successful REST poll.
+    pub fn record_poll(&self) {
+        self.polls.fetch_add(1, Ordering::Relaxed);
+        *self.last_poll.lock().unwrap_or_else(|e| e.into_inner()) = Some(Instant::now());
+    }
+
+    /// Record a failed REST poll.
+    pub fn record_poll_failure(&self) {
+        self.poll_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a detected channel rename.
+    pub fn record_rename(&self) {
+        self.renames.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a REST request answered with 429.
+    pub fn record_rate_limit(&self) {
+        self.rate_limits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record an alarm that rang.
+    pub fn record_alarm(&self) {
+        self.alarms.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record how long the Gateway took to ACK a heartbeat.
+    pub fn record_heartbeat_latency(&self, latency: Duration) {
+        *self.heartbeat_latency.lock().unwrap_or_else(|e| e.into_inner()) = Some(latency);
+    }
+
+    /// Latency of the last acknowledged heartbeat, if any.
+    pub fn heartbeat_latency(&self) -> Option<Duration> {
+        *self.heartbeat_latency.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Record a configuration reload.
+    pub fn record_reload(&self) {
+        self.reloads.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Start the uptime clock; later calls are ignored.
+    pub fn mark_started(&self) {
+        let _ = self.started.set(Instant::now());
+    }
+
+    /// Counters since the monitor started.
+    pub fn stats(&self) -> Stats {
+        Stats {
+            uptime_secs: self.started.get().map(|at| at.elapsed().as_secs()).unwrap_or_default(),
+            polls: self.polls.load(Ordering::Relaxed),
+            poll_failures: self.poll_failures.load(Ordering::Relaxed),
+            gateway_sessions: self.gateway_sessions.load(Ordering::Relaxed),
+            renames: self.renames.load(Ordering::Relaxed),
+            reloads: self.reloads.load(Ordering::Relaxed),
+            rate_limits: self.rate_limits.load(Ordering::Relaxed),
+            alarms: self.alarms.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Time since the last successful REST poll, if any.
+    pub fn last_poll_age(&self) -> Option<Duration> {
+        self.last_poll
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .map(|at| at.elapsed())
+    }
+
+    /// Record the outcome of the initial channel fetch.
+    pub fn record_initial_fetch(&self, result: Result<(), String>) {
+        *self.initial_fetch.lock().unwrap_or_else(|e| e.into_inner()) = Some(result);
+    }
+
+    /// Outcome of the initial channel fetch, or `None` while it is still running.
+    pub fn initial_fetch(&self) -> Option<Result<(), String>> {
+        self.initial_fetch.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Record why the Gateway connection last failed or closed.
+    pub fn record_ws_error(&self, error: String) {
+        *self.last_ws_error.lock().unwrap_or_else(|e| e.into_inner()) = Some(error);
+    }
+
+    /// The most recent Gateway error, if any.
+    pub fn last_ws_error(&self) -> Option<String> {
+        self.last_ws_error.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+/// Build the one-line health summary, e.g. `WS ok, last poll 2s ago, channel: open`.
+///
+/// `channels` are the names to show: the primary channel's, or every
+/// discovered channel's when there is no primary.
+pub fn format_summary(ws_connected: bool, last_poll_age: Option<Duration>, channels: &[&str]) -> String {
+    let ws = if ws_connected { "WS ok" } else { "WS down" };
+    let poll = match last_poll_age {
+        Some(age) => format!("last poll {}s ago", age.as_secs()),
+        None => "no successful poll yet".to_string(),
+    };
+    match channels {
+        [] => format!("{}, {}, channel: (unknown)", ws, poll),
+        [channel] => format!("{}, {}, channel: {}", ws, poll, channel),
+        channels => format!("{}, {}, channels: {}", ws, poll, channels.join(", ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_summary_healthy() {
+        let line = format_summary(true, Some(Duration::from_millis(2500)), &["〖start-order-❌〗"]);
+        assert_eq!(line, "WS ok, last poll 2s ago, channel: 〖start-order-❌〗");
+    }
+
+    #[test]
+    fn test_format_summary_before_first_poll() {
+        let line = format_summary(false, None, &[]);
+        assert_eq!(line, "WS down, no successful poll yet, channel: (unknown)");
+    }
+
+    #[test]
+    fn test_format_summary_lists_discovered_channels() {
+        let line = format_summary(true, None, &["order-a", "order-b"]);
+        assert_eq!(line, "WS ok, no successful poll yet, channels: order-a, order-b");
+    }
+
+    #[test]
+    fn test_health_records_state() {
+        let health = Health::default();
+        assert!(!health.ws_connected());
+        assert!(health.last_poll_age().is_none());
+
+        health.set_ws_connected(true);
+        health.record_poll();
+        assert!(health.ws_connected());
+        assert!(health.last_poll_age().unwrap() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_health_records_startup_errors() {
+        let health = Health::default();
+        assert_eq!(health.initial_fetch(), None);
+        assert_eq!(health.last_ws_error(), None);
+
+        health.record_initial_fetch(Err("401 Unauthorized".to_string()));
+        health.record_ws_error("closed with 4004: Authentication failed.".to_string());
+        assert_eq!(health.initial_fetch(), Some(Err("401 Unauthorized".to_string())));
+        assert_eq!(health.last_ws_error().as_deref(), Some("closed with 4004: Authentication failed."));
+    }
+
+    #[test]
+    fn test_health_counts_stats() {
+        let health = Health::default();
+        assert_eq!(health.stats(), Stats::default());
+
+        health.set_ws_connected(true);
+        health.set_ws_connected(false);
+        health.set_ws_connected(true);
+        health.record_poll();
+        health.record_poll_failure();
+        health.record_rename();
+        health.record_reload();
+        health.record_rate_limit();
+        health.record_alarm();
+
+        let stats = health.stats();
+        assert_eq!(stats.gateway_sessions, 2);
+        assert_eq!((stats.polls, stats.poll_failures), (1, 1));
+        assert_eq!((stats.renames, stats.reloads), (1, 1));
+        assert_eq!((stats.rate_limits, stats.alarms), (1, 1));
+    }
+}
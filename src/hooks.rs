@@ -0,0 +1,327 @@
+//! Actions run when a monitored channel is renamed.
+//!
+//! Renames that raise an alarm also run the configured hooks: a shell command
+//! (`ON_CHANGE_EXEC`) with the change in its environment, and a JSON POST
+//! (`ON_CHANGE_URL`). Hooks follow the notifier's decision, so a muted channel
+//! runs none and under a compound rule they run once its message arrives.
+//! Each hook is cut off after `HOOK_TIMEOUT` and its outcome is logged.
+
+use crate::events::MonitorEvent;
+use crate::i18n::Alert;
+use crate::rest::USER_AGENT;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::{error, info, warn};
+
+/// Time a hook may run before it is stopped.
+pub const DEFAULT_HOOK_TIMEOUT: Duration = Duration::from_secs(10);
+/// Characters of a command's output kept in the log.
+const OUTPUT_LOG_CHARS: usize = 200;
+
+/// Hooks read from the environment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HookSettings {
+    /// Shell command run on each alarming rename.
+    pub exec: Option<String>,
+    /// URL the change is POSTed to as JSON.
+    pub url: Option<String>,
+    pub timeout: Duration,
+}
+
+impl Default for HookSettings {
+    fn default() -> Self {
+        Self {
+            exec: None,
+            url: None,
+            timeout: DEFAULT_HOOK_TIMEOUT,
+        }
+    }
+}
+
+impl HookSettings {
+    /// Whether any hook is configured.
+    pub fn is_enabled(&self) -> bool {
+        self.exec.is_some() || self.url.is_some()
+    }
+}
+
+/// A rename handed to the hooks.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Change {
+    pub channel_id: String,
+    pub old_name: Option<String>,
+    pub new_name: String,
+    pub changed_by: Option<String>,
+    pub source: String,
+}
+
+impl Change {
+    /// Environment variables passed to `ON_CHANGE_EXEC`; unknown values are empty.
+    pub fn env(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("CHANNEL_ID", self.channel_id.clone()),
+            ("OLD_NAME", self.old_name.clone().unwrap_or_default()),
+            ("NEW_NAME", self.new_name.clone()),
+            ("CHANGED_BY", self.changed_by.clone().unwrap_or_default()),
+            ("CHANGE_SOURCE", self.source.clone()),
+        ]
+    }
+
+    /// JSON body POSTed to `ON_CHANGE_URL`.
+    pub fn payload(&self) -> serde_json::Value {
+        serde_json::json!({
+            "channel_id": self.channel_id,
+            "old_name": self.old_name,
+            "new_name": self.new_name,
+            "changed_by": self.changed_by,
+            "source": self.source,
+        })
+    }
+}
+
+/// Runs the configured hooks for the monitor's renames.
+pub struct Hooks {
+    settings: HookSettings,
+    client: reqwest::Client,
+}
+
+impl Hooks {
+    pub fn new(settings: HookSettings) -> Self {
+        Self {
+            settings,
+            client: reqwest::Client::builder().user_agent(USER_AGENT).build().unwrap_or_default(),
+        }
+    }
+
+    /// Run the hooks for every rename alarm the notifier raises until the channel closes.
+    pub async fn listen(self: Arc<Self>, mut events: broadcast::Receiver<MonitorEvent>) {
+        loop {
+            match events.recv().await {
+                Ok(MonitorEvent::AlarmRaised {
+                    channel_id: Some(channel_id),
+                    alert:
+                        Alert::ChannelOpen {
+                            name,
+                            previous,
+                            changed_by,
+                            ..
+                        }
+                        | Alert::ChannelOpenWithMessage {
+                            name,
+                            previous,
+                            changed_by,
+                            ..
+                        },
+                    source,
+                }) => {
+                    let change = Change {
+                        channel_id,
+                        old_name: previous,
+                        new_name: name,
+                        changed_by,
+                        source: source.unwrap_or_default(),
+                    };
+                    let hooks = Arc::clone(&self);
+                    tokio::spawn(async move { hooks.run(&change).await });
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(missed)) => warn!("[HOOK] Fell behind and missed {} events", missed),
+                Err(RecvError::Closed) => return,
+            }
+        }
+    }
+
+    /// Run every configured hook for `change` concurrently, logging the outcomes.
+    pub async fn run(&self, change: &Change) {
+        let exec = async {
+            match &self.settings.exec {
+                Some(command) => Some(self.exec(command, change).await),
+                None => None,
+            }
+        };
+        let post = async {
+            match &self.settings.url {
+                Some(url) => Some(self.post(url, change).await),
+                None => None,
+            }
+        };
+        let (exec, post) = tokio::join!(exec, post);
+        for (hook, result) in [("ON_CHANGE_EXEC", exec), ("ON_CHANGE_URL", post)] {
+            match result {
+                Some(Ok(outcome)) => info!("[HOOK] {} for {}: {}", hook, change.new_name, outcome),
+                Some(Err(e)) => error!("[HOOK] {} for {} failed: {}", hook, change.new_name, e),
+                None => {}
+            }
+        }
+    }
+
+    /// Run `command` in the shell with the change in its environment.
+    async fn exec(&self, command: &str, change: &Change) -> Result<String, String> {
+        let started = Instant::now();
+        let output = shell(command)
+            .envs(change.env())
+            // Dropping the future on timeout kills the command
+            .kill_on_drop(true)
+            .output();
+        let output = tokio::time::timeout(self.settings.timeout, output)
+            .await
+            .map_err(|_| format!("timed out after {}s", self.settings.timeout.as_secs_f64()))?
+            .map_err(|e| format!("could not be started: {}", e))?;
+        let stdout = summarize(&output.stdout);
+        let elapsed = started.elapsed().as_secs_f64();
+        if output.status.success() {
+            Ok(format!("exited with {} after {:.1}s{}", output.status, elapsed, stdout))
+        } else {
+            Err(format!("exited with {} after {:.1}s{}{}", output.status, elapsed, stdout, summarize(&output.stderr)))
+        }
+    }
+
+    /// POST the change as JSON to `url`.
+    async fn post(&self, url: &str, change: &Change) -> Result<String, String> {
+        let response = self
+            .client
+            .post(url)
+            .json(&change.payload())
+            .timeout(self.settings.timeout)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            // The URL may hold a token
+            .map_err(|e| format!("request failed: {}", e.without_url()))?;
+        Ok(format!("answered {}", response.status()))
+    }
+}
+
+/// A command run by the platform shell.
+fn shell(command: &str) -> Command {
+    #[cfg(windows)]
+    let mut shell = {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    };
+    #[cfg(not(windows))]
+    let mut shell = {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    shell.arg(command);
+    shell
+}
+
+/// The start of a command's output for the log, e.g. `: order submitted`.
+fn summarize(output: &[u8]) -> String {
+    let text = String::from_utf8_lossy(output);
+    let text = text.trim();
+    if text.is_empty() {
+        return String::new();
+    }
+    let mut short: String = text.chars().take(OUTPUT_LOG_CHARS).collect();
+    if short.len() < text.len() {
+        short.push('…');
+    }
+    format!(": {}", short.replace('\n', " | "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change() -> Change {
+        Change {
+            channel_id: "100".to_string(),
+            old_name: Some("order-❌".to_string()),
+            new_name: "order-✅".to_string(),
+            changed_by: None,
+            source: "WS".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_change_env_and_payload() {
+        let change = change();
+        let env = change.env();
+        assert!(env.contains(&("OLD_NAME", "order-❌".to_string())));
+        assert!(env.contains(&("CHANGED_BY", String::new())));
+
+        let payload = change.payload();
+        assert_eq!(payload["new_name"], "order-✅");
+        assert!(payload["changed_by"].is_null());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_exec_passes_change_and_times_out() {
+        let hooks = Hooks::new(HookSettings {
+            timeout: Duration::from_millis(500),
+            ..Default::default()
+        });
+        let outcome = hooks.exec("echo \"$CHANNEL_ID: $OLD_NAME -> $NEW_NAME\"", &change()).await.unwrap();
+        assert!(outcome.ends_with(": 100: order-❌ -> order-✅"), "{}", outcome);
+
+        let err = hooks.exec("echo nope >&2; exit 3", &change()).await.unwrap_err();
+        assert!(err.contains("exit status: 3") && err.ends_with(": nope"), "{}", err);
+
+        let started = Instant::now();
+        let err = hooks.exec("sleep 5", &change()).await.unwrap_err();
+        assert_eq!(err, "timed out after 0.5s");
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_listen_runs_only_for_raised_rename_alarms() {
+        let path = std::env::temp_dir().join(format!("ollie-hook-{}.txt", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let hooks = Arc::new(Hooks::new(HookSettings {
+            exec: Some(format!("echo \"$NEW_NAME $CHANGE_SOURCE\" >> {}", path.display())),
+            ..Default::default()
+        }));
+        let events = crate::events::channel();
+        let listener = tokio::spawn(hooks.listen(events.subscribe()));
+
+        // The notifier decides; a rename alone runs nothing
+        crate::events::emit(
+            &events,
+            MonitorEvent::NameChanged {
+                channel_id: "100".to_string(),
+                old_name: None,
+                new_name: "order-⏳".to_string(),
+                changed_by: None,
+                source: "WS".to_string(),
+                alarm: true,
+                trigger: None,
+            },
+        );
+        let raised = |alert: Alert| MonitorEvent::AlarmRaised {
+            channel_id: Some("100".to_string()),
+            alert,
+            source: Some("WS".to_string()),
+        };
+        crate::events::emit(&events, raised(Alert::StageLive { topic: "drop".to_string() }));
+        crate::events::emit(
+            &events,
+            raised(Alert::ChannelOpen {
+                name: "order-✅".to_string(),
+                previous: Some("order-⏳".to_string()),
+                changed_by: None,
+                trigger: None,
+            }),
+        );
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !path.exists() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "order-✅ WS\n");
+        std::fs::remove_file(&path).ok();
+        listener.abort();
+    }
+}
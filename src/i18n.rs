@@ -0,0 +1,624 @@
+//! Localized text for user-facing notifications.
+//!
+//! Log output stays in English; only notification titles and bodies (desktop,
+//! Telegram, webhook) are translated.
+
+use crate::name_diff;
+use crate::trigger::TriggerMatch;
+
+/// Supported notification languages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    #[default]
+    En,
+    Es,
+    De,
+    Ja,
+}
+
+impl Language {
+    /// Parse a language code such as `en`, `es-MX` or `de_DE`.
+    pub fn parse(code: &str) -> Option<Self> {
+        let primary = code
+            .split(['-', '_'])
+            .next()
+            .unwrap_or("")
+            .to_lowercase();
+        match primary.as_str() {
+            "en" => Some(Language::En),
+            "es" => Some(Language::Es),
+            "de" => Some(Language::De),
+            "ja" => Some(Language::Ja),
+            _ => None,
+        }
+    }
+
+    /// Short language code.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::En => "en",
+            Language::Es => "es",
+            Language::De => "de",
+            Language::Ja => "ja",
+        }
+    }
+}
+
+/// An alarm-worthy event, rendered per language into a title and body.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Alert {
+    /// The monitored channel was renamed; `previous` is its old name,
+    /// `changed_by` who renamed it, if known, and `trigger` the rule it matched.
+    ChannelOpen { name: String, previous: Option<String>, changed_by: Option<String>, trigger: Option<TriggerMatch> },
+    /// A compound rule matched: the channel was renamed and a keyword message arrived.
+    ChannelOpenWithMessage { name: String, previous: Option<String>, changed_by: Option<String>, message: String },
+    StageLive { topic: String },
+    UserStreamingInVoice { user_id: String, channel_id: String },
+    UserStreaming { user_id: String, activity: String },
+    UserJoinedVoice { user_id: String, channel: String },
+    RoleAppeared { role: String },
+    RolePermissionsChanged { role: String, old: String, new: String },
+    MemberSurge { guild: String, gained: u64, total: u64 },
+    /// A new message in `channel` passed its keyword and author filters;
+    /// `trigger` is the keyword it matched.
+    MessagePosted { channel: String, author: String, content: String, trigger: Option<TriggerMatch> },
+    /// No new messages in a monitored channel for a while.
+    ChannelInactive { channel_id: String, name: String, idle_minutes: u64 },
+    /// A ringing alarm, already rendered, went unacknowledged for `minutes`.
+    Unacknowledged { title: String, body: String, minutes: u64 },
+    /// Several alerts collapsed into one notification, each as `TITLE: body`,
+    /// along with how many channels were renamed during the burst and how many
+    /// of those renames matched their open rule.
+    Grouped { alerts: Vec<String>, changed: usize, matched: usize },
+}
+
+/// Most alerts listed in a grouped notification body.
+const GROUPED_LIST_LIMIT: usize = 5;
+
+/// Pick the singular or plural form for languages that inflect by count.
+fn plural<'a>(count: u64, one: &'a str, other: &'a str) -> &'a str {
+    if count == 1 {
+        one
+    } else {
+        other
+    }
+}
+
+/// The line naming the trigger pattern an alert matched, and what it matched.
+fn trigger_line(trigger: &TriggerMatch, lang: Language) -> String {
+    let label = match lang {
+        Language::En => "Trigger",
+        Language::Es => "Disparador",
+        Language::De => "Auslöser",
+        Language::Ja => "トリガー",
+    };
+    format!("\n{}: {} ({})", label, trigger.pattern, trigger.text)
+}
+
+impl Alert {
+    /// What raised the alert (a channel, the stage, a role...), used to budget
+    /// audible alarms per source.
+    pub fn source(&self) -> String {
+        match self {
+            Alert::ChannelOpen { .. } | Alert::ChannelOpenWithMessage { .. } | Alert::MessagePosted { .. } => {
+                "channel".to_string()
+            }
+            Alert::StageLive { .. } => "stage".to_string(),
+            Alert::UserStreamingInVoice { channel_id, .. } => format!("voice:{}", channel_id),
+            Alert::UserJoinedVoice { channel, .. } => format!("voice:{}", channel),
+            Alert::UserStreaming { user_id, .. } => format!("stream:{}", user_id),
+            Alert::RoleAppeared { role } | Alert::RolePermissionsChanged { role, .. } => {
+                format!("role:{}", role)
+            }
+            Alert::MemberSurge { guild, .. } => format!("guild:{}", guild),
+            Alert::ChannelInactive { channel_id, .. } => format!("inactive:{}", channel_id),
+            Alert::Unacknowledged { .. } => "escalation".to_string(),
+            Alert::Grouped { .. } => "grouped".to_string(),
+        }
+    }
+
+    /// Notification title.
+    pub fn title(&self, lang: Language) -> String {
+        use Language::*;
+        if let Alert::Grouped { alerts, changed, .. } = self {
+            if *changed > 1 {
+                return match lang {
+                    En => "CHANNELS CHANGED",
+                    Es => "CANALES CAMBIADOS",
+                    De => "KANÄLE GEÄNDERT",
+                    Ja => "チャンネル変更",
+                }
+                .to_string();
+            }
+            let count = alerts.len();
+            return match lang {
+                En => format!("{} MORE {}", count, plural(count as u64, "ALERT", "ALERTS")),
+                Es => format!("{} {} MÁS", count, plural(count as u64, "ALERTA", "ALERTAS")),
+                De => format!("{} WEITERE {}", count, plural(count as u64, "MELDUNG", "MELDUNGEN")),
+                Ja => format!("他{}件のアラート", count),
+            };
+        }
+        if let Alert::Unacknowledged { title, .. } = self {
+            let prefix = match lang {
+                En => "UNACKNOWLEDGED",
+                Es => "SIN CONFIRMAR",
+                De => "UNBESTÄTIGT",
+                Ja => "未確認",
+            };
+            return format!("{}: {}", prefix, title);
+        }
+        let title = match self {
+            Alert::ChannelOpen { .. } => match lang {
+                En => "CHANNEL OPEN",
+                Es => "CANAL ABIERTO",
+                De => "KANAL OFFEN",
+                Ja => "チャンネル開放",
+            },
+            Alert::ChannelOpenWithMessage { .. } => match lang {
+                En => "CHANNEL OPEN + MESSAGE",
+                Es => "CANAL ABIERTO + MENSAJE",
+                De => "KANAL OFFEN + NACHRICHT",
+                Ja => "チャンネル開放 + メッセージ",
+            },
+            Alert::StageLive { .. } => match lang {
+                En => "STAGE LIVE",
+                Es => "ESCENARIO EN VIVO",
+                De => "STAGE LIVE",
+                Ja => "ステージ開始",
+            },
+            Alert::UserStreamingInVoice { .. } | Alert::UserStreaming { .. } => match lang {
+                En => "USER LIVE",
+                Es => "USUARIO EN VIVO",
+                De => "NUTZER LIVE",
+                Ja => "ユーザー配信中",
+            },
+            Alert::UserJoinedVoice { .. } => match lang {
+                En => "USER IN VOICE",
+                Es => "USUARIO EN VOZ",
+                De => "NUTZER IM SPRACHKANAL",
+                Ja => "ボイス参加",
+            },
+            Alert::RoleAppeared { .. } | Alert::RolePermissionsChanged { .. } => match lang {
+                En => "ROLE CHANGED",
+                Es => "ROL CAMBIADO",
+                De => "ROLLE GEÄNDERT",
+                Ja => "ロール変更",
+            },
+            Alert::MemberSurge { .. } => match lang {
+                En => "MEMBER SURGE",
+                Es => "AUMENTO DE MIEMBROS",
+                De => "MITGLIEDERANSTIEG",
+                Ja => "メンバー急増",
+            },
+            Alert::MessagePosted { .. } => match lang {
+                En => "NEW MESSAGE",
+                Es => "MENSAJE NUEVO",
+                De => "NEUE NACHRICHT",
+                Ja => "新着メッセージ",
+            },
+            Alert::ChannelInactive { .. } => match lang {
+                En => "CHANNEL QUIET",
+                Es => "CANAL INACTIVO",
+                De => "KANAL STILL",
+                Ja => "チャンネル停滞",
+            },
+            Alert::Unacknowledged { .. } | Alert::Grouped { .. } => {
+                unreachable!("escalated and grouped titles are formatted above")
+            }
+        };
+        title.to_string()
+    }
+
+    /// Notification body.
+    pub fn body(&self, lang: Language) -> String {
+        use Language::*;
+        match self {
+            Alert::ChannelOpen { name, previous, changed_by, trigger } => {
+                let mut body = match lang {
+                    En => format!("Channel is now: {}", name),
+                    Es => format!("El canal ahora es: {}", name),
+                    De => format!("Kanal heißt jetzt: {}", name),
+                    Ja => format!("チャンネル名: {}", name),
+                };
+                if let Some(previous) = previous {
+                    let (was, change) = match lang {
+                        En => ("Was", "Change"),
+                        Es => ("Antes", "Cambio"),
+                        De => ("Vorher", "Änderung"),
+                        Ja => ("変更前", "変更点"),
+                    };
+                    body.push_str(&format!(
+                        "\n{}: {}\n{}: {}",
+                        was,
+                        previous,
+                        change,
+                        name_diff::highlight_change(previous, name)
+                    ));
+                }
+                if let Some(user) = changed_by {
+                    let by = match lang {
+                        En => "Changed by",
+                        Es => "Cambiado por",
+                        De => "Geändert von",
+                        Ja => "変更者",
+                    };
+                    body.push_str(&format!("\n{}: {}", by, user));
+                }
+                if let Some(trigger) = trigger {
+                    body.push_str(&trigger_line(trigger, lang));
+                }
+                body
+            }
+            Alert::ChannelOpenWithMessage { name, previous, changed_by, message } => {
+                let open = Alert::ChannelOpen {
+                    name: name.clone(),
+                    previous: previous.clone(),
+                    changed_by: changed_by.clone(),
+                    trigger: None,
+                };
+                let label = match lang {
+                    En => "Message",
+                    Es => "Mensaje",
+                    De => "Nachricht",
+                    Ja => "メッセージ",
+                };
+                format!("{}\n{}: {}", open.body(lang), label, message)
+            }
+            Alert::MessagePosted { channel, author, content, trigger } => {
+                let mut body = match lang {
+                    En => format!("{} in {}: {}", author, channel, content),
+                    Es => format!("{} en {}: {}", author, channel, content),
+                    De => format!("{} in {}: {}", author, channel, content),
+                    Ja => format!("{} ({}): {}", author, channel, content),
+                };
+                if let Some(trigger) = trigger {
+                    body.push_str(&trigger_line(trigger, lang));
+                }
+                body
+            }
+            Alert::StageLive { topic } => match lang {
+                En => format!("Stage is live: {}", topic),
+                Es => format!("El escenario está en vivo: {}", topic),
+                De => format!("Stage ist live: {}", topic),
+                Ja => format!("ステージ配信中: {}", topic),
+            },
+            Alert::UserStreamingInVoice { user_id, channel_id } => match lang {
+                En => format!("User {} is streaming in voice channel {}", user_id, channel_id),
+                Es => format!("El usuario {} está transmitiendo en el canal de voz {}", user_id, channel_id),
+                De => format!("Nutzer {} streamt im Sprachkanal {}", user_id, channel_id),
+                Ja => format!("ユーザー {} がボイスチャンネル {} で配信中", user_id, channel_id),
+            },
+            Alert::UserStreaming { user_id, activity } => match lang {
+                En => format!("User {} is streaming: {}", user_id, activity),
+                Es => format!("El usuario {} está transmitiendo: {}", user_id, activity),
+                De => format!("Nutzer {} streamt: {}", user_id, activity),
+                Ja => format!("ユーザー {} が配信中: {}", user_id, activity),
+            },
+            Alert::UserJoinedVoice { user_id, channel } => match lang {
+                En => format!("User {} joined voice: {}", user_id, channel),
+                Es => format!("El usuario {} entró al canal de voz: {}", user_id, channel),
+                De => format!("Nutzer {} ist dem Sprachkanal beigetreten: {}", user_id, channel),
+                Ja => format!("ユーザー {} がボイスに参加: {}", user_id, channel),
+            },
+            Alert::RoleAppeared { role } => match lang {
+                En => format!("Role appeared: {}", role),
+                Es => format!("Nuevo rol: {}", role),
+                De => format!("Neue Rolle: {}", role),
+                Ja => format!("ロール追加: {}", role),
+            },
+            Alert::RolePermissionsChanged { role, old, new } => match lang {
+                En => format!("Role {} permissions changed: {} -> {}", role, old, new),
+                Es => format!("Permisos del rol {} cambiados: {} -> {}", role, old, new),
+                De => format!("Berechtigungen der Rolle {} geändert: {} -> {}", role, old, new),
+                Ja => format!("ロール {} の権限変更: {} -> {}", role, old, new),
+            },
+            Alert::MemberSurge { guild, gained, total } => match lang {
+                En => format!(
+                    "{} gained {} {} in the last hour (now {})",
+                    guild,
+                    gained,
+                    plural(*gained, "member", "members"),
+                    total
+                ),
+                Es => format!(
+                    "{} ganó {} {} en la última hora (ahora {})",
+                    guild,
+                    gained,
+                    plural(*gained, "miembro", "miembros"),
+                    total
+                ),
+                De => format!(
+                    "{} hat in der letzten Stunde {} {} gewonnen (jetzt {})",
+                    guild,
+                    gained,
+                    plural(*gained, "Mitglied", "Mitglieder"),
+                    total
+                ),
+                // Japanese does not inflect for number
+                Ja => format!("{} のメンバーが過去1時間で{}人増加 (現在 {}人)", guild, gained, total),
+            },
+            Alert::ChannelInactive { name, idle_minutes, .. } => match lang {
+                En => format!(
+                    "No new messages in {} for {} {}",
+                    name,
+                    idle_minutes,
+                    plural(*idle_minutes, "minute", "minutes")
+                ),
+                Es => format!(
+                    "Sin mensajes nuevos en {} desde hace {} {}",
+                    name,
+                    idle_minutes,
+                    plural(*idle_minutes, "minuto", "minutos")
+                ),
+                De => format!(
+                    "Keine neuen Nachrichten in {} seit {} {}",
+                    name,
+                    idle_minutes,
+                    plural(*idle_minutes, "Minute", "Minuten")
+                ),
+                Ja => format!("{} に{}分間新しいメッセージがありません", name, idle_minutes),
+            },
+            Alert::Unacknowledged { body, minutes, .. } => {
+                let ringing = match lang {
+                    En => format!("Ringing for {} {}", minutes, plural(*minutes, "minute", "minutes")),
+                    Es => format!("Sonando desde hace {} {}", minutes, plural(*minutes, "minuto", "minutos")),
+                    De => format!("Klingelt seit {} {}", minutes, plural(*minutes, "Minute", "Minuten")),
+                    Ja => format!("{}分間鳴り続けています", minutes),
+                };
+                format!("{}\n{}", body, ringing)
+            }
+            Alert::Grouped { alerts, changed, matched } => {
+                let mut lines = Vec::new();
+                if *changed > 1 {
+                    lines.push(match lang {
+                        En => format!("{} channels changed, {} matched open rules", changed, matched),
+                        Es => format!(
+                            "{} canales cambiaron, {} {} con las reglas de apertura",
+                            changed,
+                            matched,
+                            plural(*matched as u64, "coincidió", "coincidieron")
+                        ),
+                        De => format!("{} Kanäle geändert, {} passend zu den Öffnungsregeln", changed, matched),
+                        Ja => format!("{}件のチャンネルが変更され、{}件がオープン条件に一致しました", changed, matched),
+                    });
+                }
+                lines.extend(alerts.iter().take(GROUPED_LIST_LIMIT).cloned());
+                let rest = alerts.len().saturating_sub(GROUPED_LIST_LIMIT);
+                if rest > 0 {
+                    lines.push(match lang {
+                        En => format!("...and {} more", rest),
+                        Es => format!("...y {} más", rest),
+                        De => format!("...und {} weitere", rest),
+                        Ja => format!("...他{}件", rest),
+                    });
+                }
+                lines.join("\n")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_language_codes() {
+        assert_eq!(Language::parse("en"), Some(Language::En));
+        assert_eq!(Language::parse("es-MX"), Some(Language::Es));
+        assert_eq!(Language::parse("de_DE"), Some(Language::De));
+        assert_eq!(Language::parse("JA"), Some(Language::Ja));
+        assert_eq!(Language::parse("fr"), None);
+        assert_eq!(Language::parse(""), None);
+    }
+
+    #[test]
+    fn test_english_matches_default_notification_text() {
+        let alert = Alert::ChannelOpen {
+            name: "test-channel".to_string(),
+            previous: None,
+            changed_by: None,
+            trigger: None,
+        };
+        assert_eq!(alert.title(Language::En), "CHANNEL OPEN");
+        assert_eq!(alert.body(Language::En), "Channel is now: test-channel");
+    }
+
+    #[test]
+    fn test_translated_channel_open() {
+        let alert = Alert::ChannelOpen {
+            name: "start-order-✅".to_string(),
+            previous: None,
+            changed_by: None,
+            trigger: None,
+        };
+        assert_eq!(alert.title(Language::Es), "CANAL ABIERTO");
+        assert_eq!(alert.body(Language::De), "Kanal heißt jetzt: start-order-✅");
+        assert_eq!(alert.body(Language::Ja), "チャンネル名: start-order-✅");
+    }
+
+    #[test]
+    fn test_channel_open_shows_previous_name() {
+        let alert = Alert::ChannelOpen {
+            name: "start-order-✅".to_string(),
+            previous: Some("start-order-❌".to_string()),
+            changed_by: None,
+            trigger: None,
+        };
+
+        assert_eq!(
+            alert.body(Language::En),
+            "Channel is now: start-order-✅\nWas: start-order-❌\nChange: start-order-[❌ → ✅]"
+        );
+        assert!(alert.body(Language::De).contains("\nVorher: start-order-❌\n"));
+    }
+
+    #[test]
+    fn test_channel_open_names_who_renamed_it() {
+        let alert = Alert::ChannelOpen {
+            name: "start-order-✅".to_string(),
+            previous: None,
+            changed_by: Some("Shop Keeper".to_string()),
+            trigger: None,
+        };
+
+        assert_eq!(alert.body(Language::En), "Channel is now: start-order-✅\nChanged by: Shop Keeper");
+        assert!(alert.body(Language::Es).ends_with("\nCambiado por: Shop Keeper"));
+    }
+
+    #[test]
+    fn test_channel_open_names_matched_trigger() {
+        let alert = Alert::ChannelOpen {
+            name: "start-order-✅".to_string(),
+            previous: None,
+            changed_by: None,
+            trigger: Some(TriggerMatch {
+                pattern: "✅|open".to_string(),
+                text: "✅".to_string(),
+            }),
+        };
+
+        assert_eq!(alert.body(Language::En), "Channel is now: start-order-✅\nTrigger: ✅|open (✅)");
+    }
+
+    #[test]
+    fn test_channel_open_with_message_carries_both() {
+        let alert = Alert::ChannelOpenWithMessage {
+            name: "start-order-✅".to_string(),
+            previous: None,
+            changed_by: None,
+            message: "password: hunter2".to_string(),
+        };
+
+        assert_eq!(alert.title(Language::En), "CHANNEL OPEN + MESSAGE");
+        assert_eq!(
+            alert.body(Language::En),
+            "Channel is now: start-order-✅\nMessage: password: hunter2"
+        );
+        assert_eq!(alert.source(), "channel");
+    }
+
+    #[test]
+    fn test_message_posted_names_author_and_keyword() {
+        let alert = Alert::MessagePosted {
+            channel: "drops".to_string(),
+            author: "shopbot".to_string(),
+            content: "Orders open now!".to_string(),
+            trigger: Some(TriggerMatch {
+                pattern: "(?i)orders open".to_string(),
+                text: "Orders open".to_string(),
+            }),
+        };
+
+        assert_eq!(alert.title(Language::En), "NEW MESSAGE");
+        assert_eq!(
+            alert.body(Language::En),
+            "shopbot in drops: Orders open now!\nTrigger: (?i)orders open (Orders open)"
+        );
+        assert_eq!(alert.source(), "channel");
+    }
+
+    #[test]
+    fn test_unacknowledged_wraps_alarm_text() {
+        let alert = Alert::Unacknowledged {
+            title: "CHANNEL OPEN".to_string(),
+            body: "Channel is now: start-order-✅".to_string(),
+            minutes: 5,
+        };
+
+        assert_eq!(alert.title(Language::En), "UNACKNOWLEDGED: CHANNEL OPEN");
+        assert_eq!(alert.body(Language::En), "Channel is now: start-order-✅\nRinging for 5 minutes");
+        assert_eq!(alert.title(Language::De), "UNBESTÄTIGT: CHANNEL OPEN");
+    }
+
+    #[test]
+    fn test_channel_inactive_text() {
+        let alert = Alert::ChannelInactive {
+            channel_id: "100".to_string(),
+            name: "drops-feed".to_string(),
+            idle_minutes: 90,
+        };
+
+        assert_eq!(alert.title(Language::En), "CHANNEL QUIET");
+        assert_eq!(alert.body(Language::En), "No new messages in drops-feed for 90 minutes");
+        assert_eq!(alert.source(), "inactive:100");
+    }
+
+    #[test]
+    fn test_alert_source() {
+        let open = Alert::ChannelOpen {
+            name: "start-order-✅".to_string(),
+            previous: None,
+            changed_by: None,
+            trigger: None,
+        };
+        let role = Alert::RolePermissionsChanged {
+            role: "Buyer".to_string(),
+            old: "0".to_string(),
+            new: "8".to_string(),
+        };
+
+        assert_eq!(open.source(), "channel");
+        assert_eq!(role.source(), "role:Buyer");
+    }
+
+    #[test]
+    fn test_grouped_alert_lists_and_truncates() {
+        let alerts: Vec<String> = (1..=7).map(|i| format!("ROLE CHANGED: Role appeared: r{}", i)).collect();
+        let alert = Alert::Grouped {
+            alerts,
+            changed: 0,
+            matched: 0,
+        };
+
+        assert_eq!(alert.title(Language::En), "7 MORE ALERTS");
+        let body = alert.body(Language::En);
+        assert_eq!(body.lines().count(), 6);
+        assert!(body.starts_with("ROLE CHANGED: Role appeared: r1\n"));
+        assert!(body.ends_with("...and 2 more"));
+
+        let single = Alert::Grouped {
+            alerts: vec!["STAGE LIVE: Stage is live: drop".to_string()],
+            changed: 1,
+            matched: 1,
+        };
+        assert_eq!(single.title(Language::En), "1 MORE ALERT");
+        assert_eq!(single.body(Language::En), "STAGE LIVE: Stage is live: drop");
+    }
+
+    #[test]
+    fn test_grouped_alert_counts_renames() {
+        let alert = Alert::Grouped {
+            alerts: vec!["ROLE CHANGED: Role appeared: r1".to_string()],
+            changed: 7,
+            matched: 1,
+        };
+
+        assert_eq!(alert.title(Language::En), "CHANNELS CHANGED");
+        assert_eq!(
+            alert.body(Language::En),
+            "7 channels changed, 1 matched open rules\nROLE CHANGED: Role appeared: r1"
+        );
+        assert!(alert.body(Language::Es).starts_with("7 canales cambiaron, 1 coincidió"));
+    }
+
+    #[test]
+    fn test_member_surge_plurals() {
+        let one = Alert::MemberSurge {
+            guild: "Shop".to_string(),
+            gained: 1,
+            total: 10,
+        };
+        let many = Alert::MemberSurge {
+            guild: "Shop".to_string(),
+            gained: 51,
+            total: 60,
+        };
+
+        assert_eq!(one.body(Language::En), "Shop gained 1 member in the last hour (now 10)");
+        assert_eq!(many.body(Language::En), "Shop gained 51 members in the last hour (now 60)");
+        assert!(one.body(Language::Es).contains("1 miembro "));
+        assert!(many.body(Language::De).contains("51 Mitglieder"));
+        assert_eq!(many.body(Language::Ja), "Shop のメンバーが過去1時間で51人増加 (現在 60人)");
+    }
+}
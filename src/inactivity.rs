@@ -0,0 +1,190 @@
+//! Inactivity detection for the monitored channels.
+//!
+//! Message activity is observed through each channel's `last_message_id` (REST
+//! polling) and `MESSAGE_CREATE` dispatches (gateway). When nothing new has
+//! arrived in a channel for the configured timeout, one alert is raised for it
+//! until activity resumes.
+
+use crate::clock::Instant;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Tracks when the monitored channel last saw a new message.
+#[derive(Debug)]
+pub struct ActivityTracker {
+    timeout: Duration,
+    last_message_id: Option<String>,
+    last_activity: Instant,
+    alerted: bool,
+}
+
+impl ActivityTracker {
+    /// Start tracking at `now`, alerting after `timeout` without messages.
+    pub fn new(timeout: Duration, now: Instant) -> Self {
+        Self {
+            timeout,
+            last_message_id: None,
+            last_activity: now,
+            alerted: false,
+        }
+    }
+
+    /// Record the latest message ID seen for the channel.
+    ///
+    /// The first ID seen only establishes a baseline. Returns true if this
+    /// message ended an alerted quiet period.
+    pub fn record_message(&mut self, message_id: &str, now: Instant) -> bool {
+        if self.last_message_id.as_deref() == Some(message_id) {
+            return false;
+        }
+        let baseline = self.last_message_id.is_none();
+        self.last_message_id = Some(message_id.to_string());
+        if baseline {
+            return false;
+        }
+
+        self.last_activity = now;
+        std::mem::take(&mut self.alerted)
+    }
+
+    /// Return how long the channel has been quiet if it just crossed the timeout.
+    ///
+    /// Reports once per quiet period.
+    pub fn check(&mut self, now: Instant) -> Option<Duration> {
+        let idle = now.duration_since(self.last_activity);
+        if self.alerted || idle < self.timeout {
+            return None;
+        }
+        self.alerted = true;
+        Some(idle)
+    }
+}
+
+/// One [`ActivityTracker`] per monitored channel.
+#[derive(Debug)]
+pub struct ChannelActivity {
+    timeout: Duration,
+    trackers: Mutex<HashMap<String, ActivityTracker>>,
+}
+
+impl ChannelActivity {
+    /// Track `channel_ids` from `now`, alerting after `timeout` without messages.
+    pub fn new(timeout: Duration, channel_ids: &[String], now: Instant) -> Self {
+        let activity = Self {
+            timeout,
+            trackers: Mutex::default(),
+        };
+        activity.set_channels(channel_ids, now);
+        activity
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Track exactly `channel_ids`, starting new ones at `now` and keeping the rest.
+    pub fn set_channels(&self, channel_ids: &[String], now: Instant) {
+        let mut trackers = self.trackers.lock().expect("activity tracker lock poisoned");
+        trackers.retain(|id, _| channel_ids.contains(id));
+        for id in channel_ids {
+            trackers.entry(id.clone()).or_insert_with(|| ActivityTracker::new(self.timeout, now));
+        }
+    }
+
+    /// Record the latest message ID seen in `channel_id`.
+    ///
+    /// Returns true if this message ended an alerted quiet period.
+    pub fn record_message(&self, channel_id: &str, message_id: &str, now: Instant) -> bool {
+        let mut trackers = self.trackers.lock().expect("activity tracker lock poisoned");
+        trackers
+            .entry(channel_id.to_string())
+            .or_insert_with(|| ActivityTracker::new(self.timeout, now))
+            .record_message(message_id, now)
+    }
+
+    /// Return the channels that just crossed the timeout, with how long each has been quiet.
+    pub fn check(&self, now: Instant) -> Vec<(String, Duration)> {
+        let mut trackers = self.trackers.lock().expect("activity tracker lock poisoned");
+        let mut quiet: Vec<(String, Duration)> = trackers
+            .iter_mut()
+            .filter_map(|(id, tracker)| tracker.check(now).map(|idle| (id.clone(), idle)))
+            .collect();
+        quiet.sort();
+        quiet
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(600);
+
+    #[test]
+    fn test_alerts_once_after_timeout() {
+        let start = Instant::now();
+        let mut tracker = ActivityTracker::new(TIMEOUT, start);
+
+        assert_eq!(tracker.check(start + Duration::from_secs(599)), None);
+        assert_eq!(tracker.check(start + TIMEOUT), Some(TIMEOUT));
+        assert_eq!(tracker.check(start + TIMEOUT * 2), None);
+    }
+
+    #[test]
+    fn test_new_message_resets_the_timer() {
+        let start = Instant::now();
+        let mut tracker = ActivityTracker::new(TIMEOUT, start);
+        tracker.record_message("1", start);
+
+        let later = start + Duration::from_secs(500);
+        assert!(!tracker.record_message("2", later));
+        assert_eq!(tracker.check(start + TIMEOUT), None);
+        assert!(tracker.check(later + TIMEOUT).is_some());
+
+        // Activity after an alert ends the quiet period and re-arms the alert
+        assert!(tracker.record_message("3", later + TIMEOUT * 2));
+        assert!(tracker.check(later + TIMEOUT * 3).is_some());
+    }
+
+    #[test]
+    fn test_repeated_message_id_is_not_activity() {
+        let start = Instant::now();
+        let mut tracker = ActivityTracker::new(TIMEOUT, start);
+        tracker.record_message("1", start);
+
+        tracker.record_message("1", start + Duration::from_secs(500));
+        assert!(tracker.check(start + TIMEOUT).is_some());
+    }
+
+    #[test]
+    fn test_first_message_id_is_baseline() {
+        let start = Instant::now();
+        let mut tracker = ActivityTracker::new(TIMEOUT, start);
+
+        // Seeing the existing last message at startup is not new activity
+        tracker.record_message("1", start + Duration::from_secs(500));
+        assert!(tracker.check(start + TIMEOUT).is_some());
+    }
+
+    #[test]
+    fn test_each_channel_has_its_own_timer() {
+        let start = Instant::now();
+        let activity = ChannelActivity::new(TIMEOUT, &["100".to_string(), "200".to_string()], start);
+        activity.record_message("100", "1", start);
+        activity.record_message("200", "5", start);
+
+        let later = start + Duration::from_secs(500);
+        activity.record_message("200", "6", later);
+        assert_eq!(activity.check(start + TIMEOUT), vec![("100".to_string(), TIMEOUT)]);
+        assert_eq!(activity.check(later + TIMEOUT), vec![("200".to_string(), TIMEOUT)]);
+
+        // A channel that is no longer monitored is dropped, a new one starts now
+        activity.set_channels(&["100".to_string(), "300".to_string()], later);
+        assert!(activity.record_message("100", "2", later + TIMEOUT));
+        assert_eq!(
+            activity.check(later + TIMEOUT * 2),
+            vec![("100".to_string(), TIMEOUT), ("300".to_string(), TIMEOUT * 2)]
+        );
+    }
+}
@@ -0,0 +1,36 @@
+//! Discord channel status monitor.
+//!
+//! [`monitor::Monitor`] watches channels over REST polling and the Gateway and
+//! publishes what it sees as [`events::MonitorEvent`]s; the `ollie-scraper`
+//! CLI is a thin consumer of this crate.
+
+mod alarm_queue;
+mod audio;
+mod audit_log;
+mod budget;
+mod clock;
+mod compound;
+pub mod config;
+mod discovery;
+mod etf;
+pub mod events;
+mod gateway;
+mod grouping;
+mod health;
+mod hooks;
+pub mod i18n;
+mod inactivity;
+mod log_settings;
+mod member_count;
+mod metrics;
+#[cfg(feature = "mock-discord")]
+pub mod mock_discord;
+pub mod models;
+pub mod monitor;
+mod name_diff;
+mod notifier;
+mod pause;
+mod playlist;
+mod rest;
+mod sinks;
+mod trigger;
@@ -0,0 +1,113 @@
+//! Log settings, as read from `LOG_LEVEL`, `LOG_FORMAT`, `LOG_ROTATION` and
+//! `LOG_MAX_FILES`.
+
+use clap::ValueEnum;
+
+/// Rotated log files kept by default.
+pub const DEFAULT_LOG_MAX_FILES: usize = 7;
+
+/// Log verbosity, from least to most verbose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    /// Parse a level name as accepted by `--log-level`, case-insensitive.
+    pub fn parse(value: &str) -> Option<Self> {
+        <Self as ValueEnum>::from_str(value.trim(), true).ok()
+    }
+
+    /// Name as accepted by `--log-level`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Level::Error => "error",
+            Level::Warn => "warn",
+            Level::Info => "info",
+            Level::Debug => "debug",
+            Level::Trace => "trace",
+        }
+    }
+}
+
+/// Format of log file lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    /// The same lines as on the console, without color.
+    #[default]
+    Text,
+    /// One JSON object per line.
+    Json,
+}
+
+impl LogFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "text" => Some(LogFormat::Text),
+            "json" => Some(LogFormat::Json),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogFormat::Text => "text",
+            LogFormat::Json => "json",
+        }
+    }
+}
+
+/// How often a new log file is started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogRotation {
+    #[default]
+    Daily,
+    Hourly,
+    /// Always append to the one `LOG_PATH` file.
+    Never,
+}
+
+impl LogRotation {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "daily" => Some(LogRotation::Daily),
+            "hourly" => Some(LogRotation::Hourly),
+            "never" => Some(LogRotation::Never),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogRotation::Daily => "daily",
+            LogRotation::Hourly => "hourly",
+            LogRotation::Never => "never",
+        }
+    }
+}
+
+/// Log settings read from the environment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogSettings {
+    /// Level used when none is given with `-v`, `-q` or `--log-level`.
+    pub level: Option<Level>,
+    pub format: LogFormat,
+    pub rotation: LogRotation,
+    /// Rotated files kept before the oldest is deleted; 0 keeps all.
+    pub max_files: usize,
+}
+
+impl Default for LogSettings {
+    fn default() -> Self {
+        Self {
+            level: None,
+            format: LogFormat::default(),
+            rotation: LogRotation::default(),
+            max_files: DEFAULT_LOG_MAX_FILES,
+        }
+    }
+}
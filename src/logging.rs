@@ -0,0 +1,406 @@
+//! Leveled logging built on `tracing`.
+//!
+//! The active level is process-global and set once at startup from the CLI
+//! flags or `LOG_LEVEL`. Use the `error!`, `warn!`, `info!`, `debug!` and
+//! `trace!` macros re-exported here instead of `println!`/`eprintln!` for
+//! anything that is log output rather than command output.
+//!
+//! Console lines are prefixed with a timestamp (in the configured display
+//! timezone) and level, and a leading source tag such as `[WS]` or `[POLL]` is
+//! colorized when color output is enabled. With `run --log-file` the lines go to
+//! rolling files next to `LOG_PATH` instead, as text or JSON (`LOG_FORMAT`).
+
+use crate::timezone;
+use ollie_scraper::config::{Level, LogFormat, LogRotation, LogSettings};
+use std::fmt;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU8, Ordering};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::{LevelFilter, Targets};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::writer::MakeWriterExt;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{Layer, Registry};
+
+static LEVEL: AtomicU8 = AtomicU8::new(Level::Info as u8);
+
+const RESET: &str = "\x1b[0m";
+const BOLD_RED: &str = "\x1b[1;31m";
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const BLUE: &str = "\x1b[34m";
+const MAGENTA: &str = "\x1b[35m";
+const CYAN: &str = "\x1b[36m";
+const DIM: &str = "\x1b[2m";
+
+/// Fixed-width label used in log lines.
+fn level_label(level: Level) -> &'static str {
+    match level {
+        Level::Error => "ERROR",
+        Level::Warn => "WARN ",
+        Level::Info => "INFO ",
+        Level::Debug => "DEBUG",
+        Level::Trace => "TRACE",
+    }
+}
+
+fn level_color(level: Level) -> &'static str {
+    match level {
+        Level::Error => RED,
+        Level::Warn => YELLOW,
+        Level::Info => "",
+        Level::Debug | Level::Trace => DIM,
+    }
+}
+
+fn from_tracing(level: &tracing::Level) -> Level {
+    match *level {
+        tracing::Level::ERROR => Level::Error,
+        tracing::Level::WARN => Level::Warn,
+        tracing::Level::INFO => Level::Info,
+        tracing::Level::DEBUG => Level::Debug,
+        tracing::Level::TRACE => Level::Trace,
+    }
+}
+
+fn level_filter(level: Level) -> LevelFilter {
+    match level {
+        Level::Error => LevelFilter::ERROR,
+        Level::Warn => LevelFilter::WARN,
+        Level::Info => LevelFilter::INFO,
+        Level::Debug => LevelFilter::DEBUG,
+        Level::Trace => LevelFilter::TRACE,
+    }
+}
+
+fn rotation(rotation: LogRotation) -> Rotation {
+    match rotation {
+        LogRotation::Daily => Rotation::DAILY,
+        LogRotation::Hourly => Rotation::HOURLY,
+        LogRotation::Never => Rotation::NEVER,
+    }
+}
+
+/// The rolling log files for a `LOG_PATH` such as `/var/log/scraper.log`.
+///
+/// Rotated files are named `scraper.<date>.log` in the same directory. The raw
+/// output of the daemon process, such as startup errors and panics, goes to
+/// `scraper.out` beside them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogFiles {
+    dir: PathBuf,
+    prefix: String,
+    suffix: String,
+}
+
+impl LogFiles {
+    pub fn new(path: &Path) -> Self {
+        let name = |part: Option<&std::ffi::OsStr>| part.map(|p| p.to_string_lossy().to_string());
+        Self {
+            dir: path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new(".")).to_path_buf(),
+            prefix: name(path.file_stem()).unwrap_or_else(|| "scraper".to_string()),
+            suffix: name(path.extension()).unwrap_or_else(|| "log".to_string()),
+        }
+    }
+
+    /// Path of the log files with `rotation`, `*` standing for the date.
+    pub fn pattern(&self, rotation: LogRotation) -> PathBuf {
+        match rotation {
+            LogRotation::Never => self.dir.join(format!("{}.{}", self.prefix, self.suffix)),
+            _ => self.dir.join(format!("{}.*.{}", self.prefix, self.suffix)),
+        }
+    }
+
+    /// File capturing the daemon's stdout and stderr.
+    pub fn output_path(&self) -> PathBuf {
+        self.dir.join(format!("{}.out", self.prefix))
+    }
+
+    /// The most recently written log file, if any.
+    pub fn latest(&self) -> Option<PathBuf> {
+        let plain = format!("{}.{}", self.prefix, self.suffix);
+        let (start, end) = (format!("{}.", self.prefix), format!(".{}", self.suffix));
+        std::fs::read_dir(&self.dir)
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                name == plain || (name.starts_with(&start) && name.ends_with(&end))
+            })
+            .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+            .max()
+            .map(|(_, path)| path)
+    }
+
+    fn appender(&self, settings: &LogSettings) -> Result<RollingFileAppender, String> {
+        RollingFileAppender::builder()
+            .rotation(rotation(settings.rotation))
+            .filename_prefix(&self.prefix)
+            .filename_suffix(&self.suffix)
+            .max_log_files(settings.max_files)
+            .build(&self.dir)
+            .map_err(|e| format!("Failed to open log file in {}: {}", self.dir.display(), e))
+    }
+}
+
+/// Resolve the effective level from `-v`/`-q` counts, an explicit
+/// `--log-level`, and the `LOG_LEVEL` setting.
+///
+/// An explicit level always wins; otherwise each `-v` raises verbosity one step
+/// above `info` and `-q` lowers it to `warn`. Without either flag `LOG_LEVEL`
+/// applies.
+pub fn resolve_level(verbose: u8, quiet: bool, explicit: Option<Level>, setting: Option<Level>) -> Level {
+    if let Some(level) = explicit {
+        return level;
+    }
+    if quiet {
+        return Level::Warn;
+    }
+    match verbose {
+        0 => setting.unwrap_or(Level::Info),
+        1 => Level::Debug,
+        _ => Level::Trace,
+    }
+}
+
+/// Set the process-wide log level.
+pub fn set_level(level: Level) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// Get the process-wide log level.
+pub fn level() -> Level {
+    match LEVEL.load(Ordering::Relaxed) {
+        0 => Level::Error,
+        1 => Level::Warn,
+        2 => Level::Info,
+        3 => Level::Debug,
+        _ => Level::Trace,
+    }
+}
+
+/// Check whether messages at `level` should be emitted.
+pub fn enabled(level: Level) -> bool {
+    level <= self::level()
+}
+
+/// Decide whether to colorize output: not disabled by `--no-color` or a
+/// non-empty `NO_COLOR`, and stdout is a terminal (daemon logs stay plain).
+pub fn should_color(no_color_flag: bool) -> bool {
+    let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    !no_color_flag && !no_color_env && std::io::stdout().is_terminal()
+}
+
+/// Color for a source tag like `WS` or `POLL`.
+fn tag_color(tag: &str) -> &'static str {
+    match tag {
+        "WS" => CYAN,
+        "POLL" => BLUE,
+        "ALARM" => BOLD_RED,
+        "SIM" | "CTL" => MAGENTA,
+        "HEALTH" => GREEN,
+        _ => YELLOW,
+    }
+}
+
+/// Split a leading `[TAG]` off a message, if present.
+fn split_tag(message: &str) -> Option<(&str, &str)> {
+    let rest = message.strip_prefix('[')?;
+    let end = rest.find(']')?;
+    let tag = &rest[..end];
+    if tag.is_empty() || !tag.chars().all(|c| c.is_ascii_uppercase()) {
+        return None;
+    }
+    Some((tag, &rest[end + 1..]))
+}
+
+/// Format a log line with timestamp, level, and (optionally colorized) source tag.
+fn format_line(level: Level, timestamp: &str, message: &str, color: bool) -> String {
+    // Keep leading blank lines (e.g. after ^C) ahead of the prefix
+    let body = message.trim_start_matches('\n');
+    let newlines = &message[..message.len() - body.len()];
+
+    if !color {
+        return format!("{}{} {} {}", newlines, timestamp, level_label(level), body);
+    }
+
+    let body = match split_tag(body) {
+        Some((tag, rest)) => format!("{}[{}]{}{}", tag_color(tag), tag, RESET, rest),
+        None => body.to_string(),
+    };
+    format!(
+        "{}{}{}{} {}{}{} {}",
+        newlines,
+        DIM,
+        timestamp,
+        RESET,
+        level_color(level),
+        level_label(level),
+        RESET,
+        body
+    )
+}
+
+/// Collects an event's message and any other fields.
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.0.insert_str(0, &format!("{:?}", value));
+        } else {
+            self.0.push_str(&format!(" {}={:?}", field.name(), value));
+        }
+    }
+}
+
+/// Formats events as [`format_line`] lines.
+struct LineFormat {
+    color: bool,
+}
+
+impl<S, N> FormatEvent<S, N> for LineFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, _ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> fmt::Result {
+        let mut visitor = MessageVisitor(String::new());
+        event.record(&mut visitor);
+        let level = from_tracing(event.metadata().level());
+        let timestamp = timezone::format(chrono::Utc::now());
+        writeln!(writer, "{}", format_line(level, &timestamp, &visitor.0, self.color))
+    }
+}
+
+/// Install the global subscriber at `level`.
+///
+/// Logs go to the console, errors and warnings on stderr, unless `files` is
+/// given, in which case they go to those rolling files in the configured format.
+/// Only this crate's events are logged, not those of its dependencies.
+pub fn init(level: Level, color: bool, settings: &LogSettings, files: Option<&LogFiles>) -> Result<(), String> {
+    set_level(level);
+    let layer: Box<dyn Layer<Registry> + Send + Sync> = match files {
+        None => {
+            let console = std::io::stderr.with_max_level(tracing::Level::WARN).or_else(std::io::stdout);
+            Box::new(tracing_subscriber::fmt::layer().event_format(LineFormat { color }).with_writer(console))
+        }
+        Some(files) => {
+            let appender = files.appender(settings)?;
+            match settings.format {
+                LogFormat::Text => Box::new(
+                    tracing_subscriber::fmt::layer()
+                        .event_format(LineFormat { color: false })
+                        .with_writer(appender),
+                ),
+                LogFormat::Json => Box::new(
+                    tracing_subscriber::fmt::layer()
+                        .json()
+                        .flatten_event(true)
+                        .with_current_span(false)
+                        .with_span_list(false)
+                        .with_writer(appender),
+                ),
+            }
+        }
+    };
+    let filter = Targets::new().with_target(env!("CARGO_CRATE_NAME"), level_filter(level));
+    tracing::subscriber::set_global_default(tracing_subscriber::registry().with(layer.with_filter(filter)))
+        .map_err(|e| format!("Failed to set up logging: {}", e))
+}
+
+pub use tracing::{error, info, warn};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_level_defaults_to_info() {
+        assert_eq!(resolve_level(0, false, None, None), Level::Info);
+    }
+
+    #[test]
+    fn test_resolve_level_verbosity_flags() {
+        assert_eq!(resolve_level(1, false, None, None), Level::Debug);
+        assert_eq!(resolve_level(2, false, None, None), Level::Trace);
+        assert_eq!(resolve_level(5, false, None, None), Level::Trace);
+        assert_eq!(resolve_level(0, true, None, None), Level::Warn);
+    }
+
+    #[test]
+    fn test_explicit_log_level_wins() {
+        assert_eq!(resolve_level(2, false, Some(Level::Error), None), Level::Error);
+        assert_eq!(resolve_level(0, true, Some(Level::Debug), None), Level::Debug);
+    }
+
+    #[test]
+    fn test_log_level_setting_applies_without_flags() {
+        assert_eq!(resolve_level(0, false, None, Some(Level::Debug)), Level::Debug);
+        assert_eq!(resolve_level(1, false, None, Some(Level::Error)), Level::Debug);
+        assert_eq!(resolve_level(0, true, None, Some(Level::Debug)), Level::Warn);
+        assert_eq!(Level::parse("WARN"), Some(Level::Warn));
+        assert_eq!(Level::parse("loud"), None);
+    }
+
+    #[test]
+    fn test_log_files_find_latest_rotated_file() {
+        let dir = std::env::temp_dir().join(format!("ollie-logs-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let files = LogFiles::new(&dir.join("scraper.log"));
+        assert_eq!(files.latest(), None);
+        assert_eq!(files.output_path(), dir.join("scraper.out"));
+
+        std::fs::write(dir.join("scraper.2025-01-23.log"), "old").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        std::fs::write(dir.join("scraper.2025-01-24.log"), "new").unwrap();
+        std::fs::write(dir.join("other.log"), "").unwrap();
+        // The raw output capture is not a log file
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        std::fs::write(files.output_path(), "").unwrap();
+
+        assert_eq!(files.latest(), Some(dir.join("scraper.2025-01-24.log")));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_format_line_plain() {
+        let line = format_line(Level::Info, "2025-01-24 12:00:00", "[WS] Connected to Gateway", false);
+        assert_eq!(line, "2025-01-24 12:00:00 INFO  [WS] Connected to Gateway");
+    }
+
+    #[test]
+    fn test_format_line_keeps_leading_newlines() {
+        let line = format_line(Level::Info, "ts", "\nReceived Ctrl+C", false);
+        assert_eq!(line, "\nts INFO  Received Ctrl+C");
+    }
+
+    #[test]
+    fn test_format_line_colors_source_tag() {
+        let line = format_line(Level::Warn, "ts", "[POLL] Failed", true);
+        assert!(line.contains(&format!("{}[POLL]{}", BLUE, RESET)));
+        assert!(line.contains(&format!("{}WARN {}", YELLOW, RESET)));
+        assert!(line.ends_with(" Failed"));
+    }
+
+    #[test]
+    fn test_split_tag() {
+        assert_eq!(split_tag("[ALARM] ringing"), Some(("ALARM", " ringing")));
+        assert_eq!(split_tag("Initial channel name: Some(\"x\")"), None);
+        assert_eq!(split_tag("[not a tag] x"), None);
+        assert_eq!(split_tag("[] x"), None);
+    }
+
+    #[test]
+    fn test_level_ordering() {
+        assert!(Level::Error < Level::Warn);
+        assert!(Level::Info < Level::Debug);
+        assert_eq!(Level::Trace.as_str(), "trace");
+    }
+}
@@ -0,0 +1,100 @@
+//! Guild member-count jump detection.
+//!
+//! Keeps a sliding window of member-count samples and reports when the count
+//! rises by more than a configured threshold within that window.
+
+use crate::clock::Instant;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Window over which member-count growth is measured.
+pub const MEMBER_JUMP_WINDOW: Duration = Duration::from_secs(3600);
+
+/// Sliding-window tracker for guild member counts.
+#[derive(Debug)]
+pub struct MemberCountTracker {
+    samples: VecDeque<(Instant, u64)>,
+    window: Duration,
+    threshold: u64,
+}
+
+impl MemberCountTracker {
+    /// Create a tracker that reports jumps larger than `threshold` within `window`.
+    pub fn new(threshold: u64, window: Duration) -> Self {
+        Self {
+            samples: VecDeque::new(),
+            window,
+            threshold,
+        }
+    }
+
+    /// Record a sample, returning the jump size if it exceeds the threshold.
+    ///
+    /// After a jump is reported the window restarts from the current count so the
+    /// same growth does not alarm repeatedly.
+    pub fn record(&mut self, now: Instant, count: u64) -> Option<u64> {
+        while let Some(&(at, _)) = self.samples.front() {
+            if now.duration_since(at) > self.window {
+                self.samples.pop_front();
+            } else {
+                break;
+            }
+        }
+        self.samples.push_back((now, count));
+
+        let baseline = self.samples.iter().map(|&(_, c)| c).min()?;
+        let jump = count.saturating_sub(baseline);
+        if jump > self.threshold {
+            self.samples.clear();
+            self.samples.push_back((now, count));
+            Some(jump)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jump_within_window_is_reported() {
+        let start = Instant::now();
+        let mut tracker = MemberCountTracker::new(50, MEMBER_JUMP_WINDOW);
+
+        assert_eq!(tracker.record(start, 1000), None);
+        assert_eq!(tracker.record(start + Duration::from_secs(600), 1030), None);
+        assert_eq!(tracker.record(start + Duration::from_secs(1200), 1051), Some(51));
+    }
+
+    #[test]
+    fn test_jump_is_not_reported_twice() {
+        let start = Instant::now();
+        let mut tracker = MemberCountTracker::new(10, MEMBER_JUMP_WINDOW);
+
+        tracker.record(start, 100);
+        assert_eq!(tracker.record(start + Duration::from_secs(60), 120), Some(20));
+        assert_eq!(tracker.record(start + Duration::from_secs(120), 125), None);
+    }
+
+    #[test]
+    fn test_old_samples_leave_the_window() {
+        let start = Instant::now();
+        let mut tracker = MemberCountTracker::new(50, Duration::from_secs(3600));
+
+        tracker.record(start, 1000);
+        tracker.record(start + Duration::from_secs(1800), 1040);
+        // The 1000 sample is now outside the window; growth is measured from 1040
+        assert_eq!(tracker.record(start + Duration::from_secs(3700), 1080), None);
+    }
+
+    #[test]
+    fn test_member_drop_is_ignored() {
+        let start = Instant::now();
+        let mut tracker = MemberCountTracker::new(5, MEMBER_JUMP_WINDOW);
+
+        tracker.record(start, 500);
+        assert_eq!(tracker.record(start + Duration::from_secs(60), 400), None);
+    }
+}
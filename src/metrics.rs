@@ -0,0 +1,237 @@
+//! Prometheus metrics and a health check over HTTP.
+//!
+//! With `METRICS_ADDR` set, the monitor serves its counters on `/metrics` in
+//! the Prometheus text format, and `/healthz` answers 503 once neither the
+//! Gateway nor REST polling is making progress, so an outside check can alert
+//! when the scraper is stuck.
+
+use crate::health::Health;
+use crate::monitor::ChannelNames;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, error};
+
+/// With the Gateway down, `/healthz` fails once the last successful poll is this old.
+pub const STALE_POLL_AGE: Duration = Duration::from_secs(120);
+
+/// How long a client has to send its request line and headers.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Most bytes of request line and headers read from a client.
+const MAX_REQUEST_BYTES: u64 = 8 * 1024;
+
+/// Whether the monitor is making progress: the Gateway is up or a poll succeeded recently.
+pub fn is_healthy(health: &Health) -> bool {
+    health.ws_connected() || health.last_poll_age().is_some_and(|age| age < STALE_POLL_AGE)
+}
+
+/// Escape a Prometheus label value.
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Render every metric in the Prometheus text format.
+pub fn render(health: &Health, names: &HashMap<String, String>) -> String {
+    let stats = health.stats();
+    let mut out = String::new();
+    let mut metric = |name: &str, kind: &str, help: &str, value: String| {
+        let _ = writeln!(out, "# HELP {} {}\n# TYPE {} {}\n{} {}", name, help, name, kind, name, value);
+    };
+
+    metric("ollie_uptime_seconds", "gauge", "Seconds since the monitor started.", stats.uptime_secs.to_string());
+    metric(
+        "ollie_gateway_connected",
+        "gauge",
+        "Whether the Gateway connection is identified.",
+        u8::from(health.ws_connected()).to_string(),
+    );
+    metric(
+        "ollie_gateway_connects_total",
+        "counter",
+        "Gateway connections that reached READY or RESUMED.",
+        stats.gateway_sessions.to_string(),
+    );
+    if let Some(latency) = health.heartbeat_latency() {
+        metric(
+            "ollie_heartbeat_latency_seconds",
+            "gauge",
+            "Time between the last heartbeat and its ACK.",
+            latency.as_secs_f64().to_string(),
+        );
+    }
+    metric("ollie_polls_total", "counter", "Successful REST polls.", stats.polls.to_string());
+    metric("ollie_poll_failures_total", "counter", "Failed REST polls.", stats.poll_failures.to_string());
+    if let Some(age) = health.last_poll_age() {
+        metric(
+            "ollie_last_poll_age_seconds",
+            "gauge",
+            "Seconds since the last successful REST poll.",
+            age.as_secs_f64().to_string(),
+        );
+    }
+    metric(
+        "ollie_rate_limits_total",
+        "counter",
+        "REST requests answered with 429 Too Many Requests.",
+        stats.rate_limits.to_string(),
+    );
+    metric("ollie_alarms_total", "counter", "Alarms that rang.", stats.alarms.to_string());
+    metric("ollie_renames_total", "counter", "Channel renames detected.", stats.renames.to_string());
+    metric("ollie_reloads_total", "counter", "Configuration reloads.", stats.reloads.to_string());
+
+    let _ = writeln!(out, "# HELP ollie_channel_info Current name of each monitored channel.");
+    let _ = writeln!(out, "# TYPE ollie_channel_info gauge");
+    let mut channels: Vec<_> = names.iter().collect();
+    channels.sort();
+    for (id, name) in channels {
+        let _ = writeln!(
+            out,
+            "ollie_channel_info{{channel_id=\"{}\",name=\"{}\"}} 1",
+            escape_label(id),
+            escape_label(name)
+        );
+    }
+    out
+}
+
+/// Serve `/metrics` and `/healthz` until the listener fails.
+pub async fn serve(listener: TcpListener, health: Arc<Health>, names: ChannelNames) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                let health = Arc::clone(&health);
+                let names = Arc::clone(&names);
+                tokio::spawn(async move {
+                    if let Err(e) = handle(stream, &health, &names).await {
+                        debug!("[METRICS] Connection failed: {}", e);
+                    }
+                });
+            }
+            Err(e) => {
+                error!("[METRICS] Accept failed: {}", e);
+                return;
+            }
+        }
+    }
+}
+
+/// Read the request line, skipping the headers after it.
+async fn read_request_line<R: AsyncBufReadExt + Unpin>(reader: &mut R) -> std::io::Result<String> {
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).await? == 0 || header.trim().is_empty() {
+            break;
+        }
+    }
+    Ok(request_line)
+}
+
+/// Answer a single HTTP request.
+///
+/// A client that is slow to send its headers is dropped, and anything past
+/// [`MAX_REQUEST_BYTES`] is not read.
+async fn handle(stream: TcpStream, health: &Health, names: &ChannelNames) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.take(MAX_REQUEST_BYTES));
+    let request_line = tokio::time::timeout(REQUEST_TIMEOUT, read_request_line(&mut reader))
+        .await
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::TimedOut, "request not received in time"))??;
+
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let path = path.split('?').next().unwrap_or(path);
+    let (status, body) = match (method, path) {
+        ("GET", "/metrics") => ("200 OK", render(health, &*names.read().await)),
+        ("GET", "/healthz") if is_healthy(health) => ("200 OK", "ok\n".to_string()),
+        ("GET", "/healthz") => ("503 Service Unavailable", "stale\n".to_string()),
+        ("GET", _) => ("404 Not Found", "not found\n".to_string()),
+        _ => ("405 Method Not Allowed", "method not allowed\n".to_string()),
+    };
+    debug!("[METRICS] {} -> {}", request_line.trim(), status);
+
+    let mut stream = reader.into_inner().into_inner();
+    stream
+        .write_all(
+            format!(
+                "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            )
+            .as_bytes(),
+        )
+        .await?;
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::RwLock;
+
+    #[test]
+    fn test_render_counters_and_channel_labels() {
+        let health = Health::default();
+        health.set_ws_connected(true);
+        health.record_poll();
+        health.record_rate_limit();
+        health.record_heartbeat_latency(Duration::from_millis(250));
+        let names = HashMap::from([("100".to_string(), "drops \"open\"".to_string())]);
+
+        let text = render(&health, &names);
+        assert!(text.contains("# TYPE ollie_gateway_connects_total counter\nollie_gateway_connects_total 1\n"));
+        assert!(text.contains("\nollie_gateway_connected 1\n"));
+        assert!(text.contains("\nollie_polls_total 1\n"));
+        assert!(text.contains("\nollie_rate_limits_total 1\n"));
+        assert!(text.contains("\nollie_heartbeat_latency_seconds 0.25\n"));
+        assert!(text.contains("ollie_channel_info{channel_id=\"100\",name=\"drops \\\"open\\\"\"} 1\n"));
+    }
+
+    #[test]
+    fn test_healthy_while_connected_or_polling() {
+        let health = Health::default();
+        assert!(!is_healthy(&health));
+        health.record_poll();
+        assert!(is_healthy(&health));
+
+        let health = Health::default();
+        health.set_ws_connected(true);
+        assert!(is_healthy(&health));
+    }
+
+    #[tokio::test]
+    async fn test_serves_metrics_and_healthz() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let health = Arc::new(Health::default());
+        let names: ChannelNames = Arc::new(RwLock::new(HashMap::from([("100".to_string(), "drops".to_string())])));
+        tokio::spawn(serve(listener, Arc::clone(&health), names));
+
+        let get = |path: &str| reqwest::get(format!("http://{}{}", addr, path));
+        assert_eq!(get("/healthz").await.unwrap().status(), 503);
+        health.record_poll();
+        assert_eq!(get("/healthz").await.unwrap().status(), 200);
+
+        let metrics = get("/metrics").await.unwrap().text().await.unwrap();
+        assert!(metrics.contains("ollie_channel_info{channel_id=\"100\",name=\"drops\"} 1"));
+        assert_eq!(get("/nope").await.unwrap().status(), 404);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_drops_clients_that_never_finish_the_request() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, Arc::new(Health::default()), ChannelNames::default()));
+
+        let mut client = TcpStream::connect(addr).await.unwrap();
+        client.write_all(b"GET /metrics HTTP/1.1\r\n").await.unwrap();
+        let mut response = Vec::new();
+        client.read_to_end(&mut response).await.unwrap();
+        assert!(response.is_empty());
+    }
+}
@@ -0,0 +1,1281 @@
+//! Mock Discord REST API and Gateway for offline development and tests.
+//!
+//! Built with the `mock-discord` feature. The REST side answers channel, guild
+//! and audit log lookups from in-memory tables; the Gateway side speaks just enough
+//! of the protocol (Hello, Identify, Resume, Heartbeat ACK, READY) to drive
+//! `websocket_loop`, then forwards scripted dispatch events to every session.
+//! Point `DISCORD_API_BASE` and `DISCORD_GATEWAY_URL` at it to run the monitor
+//! without a real token.
+//!
+//! In chaos mode the mock randomly drops connections, sends invalid-session
+//! opcodes and malformed frames, and answers REST slowly or with 429s, to check
+//! that reconnects and the REST fallback keep the monitor from going blind.
+
+use crate::etf;
+use crate::gateway::{Codec, Compression, Encoding, Transport};
+use crate::models::GatewayMessage;
+use flate2::{Compress, FlushCompress};
+use futures_util::{SinkExt, StreamExt};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, watch};
+use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, error, info};
+
+/// Heartbeat interval announced in Hello.
+const MOCK_HEARTBEAT_INTERVAL_MS: u64 = 41250;
+/// Member count reported for every mock guild.
+const MOCK_MEMBER_COUNT: u64 = 1000;
+/// How long a slow REST response is held back by default.
+const DEFAULT_SLOW_DELAY: Duration = Duration::from_millis(500);
+
+/// Probabilities (0.0 to 1.0) of each injected fault.
+#[derive(Debug, Clone, Default)]
+pub struct Chaos {
+    /// Drop the Gateway connection instead of sending a dispatch.
+    pub disconnect: f64,
+    /// Send Invalid Session (op 9) and close instead of sending a dispatch.
+    pub invalid_session: f64,
+    /// Send a truncated frame before a dispatch.
+    pub malformed: f64,
+    /// Answer a REST request with 429 Too Many Requests.
+    pub rate_limit: f64,
+    /// Hold a REST response back for `slow_delay`.
+    pub slow: f64,
+    pub slow_delay: Duration,
+    /// Seed for the fault dice, so a failing run can be replayed.
+    pub seed: u64,
+}
+
+impl Chaos {
+    /// Every fault at the same `rate`.
+    pub fn uniform(rate: f64, seed: u64) -> Self {
+        let rate = rate.clamp(0.0, 1.0);
+        Self {
+            disconnect: rate,
+            invalid_session: rate,
+            malformed: rate,
+            rate_limit: rate,
+            slow: rate,
+            slow_delay: DEFAULT_SLOW_DELAY,
+            seed,
+        }
+    }
+}
+
+/// How many faults of each kind were injected.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FaultCounts {
+    pub disconnects: u64,
+    pub invalid_sessions: u64,
+    pub malformed: u64,
+    pub rate_limited: u64,
+    pub slow: u64,
+}
+
+/// Shared state between the servers and the handle.
+struct MockState {
+    /// Channel names by ID, as served over REST.
+    channels: Mutex<HashMap<String, String>>,
+    /// Audit log entries, newest first.
+    audit_log: Mutex<Vec<Value>>,
+    sequence: AtomicU64,
+    next_session: AtomicU64,
+    dispatches: broadcast::Sender<String>,
+    /// Close codes to end every session with.
+    closes: broadcast::Sender<u16>,
+    identified: watch::Sender<usize>,
+    resumed: watch::Sender<usize>,
+    /// Sessions the client closed with a normal Close frame.
+    closed: watch::Sender<usize>,
+    /// Session IDs handed out in READY that can still be resumed.
+    sessions: Mutex<HashSet<String>>,
+    chaos: Chaos,
+    rng: Mutex<StdRng>,
+    faults: Mutex<FaultCounts>,
+}
+
+impl MockState {
+    /// Roll for a fault with probability `rate`, counting it when it hits.
+    fn inject(&self, rate: f64, count: impl FnOnce(&mut FaultCounts)) -> bool {
+        let hit = rate > 0.0 && self.rng.lock().unwrap_or_else(|e| e.into_inner()).gen_bool(rate.min(1.0));
+        if hit {
+            count(&mut self.faults.lock().unwrap_or_else(|e| e.into_inner()));
+        }
+        hit
+    }
+
+    /// Serialize a dispatch (op 0) with the next sequence number.
+    fn dispatch_json(&self, event: &str, d: Value) -> String {
+        let message = GatewayMessage {
+            op: 0,
+            s: Some(self.sequence.fetch_add(1, Ordering::SeqCst) + 1),
+            t: Some(event.to_string()),
+            d: Some(d),
+        };
+        serde_json::to_string(&message).expect("Failed to serialize mock dispatch")
+    }
+}
+
+/// A running mock Discord, serving REST and Gateway on two local ports.
+pub struct MockDiscord {
+    rest_addr: SocketAddr,
+    gateway_addr: SocketAddr,
+    state: Arc<MockState>,
+}
+
+impl MockDiscord {
+    /// Bind REST on `port` and the Gateway on `port + 1`; port 0 picks free ports.
+    pub async fn start(port: u16) -> std::io::Result<Self> {
+        Self::start_with_chaos(port, Chaos::default()).await
+    }
+
+    /// Like `start`, injecting faults according to `chaos`.
+    pub async fn start_with_chaos(port: u16, chaos: Chaos) -> std::io::Result<Self> {
+        let rest = TcpListener::bind(("127.0.0.1", port)).await?;
+        let gateway_port = if port == 0 { 0 } else { port + 1 };
+        let gateway = TcpListener::bind(("127.0.0.1", gateway_port)).await?;
+
+        let state = Arc::new(MockState {
+            channels: Mutex::new(HashMap::new()),
+            audit_log: Mutex::new(Vec::new()),
+            sequence: AtomicU64::new(0),
+            next_session: AtomicU64::new(0),
+            dispatches: broadcast::channel(64).0,
+            closes: broadcast::channel(4).0,
+            identified: watch::channel(0).0,
+            resumed: watch::channel(0).0,
+            closed: watch::channel(0).0,
+            sessions: Mutex::new(HashSet::new()),
+            rng: Mutex::new(StdRng::seed_from_u64(chaos.seed)),
+            chaos,
+            faults: Mutex::new(FaultCounts::default()),
+        });
+
+        let mock = Self {
+            rest_addr: rest.local_addr()?,
+            gateway_addr: gateway.local_addr()?,
+            state,
+        };
+        tokio::spawn(serve_rest(rest, Arc::clone(&mock.state)));
+        tokio::spawn(serve_gateway(gateway, Arc::clone(&mock.state)));
+        Ok(mock)
+    }
+
+    /// Value for `DISCORD_API_BASE`.
+    pub fn api_base(&self) -> String {
+        format!("http://{}/api/v9", self.rest_addr)
+    }
+
+    /// Value for `DISCORD_GATEWAY_URL`.
+    pub fn gateway_url(&self) -> String {
+        format!("ws://{}", self.gateway_addr)
+    }
+
+    /// Set a channel's name as seen by REST, without notifying the Gateway.
+    pub fn set_channel(&self, channel_id: &str, name: &str) {
+        self.state
+            .channels
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(channel_id.to_string(), name.to_string());
+    }
+
+    /// Rename a channel over REST and send the matching CHANNEL_UPDATE.
+    pub fn rename_channel(&self, channel_id: &str, name: &str) {
+        self.set_channel(channel_id, name);
+        self.dispatch("CHANNEL_UPDATE", json!({ "id": channel_id, "name": name, "type": 0 }));
+    }
+
+    /// Create a channel in `guild_id` and send the matching CHANNEL_CREATE.
+    pub fn create_channel(&self, guild_id: &str, channel_id: &str, name: &str) {
+        self.set_channel(channel_id, name);
+        self.dispatch(
+            "CHANNEL_CREATE",
+            json!({ "id": channel_id, "name": name, "type": 0, "guild_id": guild_id }),
+        );
+    }
+
+    /// Delete a channel of `guild_id` and send the matching CHANNEL_DELETE.
+    pub fn delete_channel(&self, guild_id: &str, channel_id: &str) {
+        let name = self.state.channels.lock().unwrap_or_else(|e| e.into_inner()).remove(channel_id);
+        self.dispatch(
+            "CHANNEL_DELETE",
+            json!({ "id": channel_id, "name": name, "type": 0, "guild_id": guild_id }),
+        );
+    }
+
+    /// Post a message by `author` (a username) and send the matching MESSAGE_CREATE.
+    pub fn post_message(&self, channel_id: &str, author: &str, content: &str) {
+        // Message IDs only need to be unique, and the next sequence number is
+        let id = self.state.sequence.load(Ordering::SeqCst) + 1;
+        self.dispatch(
+            "MESSAGE_CREATE",
+            json!({
+                "id": id.to_string(),
+                "channel_id": channel_id,
+                "content": content,
+                "author": { "id": format!("user-{}", author), "username": author },
+            }),
+        );
+    }
+
+    /// Add a channel rename by `user` to the audit log only, as if the Gateway
+    /// event was lost and the channel read is stale.
+    #[cfg(test)]
+    pub fn log_rename(&self, channel_id: &str, previous: &str, name: &str, user: &str) {
+        let mut audit_log = self.state.audit_log.lock().unwrap_or_else(|e| e.into_inner());
+        let id = audit_log.len() + 1;
+        audit_log.insert(
+            0,
+            json!({
+                "id": id.to_string(),
+                "target_id": channel_id,
+                "user_id": format!("user-{}", user),
+                "action_type": 11,
+                "changes": [{ "key": "name", "old_value": previous, "new_value": name }],
+                "username": user,
+            }),
+        );
+    }
+
+    /// Send a dispatch event to every identified session.
+    pub fn dispatch(&self, event: &str, d: Value) {
+        let _ = self.state.dispatches.send(self.state.dispatch_json(event, d));
+    }
+
+    /// Wait until at least `sessions` clients have identified in total.
+    #[cfg(test)]
+    pub async fn identified(&self, sessions: usize) {
+        let mut identified = self.state.identified.subscribe();
+        let _ = identified.wait_for(|&count| count >= sessions).await;
+    }
+
+    /// Wait until at least `sessions` clients have resumed in total.
+    #[cfg(test)]
+    pub async fn resumed(&self, sessions: usize) {
+        let mut resumed = self.state.resumed.subscribe();
+        let _ = resumed.wait_for(|&count| count >= sessions).await;
+    }
+
+    /// Wait until at least `sessions` clients have closed normally in total.
+    #[cfg(test)]
+    pub async fn closed(&self, sessions: usize) {
+        let mut closed = self.state.closed.subscribe();
+        let _ = closed.wait_for(|&count| count >= sessions).await;
+    }
+
+    /// Sessions identified or resumed so far.
+    pub fn sessions(&self) -> usize {
+        *self.state.identified.borrow() + *self.state.resumed.borrow()
+    }
+
+    /// Wait until more than `sessions` clients have identified or resumed in total.
+    pub async fn sessions_above(&self, sessions: usize) {
+        let mut identified = self.state.identified.subscribe();
+        let mut resumed = self.state.resumed.subscribe();
+        while *identified.borrow_and_update() + *resumed.borrow_and_update() <= sessions {
+            tokio::select! {
+                _ = identified.changed() => {}
+                _ = resumed.changed() => {}
+            }
+        }
+    }
+
+    /// Ask every session to reconnect (op 7).
+    pub fn request_reconnect(&self) {
+        let _ = self.state.dispatches.send(json!({ "op": 7, "d": null }).to_string());
+    }
+
+    /// Close every session with `code`, e.g. 4000 (resumable) or 4004 (authentication failed).
+    pub fn close_sessions(&self, code: u16) {
+        let _ = self.state.closes.send(code);
+    }
+
+    /// Faults injected so far.
+    pub fn faults(&self) -> FaultCounts {
+        *self.state.faults.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Answer REST requests until the listener fails.
+async fn serve_rest(listener: TcpListener, state: Arc<MockState>) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                let state = Arc::clone(&state);
+                tokio::spawn(async move {
+                    if let Err(e) = handle_rest(stream, &state).await {
+                        debug!("[MOCK] REST connection failed: {}", e);
+                    }
+                });
+            }
+            Err(e) => {
+                error!("[MOCK] REST accept failed: {}", e);
+                return;
+            }
+        }
+    }
+}
+
+/// Serve a single HTTP request.
+async fn handle_rest(stream: TcpStream, state: &MockState) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+
+    let mut authorized = false;
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).await? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            match name.trim().to_ascii_lowercase().as_str() {
+                "authorization" => authorized = !value.trim().is_empty(),
+                "content-length" => content_length = value.trim().parse().unwrap_or(0),
+                _ => {}
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).await?;
+
+    let path = request_line.split_whitespace().nth(1).unwrap_or("");
+    if state.inject(state.chaos.slow, |f| f.slow += 1) {
+        tokio::time::sleep(state.chaos.slow_delay).await;
+    }
+    let (status, response) = if !authorized {
+        ("401 Unauthorized", json!({ "message": "401: Unauthorized", "code": 0 }))
+    } else if state.inject(state.chaos.rate_limit, |f| f.rate_limited += 1) {
+        (
+            "429 Too Many Requests",
+            json!({ "message": "You are being rate limited.", "retry_after": 1.0, "global": false }),
+        )
+    } else {
+        rest_response(path, state)
+    };
+    debug!("[MOCK] {} -> {}", request_line.trim(), status);
+
+    let response = response.to_string();
+    let mut stream = reader.into_inner();
+    stream
+        .write_all(
+            format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                response.len(),
+                response
+            )
+            .as_bytes(),
+        )
+        .await?;
+    stream.shutdown().await
+}
+
+/// Route a REST path to its stub response.
+fn rest_response(path: &str, state: &MockState) -> (&'static str, Value) {
+    let path = path.split('?').next().unwrap_or(path);
+    let segments: Vec<&str> = path.trim_start_matches("/api/v9/").split('/').collect();
+    match segments.as_slice() {
+        ["channels", id] => {
+            let channels = state.channels.lock().unwrap_or_else(|e| e.into_inner());
+            match channels.get(*id) {
+                Some(name) => ("200 OK", json!({ "id": id, "name": name, "type": 0 })),
+                None => ("404 Not Found", json!({ "message": "Unknown Channel", "code": 10003 })),
+            }
+        }
+        // Every mock channel belongs to every guild
+        ["guilds", guild_id, "channels"] => {
+            let channels = state.channels.lock().unwrap_or_else(|e| e.into_inner());
+            let list: Vec<Value> = channels
+                .iter()
+                .map(|(id, name)| json!({ "id": id, "name": name, "type": 0, "guild_id": guild_id }))
+                .collect();
+            ("200 OK", Value::Array(list))
+        }
+        ["guilds", _, "audit-logs"] => {
+            let entries = state.audit_log.lock().unwrap_or_else(|e| e.into_inner()).clone();
+            let users: Vec<Value> = entries
+                .iter()
+                .map(|e| json!({ "id": e["user_id"], "username": e["username"], "global_name": null }))
+                .collect();
+            ("200 OK", json!({ "audit_log_entries": entries, "users": users }))
+        }
+        ["guilds", id] => (
+            "200 OK",
+            json!({
+                "id": id,
+                "name": format!("Mock Guild {}", id),
+                "approximate_member_count": MOCK_MEMBER_COUNT,
+            }),
+        ),
+        _ => ("404 Not Found", json!({ "message": "404: Not Found", "code": 0 })),
+    }
+}
+
+/// Accept Gateway sessions until the listener fails.
+async fn serve_gateway(listener: TcpListener, state: Arc<MockState>) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                let state = Arc::clone(&state);
+                tokio::spawn(async move {
+                    if let Err(e) = handle_gateway(stream, &state).await {
+                        debug!("[MOCK] Gateway session ended: {}", e);
+                    }
+                });
+            }
+            Err(e) => {
+                error!("[MOCK] Gateway accept failed: {}", e);
+                return;
+            }
+        }
+    }
+}
+
+/// How one Gateway session frames what it sends, as the client asked when connecting.
+struct MockTransport {
+    codec: Codec,
+    etf: bool,
+    /// The session's zlib stream, with `compress=zlib-stream`.
+    compress: Option<Compress>,
+}
+
+impl MockTransport {
+    fn new(query: &str) -> Self {
+        let param = |key: &str| {
+            query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix(key).and_then(|rest| rest.strip_prefix('=')))
+        };
+        let etf = param("encoding") == Some("etf");
+        let transport = Transport {
+            encoding: if etf { Encoding::Etf } else { Encoding::Json },
+            // What the client sends is never compressed
+            compression: Compression::None,
+        };
+        Self {
+            codec: transport.codec(),
+            etf,
+            compress: (param("compress") == Some("zlib-stream")).then(|| Compress::new(flate2::Compression::fast(), true)),
+        }
+    }
+
+    /// Frame a JSON payload; malformed ones are sent as they are.
+    fn frame(&mut self, text: impl Into<String>) -> Message {
+        let text = text.into();
+        let payload = match serde_json::from_str::<Value>(&text) {
+            Ok(value) if self.etf => etf::encode(&value),
+            _ if self.etf || self.compress.is_some() => text.into_bytes(),
+            _ => return Message::Text(text),
+        };
+        let Some(compress) = &mut self.compress else {
+            return Message::Binary(payload);
+        };
+        let mut out = Vec::with_capacity(payload.len() + 64);
+        let start = compress.total_in();
+        loop {
+            let done = (compress.total_in() - start) as usize;
+            if out.len() == out.capacity() {
+                out.reserve(1024);
+            }
+            compress
+                .compress_vec(&payload[done..], &mut out, FlushCompress::Sync)
+                .expect("Failed to compress mock payload");
+            if (compress.total_in() - start) as usize == payload.len() && out.len() < out.capacity() {
+                return Message::Binary(out);
+            }
+        }
+    }
+
+    fn parse(&mut self, message: &Message) -> Result<GatewayMessage, String> {
+        self.codec.decode(message)?.ok_or_else(|| "incomplete message".to_string())
+    }
+}
+
+/// Run one Gateway session: Hello, wait for Identify or Resume, READY or RESUMED, then dispatches.
+///
+/// Resuming does not replay events dispatched while the client was away.
+// The handshake callback returns tungstenite's own (large) error response
+#[allow(clippy::result_large_err)]
+async fn handle_gateway(stream: TcpStream, state: &MockState) -> Result<(), String> {
+    // The client picks the encoding and compression in the query string
+    let mut query = String::new();
+    let ws = tokio_tungstenite::accept_hdr_async(stream, |request: &Request, response: Response| {
+        query = request.uri().query().unwrap_or_default().to_string();
+        Ok(response)
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    let mut transport = MockTransport::new(&query);
+    let (mut write, mut read) = ws.split();
+
+    let hello = json!({ "op": 10, "d": { "heartbeat_interval": MOCK_HEARTBEAT_INTERVAL_MS } });
+    write.send(transport.frame(hello.to_string())).await.map_err(|e| e.to_string())?;
+
+    // The session ID the client asked to resume, if it sent Resume
+    let resume = loop {
+        match read.next().await {
+            Some(Ok(msg @ (Message::Text(_) | Message::Binary(_)))) => {
+                let message = transport.parse(&msg)?;
+                match message.op {
+                    2 => break None,
+                    6 => {
+                        let session_id = message.d.as_ref().and_then(|d| d["session_id"].as_str()).unwrap_or_default();
+                        break Some(session_id.to_string());
+                    }
+                    _ => {}
+                }
+            }
+            Some(Ok(_)) => {}
+            Some(Err(e)) => return Err(e.to_string()),
+            None => return Err("closed before Identify".to_string()),
+        }
+    };
+
+    // Subscribe before READY so nothing dispatched after `identified()` is missed
+    let mut dispatches = state.dispatches.subscribe();
+    let mut closes = state.closes.subscribe();
+    let session_id = match resume {
+        Some(session_id) => {
+            if !state.sessions.lock().unwrap_or_else(|e| e.into_inner()).contains(&session_id) {
+                let invalid = json!({ "op": 9, "d": false });
+                write.send(transport.frame(invalid.to_string())).await.map_err(|e| e.to_string())?;
+                return Ok(());
+            }
+            let resumed = state.dispatch_json("RESUMED", json!({}));
+            write.send(transport.frame(resumed)).await.map_err(|e| e.to_string())?;
+            state.resumed.send_modify(|count| *count += 1);
+            info!("[MOCK] Gateway session resumed");
+            session_id
+        }
+        None => {
+            let session_id = format!("mock-session-{}", state.next_session.fetch_add(1, Ordering::SeqCst) + 1);
+            state.sessions.lock().unwrap_or_else(|e| e.into_inner()).insert(session_id.clone());
+            let ready = state.dispatch_json("READY", json!({ "session_id": session_id, "guilds": [] }));
+            write.send(transport.frame(ready)).await.map_err(|e| e.to_string())?;
+            state.identified.send_modify(|count| *count += 1);
+            info!("[MOCK] Gateway session identified");
+            session_id
+        }
+    };
+
+    loop {
+        // In order, so a close asked for after a dispatch does not overtake it
+        tokio::select! {
+            biased;
+            dispatch = dispatches.recv() => match dispatch {
+                Ok(text) => {
+                    if state.inject(state.chaos.malformed, |f| f.malformed += 1) {
+                        let truncated: String = text.chars().take(text.chars().count() / 2).collect();
+                        write.send(transport.frame(truncated)).await.map_err(|e| e.to_string())?;
+                    }
+                    if state.inject(state.chaos.disconnect, |f| f.disconnects += 1) {
+                        return Err("injected disconnect".to_string());
+                    }
+                    if state.inject(state.chaos.invalid_session, |f| f.invalid_sessions += 1) {
+                        state.sessions.lock().unwrap_or_else(|e| e.into_inner()).remove(&session_id);
+                        let invalid = json!({ "op": 9, "d": false });
+                        write.send(transport.frame(invalid.to_string())).await.map_err(|e| e.to_string())?;
+                        return Ok(());
+                    }
+                    write.send(transport.frame(text)).await.map_err(|e| e.to_string())?;
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            },
+            Ok(code) = closes.recv() => {
+                let frame = CloseFrame { code: code.into(), reason: "mock close".into() };
+                write.send(Message::Close(Some(frame))).await.map_err(|e| e.to_string())?;
+                return Ok(());
+            }
+            msg = read.next() => match msg {
+                Some(Ok(msg @ (Message::Text(_) | Message::Binary(_)))) => {
+                    if transport.parse(&msg).is_ok_and(|m| m.op == 1) {
+                        let ack = json!({ "op": 11 });
+                        write.send(transport.frame(ack.to_string())).await.map_err(|e| e.to_string())?;
+                    }
+                }
+                Some(Ok(Message::Close(frame))) => {
+                    if frame.is_some_and(|frame| frame.code == CloseCode::Normal) {
+                        state.closed.send_modify(|count| *count += 1);
+                    }
+                    return Ok(());
+                }
+                None => return Ok(()),
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e.to_string()),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, NotificationSettings};
+    use crate::discovery::Discovery;
+    use crate::events::{self, EventSender, MonitorEvent};
+    use crate::health::Health;
+    use crate::monitor::history::AckSource;
+    use crate::i18n::Alert;
+    use crate::models::MonitorTarget;
+    use crate::monitor::{self, ChannelNames, Monitor};
+    use crate::notifier::Notifier;
+    use crate::pause::Pause;
+    use crate::rest::{RestClient, RestError};
+    use crate::trigger::{self, MessageRule};
+    use std::collections::HashMap;
+    use std::time::Duration;
+    use tokio::sync::RwLock;
+
+    /// An event channel whose alarms ring on `notifier`.
+    fn alarm_events(notifier: &Arc<Notifier>) -> EventSender {
+        let events = events::channel();
+        tokio::spawn(Arc::clone(notifier).listen(events.subscribe()));
+        events
+    }
+
+    #[tokio::test]
+    async fn test_rest_stub_serves_channels() {
+        let mock = MockDiscord::start(0).await.unwrap();
+        mock.set_channel("100", "start-order-❌");
+
+        let rest = RestClient::new(&mock.api_base(), "token");
+        let name = monitor::fetch_channel_name(&rest, "100").await.unwrap();
+        assert_eq!(name, Some("start-order-❌".to_string()));
+        assert!(monitor::fetch_channel_name(&rest, "200").await.is_err());
+        let unauthorized = RestClient::new(&mock.api_base(), "");
+        assert!(monitor::fetch_channel_name(&unauthorized, "100").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rest_client_waits_out_429() {
+        let chaos = Chaos {
+            rate_limit: 1.0,
+            ..Chaos::uniform(0.0, 42)
+        };
+        let mock = MockDiscord::start_with_chaos(0, chaos).await.unwrap();
+        mock.set_channel("100", "start-order-❌");
+        let rest = RestClient::new(&mock.api_base(), "token");
+
+        let err = monitor::fetch_channel_name(&rest, "100").await.unwrap_err();
+        assert_eq!(
+            err,
+            RestError::RateLimited {
+                retry_after: Duration::from_secs(1),
+                global: false
+            }
+        );
+        // The next request on the route holds back for the retry_after
+        let start = std::time::Instant::now();
+        assert!(monitor::fetch_channel_name(&rest, "100").await.is_err());
+        assert!(start.elapsed() >= Duration::from_millis(900));
+        assert_eq!(mock.faults().rate_limited, 2);
+    }
+
+    #[tokio::test]
+    async fn test_audit_log_loop_alarms_with_who_renamed() {
+        let mock = MockDiscord::start(0).await.unwrap();
+        mock.log_rename("100", "order-a", "order-b", "earlier");
+        let config = Arc::new(Config {
+            token: "token".to_string(),
+            channel_id: Some("100".to_string()),
+            api_base: Some(mock.api_base()),
+            ..Default::default()
+        });
+        let notifier = Arc::new(Notifier::new("/nonexistent/path.mp3".to_string()));
+        let names: ChannelNames = Arc::new(RwLock::new(HashMap::from([("100".to_string(), "start-order-❌".to_string())])));
+
+        let rest = Arc::new(RestClient::new(&mock.api_base(), "token"));
+        let audit = tokio::spawn(monitor::audit_log_loop(
+            config,
+            rest,
+            "1".to_string(),
+            Duration::from_millis(20),
+            Arc::clone(&names),
+            alarm_events(&notifier),
+            Arc::new(Health::default()),
+            Arc::new(Pause::default()),
+        ));
+        // Let the first page set the baseline, then rename behind the Gateway's back
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        mock.log_rename("100", "start-order-❌", "start-order-✅", "shopkeep");
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !notifier.is_running() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(names.read().await.get("100").map(String::as_str), Some("start-order-✅"));
+        assert!(notifier.active_alarms()[0].ends_with("\nChanged by: shopkeep"));
+        notifier.stop();
+        audit.abort();
+    }
+
+    #[tokio::test]
+    async fn test_audit_log_loop_skips_renames_polling_has_passed() {
+        let mock = MockDiscord::start(0).await.unwrap();
+        let config = Arc::new(Config {
+            token: "token".to_string(),
+            channel_id: Some("100".to_string()),
+            api_base: Some(mock.api_base()),
+            ..Default::default()
+        });
+        let notifier = Arc::new(Notifier::new("/nonexistent/path.mp3".to_string()));
+        // Polling has already seen the final name
+        let names: ChannelNames = Arc::new(RwLock::new(HashMap::from([("100".to_string(), "start-order-✅".to_string())])));
+
+        let rest = Arc::new(RestClient::new(&mock.api_base(), "token"));
+        let audit = tokio::spawn(monitor::audit_log_loop(
+            config,
+            rest,
+            "1".to_string(),
+            Duration::from_millis(20),
+            Arc::clone(&names),
+            alarm_events(&notifier),
+            Arc::new(Health::default()),
+            Arc::new(Pause::default()),
+        ));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        mock.log_rename("100", "start-order-❌", "start-order-⏳", "shopkeep");
+        mock.log_rename("100", "start-order-⏳", "start-order-✅", "shopkeep");
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        assert_eq!(names.read().await.get("100").map(String::as_str), Some("start-order-✅"));
+        assert!(!notifier.is_running());
+        audit.abort();
+    }
+
+    #[tokio::test]
+    async fn test_websocket_loop_names_the_voice_channel_joined() {
+        let mock = MockDiscord::start(0).await.unwrap();
+        mock.set_channel("300", "Lounge");
+        let config = Arc::new(Config {
+            token: "token".to_string(),
+            channel_id: Some("100".to_string()),
+            voice_user_id: Some("42".to_string()),
+            api_base: Some(mock.api_base()),
+            gateway_url: Some(mock.gateway_url()),
+            ..Default::default()
+        });
+        let events = events::channel();
+        let mut received = events.subscribe();
+
+        let ws = tokio::spawn(monitor::websocket_loop(
+            config,
+            Arc::new(RestClient::new(&mock.api_base(), "token")),
+            events,
+            ChannelNames::default(),
+            Arc::new(Health::default()),
+            None,
+            Arc::new(Pause::default()),
+            watch::channel(false).1,
+        ));
+        tokio::time::timeout(Duration::from_secs(5), mock.identified(1)).await.unwrap();
+        mock.dispatch("VOICE_STATE_UPDATE", json!({ "user_id": "42", "channel_id": "300", "guild_id": "1" }));
+
+        let alert = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Ok(MonitorEvent::Alert(alert)) = received.recv().await {
+                    return alert;
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            alert,
+            Alert::UserJoinedVoice {
+                user_id: "42".to_string(),
+                channel: "Lounge".to_string(),
+            }
+        );
+        ws.abort();
+    }
+
+    #[tokio::test]
+    async fn test_websocket_loop_alarms_on_channel_update() {
+        let mock = MockDiscord::start(0).await.unwrap();
+        let config = Arc::new(Config {
+            token: "token".to_string(),
+            channel_id: Some("100".to_string()),
+            api_base: Some(mock.api_base()),
+            gateway_url: Some(mock.gateway_url()),
+            ..Default::default()
+        });
+        let notifier = Arc::new(Notifier::new("/nonexistent/path.mp3".to_string()));
+        let names: ChannelNames = Arc::new(RwLock::new(HashMap::from([("100".to_string(), "start-order-❌".to_string())])));
+        let health = Arc::new(Health::default());
+
+        let ws = tokio::spawn(monitor::websocket_loop(
+            config,
+            Arc::new(RestClient::new(&mock.api_base(), "token")),
+            alarm_events(&notifier),
+            Arc::clone(&names),
+            Arc::clone(&health),
+            None,
+            Arc::new(Pause::default()),
+            watch::channel(false).1,
+        ));
+        tokio::time::timeout(Duration::from_secs(5), mock.identified(1)).await.unwrap();
+
+        // Another channel's update is ignored
+        mock.rename_channel("200", "unrelated");
+        mock.rename_channel("100", "start-order-✅");
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while names.read().await.get("100").map(String::as_str) != Some("start-order-✅") || !notifier.is_running() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        // READY arrived before the update, so the session counts as connected
+        assert!(health.ws_connected());
+
+        notifier.stop();
+        ws.abort();
+    }
+
+    #[tokio::test]
+    async fn test_websocket_loop_speaks_etf_over_zlib_stream() {
+        let mock = MockDiscord::start(0).await.unwrap();
+        let config = Arc::new(Config {
+            token: "token".to_string(),
+            channel_id: Some("100".to_string()),
+            gateway_url: Some(mock.gateway_url()),
+            gateway_transport: Transport {
+                encoding: Encoding::Etf,
+                compression: Compression::ZlibStream,
+            },
+            ..Default::default()
+        });
+        let names: ChannelNames = Arc::new(RwLock::new(HashMap::from([("100".to_string(), "start-order-❌".to_string())])));
+        let events = events::channel();
+        let mut received = events.subscribe();
+
+        let ws = tokio::spawn(monitor::websocket_loop(
+            config,
+            Arc::new(RestClient::new(&mock.api_base(), "token")),
+            events.clone(),
+            Arc::clone(&names),
+            Arc::new(Health::default()),
+            None,
+            Arc::new(Pause::default()),
+            watch::channel(false).1,
+        ));
+        // The mock only counts an Identify it could decode
+        tokio::time::timeout(Duration::from_secs(5), mock.identified(1)).await.unwrap();
+
+        // Several messages continue the same compressed stream
+        mock.rename_channel("100", "start-order-⏳");
+        mock.rename_channel("100", "start-order-✅");
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while names.read().await.get("100").map(String::as_str) != Some("start-order-✅") {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(received.recv().await.unwrap(), MonitorEvent::GatewayConnected { resumed: false });
+        ws.abort();
+    }
+
+    #[tokio::test]
+    async fn test_websocket_loop_closes_on_shutdown() {
+        let mock = MockDiscord::start(0).await.unwrap();
+        let config = Arc::new(Config {
+            token: "token".to_string(),
+            channel_id: Some("100".to_string()),
+            gateway_url: Some(mock.gateway_url()),
+            ..Default::default()
+        });
+        let health = Arc::new(Health::default());
+        let (shutdown_tx, shutdown) = watch::channel(false);
+
+        let ws = tokio::spawn(monitor::websocket_loop(
+            config,
+            Arc::new(RestClient::new(&mock.api_base(), "token")),
+            events::channel(),
+            Arc::new(RwLock::new(HashMap::new())),
+            Arc::clone(&health),
+            None,
+            Arc::new(Pause::default()),
+            shutdown,
+        ));
+        tokio::time::timeout(Duration::from_secs(5), mock.identified(1)).await.unwrap();
+
+        shutdown_tx.send(true).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), mock.closed(1)).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), ws).await.unwrap().unwrap();
+        assert!(!health.ws_connected());
+    }
+
+    #[tokio::test]
+    async fn test_websocket_loop_alarms_on_filtered_message() {
+        let mock = MockDiscord::start(0).await.unwrap();
+        let target = MonitorTarget {
+            channel_id: "100".to_string(),
+            message_rule: MessageRule {
+                keyword: Some(trigger::compile("MESSAGE_TRIGGER", "(?i)orders open").unwrap()),
+                authors: vec!["shopbot".to_string()],
+            },
+            ..Default::default()
+        };
+        let config = Arc::new(Config {
+            token: "token".to_string(),
+            channel_id: Some("100".to_string()),
+            targets: vec![target],
+            gateway_url: Some(mock.gateway_url()),
+            ..Default::default()
+        });
+        let notifier = Arc::new(Notifier::new("/nonexistent/path.mp3".to_string()));
+        let names: ChannelNames = Arc::new(RwLock::new(HashMap::from([("100".to_string(), "drops".to_string())])));
+
+        let ws = tokio::spawn(monitor::websocket_loop(
+            config,
+            Arc::new(RestClient::new(&mock.api_base(), "token")),
+            alarm_events(&notifier),
+            names,
+            Arc::new(Health::default()),
+            None,
+            Arc::new(Pause::default()),
+            watch::channel(false).1,
+        ));
+        tokio::time::timeout(Duration::from_secs(5), mock.identified(1)).await.unwrap();
+
+        // The keyword from someone else, and another message from the bot, are ignored
+        mock.post_message("100", "impostor", "orders open!");
+        mock.post_message("100", "shopbot", "restocking soon");
+        mock.post_message("100", "shopbot", "Orders open!");
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !notifier.is_running() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        let alarms = notifier.active_alarms();
+        assert_eq!(alarms.len(), 1);
+        assert!(alarms[0].starts_with("NEW MESSAGE: shopbot in drops: Orders open!"));
+        notifier.stop();
+        ws.abort();
+    }
+
+    #[tokio::test]
+    async fn test_websocket_loop_resumes_after_reconnect_request() {
+        let mock = MockDiscord::start(0).await.unwrap();
+        let config = Arc::new(Config {
+            token: "token".to_string(),
+            channel_id: Some("100".to_string()),
+            api_base: Some(mock.api_base()),
+            gateway_url: Some(mock.gateway_url()),
+            ..Default::default()
+        });
+        let notifier = Arc::new(Notifier::new("/nonexistent/path.mp3".to_string()));
+        let names: ChannelNames = Arc::new(RwLock::new(HashMap::from([("100".to_string(), "start-order-❌".to_string())])));
+        let health = Arc::new(Health::default());
+
+        let ws = tokio::spawn(monitor::websocket_loop(
+            config,
+            Arc::new(RestClient::new(&mock.api_base(), "token")),
+            alarm_events(&notifier),
+            Arc::clone(&names),
+            Arc::clone(&health),
+            None,
+            Arc::new(Pause::default()),
+            watch::channel(false).1,
+        ));
+        tokio::time::timeout(Duration::from_secs(5), mock.identified(1)).await.unwrap();
+
+        // Op 7 is answered with an immediate RESUME, not a fresh Identify
+        mock.request_reconnect();
+        tokio::time::timeout(Duration::from_secs(2), mock.resumed(1)).await.unwrap();
+        assert_eq!(*mock.state.identified.borrow(), 1);
+
+        mock.rename_channel("100", "start-order-✅");
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !notifier.is_running() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(health.stats().gateway_sessions, 2);
+
+        notifier.stop();
+        ws.abort();
+    }
+
+    #[tokio::test]
+    async fn test_poll_loop_dispatches_per_channel() {
+        let mock = MockDiscord::start(0).await.unwrap();
+        mock.set_channel("100", "orders-❌");
+        mock.set_channel("200", "drops-❌");
+        let targets = vec![
+            MonitorTarget::new("100"),
+            MonitorTarget {
+                channel_id: "200".to_string(),
+                title: Some("DROP OPEN".to_string()),
+                ..Default::default()
+            },
+        ];
+        let notifier = Arc::new(Notifier::new("/nonexistent/path.mp3".to_string()).with_targets(&targets));
+        let config = Arc::new(Config {
+            token: "token".to_string(),
+            channel_id: Some("100".to_string()),
+            targets,
+            poll_interval: Duration::from_millis(20),
+            api_base: Some(mock.api_base()),
+            ..Default::default()
+        });
+        let names: ChannelNames = Arc::new(RwLock::new(HashMap::from([
+            ("100".to_string(), "orders-❌".to_string()),
+            ("200".to_string(), "drops-❌".to_string()),
+        ])));
+
+        let rest = Arc::new(RestClient::new(&mock.api_base(), "token"));
+        let poll = tokio::spawn(monitor::poll_loop(
+            config,
+            rest,
+            alarm_events(&notifier),
+            Arc::clone(&names),
+            Arc::new(Health::default()),
+            None,
+            Arc::new(Pause::default()),
+        ));
+        mock.rename_channel("200", "drops-✅");
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !notifier.is_running() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        let names = names.read().await.clone();
+        assert_eq!(names["100"], "orders-❌");
+        assert_eq!(names["200"], "drops-✅");
+        assert!(notifier.active_alarms()[0].starts_with("DROP OPEN: "));
+        notifier.stop();
+        poll.abort();
+    }
+
+    #[tokio::test]
+    async fn test_paused_websocket_loop_ignores_dispatches() {
+        let mock = MockDiscord::start(0).await.unwrap();
+        let config = Arc::new(Config {
+            token: "token".to_string(),
+            channel_id: Some("100".to_string()),
+            gateway_url: Some(mock.gateway_url()),
+            ..Default::default()
+        });
+        let notifier = Arc::new(Notifier::new("/nonexistent/path.mp3".to_string()));
+        let names: ChannelNames = Arc::new(RwLock::new(HashMap::from([("100".to_string(), "order-❌".to_string())])));
+        let health = Arc::new(Health::default());
+        let pause = Arc::new(Pause::default());
+        pause.pause(None);
+
+        let ws = tokio::spawn(monitor::websocket_loop(
+            config,
+            Arc::new(RestClient::new(&mock.api_base(), "token")),
+            alarm_events(&notifier),
+            Arc::clone(&names),
+            Arc::clone(&health),
+            None,
+            Arc::clone(&pause),
+            watch::channel(false).1,
+        ));
+        tokio::time::timeout(Duration::from_secs(5), mock.identified(1)).await.unwrap();
+
+        mock.rename_channel("100", "order-✅");
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(names.read().await.get("100").map(String::as_str), Some("order-❌"));
+        // The session stays up while paused
+        assert!(health.ws_connected());
+
+        assert!(pause.resume());
+        mock.rename_channel("100", "order-✅✅");
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !notifier.is_running() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        notifier.stop();
+        ws.abort();
+    }
+
+    #[tokio::test]
+    async fn test_monitor_tracks_renames_under_chaos() {
+        let mock = MockDiscord::start_with_chaos(0, Chaos::uniform(0.3, 42)).await.unwrap();
+        mock.set_channel("100", "order-0");
+        let config = Arc::new(Config {
+            token: "token".to_string(),
+            channel_id: Some("100".to_string()),
+            poll_interval: Duration::from_millis(100),
+            api_base: Some(mock.api_base()),
+            gateway_url: Some(mock.gateway_url()),
+            ..Default::default()
+        });
+        let notifier = Arc::new(Notifier::new("/nonexistent/path.mp3".to_string()));
+        let names: ChannelNames = Arc::new(RwLock::new(HashMap::from([("100".to_string(), "order-0".to_string())])));
+        let rest = Arc::new(RestClient::new(&mock.api_base(), "token"));
+        let health = Arc::new(Health::default());
+        let events = alarm_events(&notifier);
+
+        let ws = tokio::spawn(monitor::websocket_loop(
+            Arc::clone(&config),
+            Arc::clone(&rest),
+            events.clone(),
+            Arc::clone(&names),
+            Arc::clone(&health),
+            None,
+            Arc::new(Pause::default()),
+            watch::channel(false).1,
+        ));
+        let poll = tokio::spawn(monitor::poll_loop(
+            config,
+            rest,
+            events,
+            Arc::clone(&names),
+            Arc::clone(&health),
+            None,
+            Arc::new(Pause::default()),
+        ));
+        tokio::time::timeout(Duration::from_secs(5), mock.identified(1)).await.unwrap();
+        for i in 1..=20 {
+            mock.rename_channel("100", &format!("order-{}", i));
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        // Whatever the gateway dropped, polling must converge on the final name
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while names.read().await.get("100").map(String::as_str) != Some("order-20") {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .expect("monitor went blind under injected faults");
+        assert_ne!(mock.faults(), FaultCounts::default());
+
+        notifier.stop();
+        poll.abort();
+        ws.abort();
+    }
+
+    #[tokio::test]
+    async fn test_monitor_discovers_recreated_channel() {
+        let mock = MockDiscord::start(0).await.unwrap();
+        mock.set_channel("100", "start-order-❌");
+        mock.set_channel("200", "general");
+        let discovery = Discovery::new(trigger::compile("DISCOVER_PATTERN", "order").unwrap(), MonitorTarget::default());
+        let config = Config {
+            token: "token".to_string(),
+            guild_id: Some("1".to_string()),
+            discovery: Some(discovery.clone()),
+            poll_interval: Duration::from_secs(60),
+            api_base: Some(mock.api_base()),
+            gateway_url: Some(mock.gateway_url()),
+            ..Default::default()
+        };
+        let monitor = Monitor::builder(config).alarms(false).build();
+        let mut received = monitor.subscribe();
+        let shutdown = monitor.handle();
+        let run = tokio::spawn(monitor.run());
+        tokio::time::timeout(Duration::from_secs(5), mock.identified(1)).await.unwrap();
+
+        // Found over REST before the Gateway connected
+        let ids = || discovery.targets().into_iter().map(|t| t.channel_id).collect::<Vec<_>>();
+        assert_eq!(ids(), ["100"]);
+
+        mock.delete_channel("1", "100");
+        mock.create_channel("1", "300", "start-order-✅");
+        mock.create_channel("1", "400", "random");
+        let renamed = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Ok(MonitorEvent::NameChanged { channel_id, old_name, new_name, .. }) = received.recv().await {
+                    return (channel_id, old_name, new_name);
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(renamed, ("300".to_string(), None, "start-order-✅".to_string()));
+        assert_eq!(ids(), ["300"]);
+
+        shutdown.shutdown();
+        tokio::time::timeout(Duration::from_secs(10), run).await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_monitor_alarms_queue_by_priority() {
+        let mock = MockDiscord::start(0).await.unwrap();
+        mock.set_channel("100", "order-❌");
+        let config = Config {
+            token: "token".to_string(),
+            channel_id: Some("100".to_string()),
+            targets: vec![MonitorTarget::new("100")],
+            guild_id: Some("1".to_string()),
+            poll_interval: Duration::from_secs(60),
+            api_base: Some(mock.api_base()),
+            gateway_url: Some(mock.gateway_url()),
+            ..Default::default()
+        };
+        let notifier = Arc::new(
+            Notifier::from_settings(&NotificationSettings {
+                sound_path: "/nonexistent/path.mp3".to_string(),
+                alarm_priorities: vec![("stage".to_string(), 10)],
+                ..Default::default()
+            })
+            .with_channel_id("100"),
+        );
+        let monitor = Monitor::builder(config).alarms(false).build();
+        tokio::spawn(Arc::clone(&notifier).listen(monitor.subscribe()));
+        let shutdown = monitor.handle();
+        let run = tokio::spawn(monitor.run());
+        tokio::time::timeout(Duration::from_secs(5), mock.identified(1)).await.unwrap();
+
+        let alarms = |count: usize| {
+            let notifier = Arc::clone(&notifier);
+            async move {
+                while notifier.active_alarms().len() < count {
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                }
+                notifier.active_alarms()
+            }
+        };
+        mock.rename_channel("100", "order-✅");
+        tokio::time::timeout(Duration::from_secs(5), alarms(1)).await.unwrap();
+        // A stage going live while the channel alarm rings jumps the queue
+        mock.dispatch("STAGE_INSTANCE_CREATE", json!({ "guild_id": "1", "channel_id": "5", "topic": "drop" }));
+        let active = tokio::time::timeout(Duration::from_secs(5), alarms(2)).await.unwrap();
+        assert_eq!(active[0], "STAGE LIVE: Stage is live: drop");
+        assert!(active[1].starts_with("CHANNEL OPEN"), "{:?}", active);
+
+        assert!(notifier.acknowledge(AckSource::Cli));
+        assert_eq!(notifier.active_alarms().len(), 1);
+        assert!(notifier.is_running());
+        notifier.stop();
+        shutdown.shutdown();
+        tokio::time::timeout(Duration::from_secs(10), run).await.unwrap().unwrap();
+    }
+}
@@ -1,3 +1,4 @@
+use crate::trigger::{MessageRule, TriggerRule};
 use serde::{Deserialize, Serialize};
 
 /// Discord Gateway message wrapper
@@ -33,11 +34,203 @@ pub struct IdentifyProperties {
     pub device: String,
 }
 
+/// Resume payload (op 6)
+#[derive(Debug, Serialize)]
+pub struct ResumePayload {
+    pub token: String,
+    pub session_id: String,
+    /// Last sequence number received in the session
+    pub seq: Option<u64>,
+}
+
 /// Channel object
 #[derive(Debug, Deserialize)]
 pub struct Channel {
     pub id: String,
     pub name: Option<String>,
+    /// Guild the channel belongs to; missing in some Gateway payloads
+    #[serde(default)]
+    pub guild_id: Option<String>,
+    /// ID of the most recent message, used to notice channel activity
+    #[serde(default)]
+    pub last_message_id: Option<String>,
+}
+
+/// Message object (MESSAGE_CREATE payload)
+#[derive(Debug, Deserialize)]
+pub struct Message {
+    pub id: String,
+    pub channel_id: String,
+    #[serde(default)]
+    pub content: String,
+    #[serde(default)]
+    pub author: MessageAuthor,
+}
+
+/// Author of a message
+#[derive(Debug, Default, Deserialize)]
+pub struct MessageAuthor {
+    pub id: String,
+    #[serde(default)]
+    pub username: String,
+}
+
+impl MessageAuthor {
+    /// Username, or the ID when the payload had none.
+    pub fn display_name(&self) -> &str {
+        if self.username.is_empty() {
+            &self.id
+        } else {
+            &self.username
+        }
+    }
+}
+
+/// Voice state object (VOICE_STATE_UPDATE payload)
+#[derive(Debug, Deserialize)]
+pub struct VoiceState {
+    pub guild_id: Option<String>,
+    pub channel_id: Option<String>,
+    pub user_id: String,
+    #[serde(default)]
+    pub self_stream: bool,
+}
+
+/// Partial user object as sent with presence updates
+#[derive(Debug, Deserialize)]
+pub struct PartialUser {
+    pub id: String,
+}
+
+/// Activity type for "Streaming" in presence updates
+pub const ACTIVITY_TYPE_STREAMING: u8 = 1;
+
+/// Activity object within a presence update
+#[derive(Debug, Deserialize)]
+pub struct Activity {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: u8,
+    pub url: Option<String>,
+}
+
+/// Presence update object (PRESENCE_UPDATE payload)
+#[derive(Debug, Deserialize)]
+pub struct PresenceUpdate {
+    pub user: PartialUser,
+    pub guild_id: Option<String>,
+    #[serde(default)]
+    pub activities: Vec<Activity>,
+}
+
+/// Role object
+#[derive(Debug, Clone, Deserialize)]
+pub struct Role {
+    pub id: String,
+    pub name: String,
+    /// Permission bit set, serialized by Discord as a string
+    pub permissions: String,
+}
+
+/// Role event payload (GUILD_ROLE_CREATE / GUILD_ROLE_UPDATE)
+#[derive(Debug, Deserialize)]
+pub struct GuildRoleEvent {
+    pub guild_id: String,
+    pub role: Role,
+}
+
+/// Guild object as received in READY / GUILD_CREATE, limited to the fields we track
+#[derive(Debug, Deserialize)]
+pub struct GatewayGuild {
+    pub id: String,
+    #[serde(default)]
+    pub roles: Vec<Role>,
+}
+
+/// Ready payload (READY dispatch), limited to the fields we track
+#[derive(Debug, Deserialize)]
+pub struct Ready {
+    #[serde(default)]
+    pub guilds: Vec<GatewayGuild>,
+}
+
+/// Guild object fetched via REST with `with_counts=true`
+#[derive(Debug, Deserialize)]
+pub struct GuildWithCounts {
+    pub name: String,
+    pub approximate_member_count: Option<u64>,
+}
+
+/// Stage instance object (STAGE_INSTANCE_CREATE payload)
+#[derive(Debug, Deserialize)]
+pub struct StageInstance {
+    pub guild_id: String,
+    pub channel_id: String,
+    pub topic: String,
+}
+
+/// Audit log action type for channel updates
+pub const AUDIT_LOG_CHANNEL_UPDATE: u8 = 11;
+
+/// Guild audit log page (GET /guilds/{id}/audit-logs), newest entry first
+#[derive(Debug, Deserialize)]
+pub struct AuditLog {
+    #[serde(default)]
+    pub audit_log_entries: Vec<AuditLogEntry>,
+    /// Users referenced by the entries
+    #[serde(default)]
+    pub users: Vec<AuditLogUser>,
+}
+
+/// One audit log entry
+#[derive(Debug, Deserialize)]
+pub struct AuditLogEntry {
+    pub id: String,
+    pub target_id: Option<String>,
+    pub user_id: Option<String>,
+    #[serde(default)]
+    pub changes: Vec<AuditLogChange>,
+}
+
+/// A changed field within an audit log entry
+#[derive(Debug, Deserialize)]
+pub struct AuditLogChange {
+    pub key: String,
+    pub old_value: Option<serde_json::Value>,
+    pub new_value: Option<serde_json::Value>,
+}
+
+/// User object as included in an audit log
+#[derive(Debug, Deserialize)]
+pub struct AuditLogUser {
+    pub id: String,
+    pub username: String,
+    pub global_name: Option<String>,
+}
+
+/// A monitored channel and its alarm overrides
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MonitorTarget {
+    pub channel_id: String,
+    /// Notification title used instead of the default for this channel's alarms
+    pub title: Option<String>,
+    /// Sound file or directory played instead of `SOUND_PATH`
+    pub sound_path: Option<String>,
+    /// Which renames of this channel raise an alarm
+    pub rule: TriggerRule,
+    /// Which new messages in this channel raise an alarm
+    pub message_rule: MessageRule,
+    /// Names of the remote sinks alerted instead of every configured one
+    pub sinks: Option<Vec<String>>,
+}
+
+impl MonitorTarget {
+    pub fn new(channel_id: &str) -> Self {
+        Self {
+            channel_id: channel_id.to_string(),
+            ..Default::default()
+        }
+    }
 }
 
 #[cfg(test)]
@@ -83,6 +276,147 @@ mod tests {
         assert_eq!(channel.name, Some("general-chat".to_string()));
     }
 
+    #[test]
+    fn test_deserialize_message_create_and_last_message_id() {
+        let json = r#"{
+            "id": "1100",
+            "channel_id": "123456789",
+            "content": "orders open",
+            "author": {"id": "42", "username": "bot"}
+        }"#;
+
+        let message: Message = serde_json::from_str(json).expect("Failed to parse Message");
+        assert_eq!(message.id, "1100");
+        assert_eq!(message.channel_id, "123456789");
+        assert_eq!(message.content, "orders open");
+        assert_eq!(message.author.display_name(), "bot");
+        let anonymous: Message = serde_json::from_str(r#"{"id": "1", "channel_id": "2", "author": {"id": "42"}}"#).unwrap();
+        assert_eq!(anonymous.author.display_name(), "42");
+
+        let channel: Channel = serde_json::from_str(
+            r#"{"id": "123456789", "name": "feed", "last_message_id": "1100"}"#,
+        )
+        .expect("Failed to parse Channel");
+        assert_eq!(channel.last_message_id, Some("1100".to_string()));
+    }
+
+    #[test]
+    fn test_deserialize_stage_instance_create_message() {
+        let json = r#"{
+            "op": 0,
+            "t": "STAGE_INSTANCE_CREATE",
+            "s": 42,
+            "d": {
+                "id": "840647391636226060",
+                "guild_id": "197038439483310086",
+                "channel_id": "733488538393510049",
+                "topic": "Orders open in 5 minutes",
+                "privacy_level": 2
+            }
+        }"#;
+
+        let msg: GatewayMessage = serde_json::from_str(json).expect("Failed to parse STAGE_INSTANCE_CREATE message");
+        assert_eq!(msg.t, Some("STAGE_INSTANCE_CREATE".to_string()));
+        assert_eq!(msg.s, Some(42));
+
+        let stage: StageInstance = serde_json::from_value(msg.d.unwrap()).expect("Failed to parse StageInstance");
+        assert_eq!(stage.guild_id, "197038439483310086");
+        assert_eq!(stage.channel_id, "733488538393510049");
+        assert_eq!(stage.topic, "Orders open in 5 minutes");
+    }
+
+    #[test]
+    fn test_deserialize_voice_state_update() {
+        let json = r#"{
+            "guild_id": "197038439483310086",
+            "channel_id": "733488538393510049",
+            "user_id": "80351110224678912",
+            "session_id": "abc",
+            "self_mute": false,
+            "self_stream": true
+        }"#;
+
+        let state: VoiceState = serde_json::from_str(json).expect("Failed to parse VoiceState");
+        assert_eq!(state.user_id, "80351110224678912");
+        assert_eq!(state.channel_id, Some("733488538393510049".to_string()));
+        assert!(state.self_stream);
+
+        // self_stream is omitted when the user is not streaming
+        let json = r#"{"guild_id": null, "channel_id": null, "user_id": "1"}"#;
+        let state: VoiceState = serde_json::from_str(json).expect("Failed to parse VoiceState");
+        assert!(state.channel_id.is_none());
+        assert!(!state.self_stream);
+    }
+
+    #[test]
+    fn test_deserialize_presence_update_with_streaming_activity() {
+        let json = r#"{
+            "user": {"id": "80351110224678912"},
+            "guild_id": "197038439483310086",
+            "status": "online",
+            "activities": [
+                {"name": "Twitch", "type": 1, "url": "https://twitch.tv/ollie"},
+                {"name": "Custom Status", "type": 4}
+            ]
+        }"#;
+
+        let presence: PresenceUpdate = serde_json::from_str(json).expect("Failed to parse PresenceUpdate");
+        assert_eq!(presence.user.id, "80351110224678912");
+        assert_eq!(presence.activities.len(), 2);
+        assert_eq!(presence.activities[0].kind, ACTIVITY_TYPE_STREAMING);
+        assert_eq!(presence.activities[0].url, Some("https://twitch.tv/ollie".to_string()));
+        assert!(presence.activities[1].url.is_none());
+    }
+
+    #[test]
+    fn test_deserialize_guild_role_update() {
+        let json = r#"{
+            "guild_id": "197038439483310086",
+            "role": {
+                "id": "41771983423143936",
+                "name": "Customer",
+                "color": 3447003,
+                "permissions": "1071698660929"
+            }
+        }"#;
+
+        let event: GuildRoleEvent = serde_json::from_str(json).expect("Failed to parse GuildRoleEvent");
+        assert_eq!(event.guild_id, "197038439483310086");
+        assert_eq!(event.role.name, "Customer");
+        assert_eq!(event.role.permissions, "1071698660929");
+    }
+
+    #[test]
+    fn test_deserialize_ready_with_unavailable_guilds() {
+        let json = r#"{
+            "v": 9,
+            "session_id": "abc",
+            "guilds": [
+                {"id": "1", "unavailable": true},
+                {"id": "2", "roles": [{"id": "3", "name": "Access", "permissions": "0"}]}
+            ]
+        }"#;
+
+        let ready: Ready = serde_json::from_str(json).expect("Failed to parse Ready");
+        assert_eq!(ready.guilds.len(), 2);
+        assert!(ready.guilds[0].roles.is_empty());
+        assert_eq!(ready.guilds[1].roles[0].name, "Access");
+    }
+
+    #[test]
+    fn test_deserialize_guild_with_counts() {
+        let json = r#"{
+            "id": "197038439483310086",
+            "name": "Ollie's Shop",
+            "approximate_member_count": 1523,
+            "approximate_presence_count": 210
+        }"#;
+
+        let guild: GuildWithCounts = serde_json::from_str(json).expect("Failed to parse GuildWithCounts");
+        assert_eq!(guild.name, "Ollie's Shop");
+        assert_eq!(guild.approximate_member_count, Some(1523));
+    }
+
     #[test]
     fn test_serialize_identify_payload() {
         let identify = IdentifyPayload {
@@ -3,192 +3,974 @@
 //! This module provides two concurrent monitoring strategies:
 //! - REST polling: Periodically fetches channel info via Discord API
 //! - WebSocket: Real-time updates via Discord Gateway
+//!
+//! Optionally the guild audit log is read as well, to catch renames both miss
+//! and to tell who made them.
+
+pub mod history;
 
-use crate::models::{Channel, GatewayMessage, HelloPayload, IdentifyPayload, IdentifyProperties};
+use crate::audio;
+use crate::audit_log::AuditLogWatcher;
+use crate::clock;
+use crate::config::{Config, NotificationSettings};
+use crate::events::{self, EventSender, MonitorEvent};
+use crate::health::{self, Health};
+use crate::hooks::Hooks;
+use crate::i18n::{Alert, Language};
+use crate::inactivity::ChannelActivity;
+use crate::member_count::{MemberCountTracker, MEMBER_JUMP_WINDOW};
+use crate::metrics;
+use crate::models::{
+    AuditLog, Channel, GatewayGuild, GatewayMessage, GuildRoleEvent, GuildWithCounts, HelloPayload,
+    IdentifyPayload, IdentifyProperties, Message as DiscordMessage, MonitorTarget, PresenceUpdate, Ready, ResumePayload,
+    Role, StageInstance, VoiceState, ACTIVITY_TYPE_STREAMING, AUDIT_LOG_CHANNEL_UPDATE,
+};
+use crate::name_diff;
 use crate::notifier::Notifier;
+use crate::pause::Pause;
+use crate::rest::{self, Backoff, RestClient, RestError};
 use futures_util::{SinkExt, StreamExt};
-use std::sync::Arc;
+use history::{AckSource, History};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, mpsc, watch, RwLock};
+use tokio_tungstenite::tungstenite::protocol::{frame::coding::CloseCode, CloseFrame};
 use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tracing::{debug, error, info, trace, warn};
+
+pub use crate::health::Stats;
+pub use crate::metrics::STALE_POLL_AGE;
 
 const DISCORD_API_BASE: &str = "https://discord.com/api/v9";
 const DISCORD_GATEWAY_URL: &str = "wss://gateway.discord.gg/?v=9&encoding=json";
-const USER_AGENT: &str = "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
-const POLL_INTERVAL_SECS: f64 = 1.5;
 const RECONNECT_DELAY_SECS: u64 = 5;
+/// Time the Gateway gets to confirm our Close frame on shutdown.
+const WS_CLOSE_TIMEOUT: Duration = Duration::from_secs(2);
+const MEMBER_COUNT_POLL_SECS: u64 = 300;
+/// Time between rescans of the guild's channels for discovery.
+const DISCOVERY_RESCAN_SECS: u64 = 300;
+
+/// The alarm backends of a configuration, each of which can be tried on its
+/// own, e.g. by `ollie-scraper test`.
+pub struct AlarmBackends {
+    notifier: Notifier,
+}
+
+impl AlarmBackends {
+    pub fn new(settings: &NotificationSettings) -> Self {
+        Self {
+            notifier: Notifier::from_settings(settings),
+        }
+    }
+
+    /// Render an alert as (title, body) in the configured language.
+    pub fn render(&self, alert: &Alert) -> (String, String) {
+        self.notifier.render(alert)
+    }
+
+    /// Show a desktop notification.
+    pub async fn show_popup(&self, title: &str, body: &str) -> Result<(), String> {
+        Notifier::send_alert_notification(title, body).await.map(|_| ()).map_err(|e| e.to_string())
+    }
+
+    /// The configured `SOUND_PATH` value.
+    pub fn sound_path(&self) -> &str {
+        self.notifier.sound_path()
+    }
+
+    /// Every sound file the alarm rotates through.
+    pub fn sounds(&self) -> &[String] {
+        self.notifier.sounds()
+    }
+
+    /// Play the first alarm sound once.
+    pub async fn play_sound(&self) -> Result<(), String> {
+        self.notifier.play_sound().await
+    }
+
+    /// Send to the remote sink called `name`, or `None` if it is not configured.
+    pub async fn send_remote(&self, name: &str, title: &str, body: &str) -> Option<Result<(), String>> {
+        Some(self.notifier.sink(name)?.send(title, body).await)
+    }
+}
+
+/// Last known name of each monitored channel, by channel ID.
+pub type ChannelNames = Arc<RwLock<HashMap<String, String>>>;
+
+/// REST base URL, honouring the `api_base` override.
+fn api_base(config: &Config) -> &str {
+    config.api_base.as_deref().unwrap_or(DISCORD_API_BASE)
+}
+
+/// Gateway URL, honouring the `gateway_url` override.
+fn gateway_url(config: &Config) -> &str {
+    config.gateway_url.as_deref().unwrap_or(DISCORD_GATEWAY_URL)
+}
 
 /// Check for channel name changes and notify if changed.
 ///
 /// This helper extracts the common pattern used in both poll_loop and websocket_loop
 /// to avoid code duplication.
+///
+/// `changed_by` names who made the change, when the source knows it. Every
+/// rename is published as [`MonitorEvent::NameChanged`]; only renames allowed
+/// by the channel's trigger rule are marked to alarm.
 async fn check_and_notify_change(
+    target: &MonitorTarget,
     new_name: Option<String>,
-    last_name: &Arc<RwLock<Option<String>>>,
-    notifier: &Arc<Notifier>,
+    changed_by: Option<&str>,
+    names: &ChannelNames,
+    events: &EventSender,
+    health: &Health,
     source: &str,
 ) {
-    let last = last_name.read().await;
-    if *last != new_name {
-        drop(last);
-        let mut last_write = last_name.write().await;
-        *last_write = new_name.clone();
-        drop(last_write);
+    let channel_id = &target.channel_id;
+    let mut names = names.write().await;
+    let previous = names.get(channel_id).cloned();
+    if previous != new_name {
+        match new_name {
+            Some(ref name) => names.insert(channel_id.clone(), name.clone()),
+            None => names.remove(channel_id),
+        };
+        drop(names);
         if let Some(ref name) = new_name {
-            println!("[{}] Channel name changed to: {}", source, name);
-            notifier.start_alarm(name).await;
+            health.record_rename();
+            match previous.as_deref() {
+                Some(old) => info!(
+                    "[{}] Channel name changed to: {} (was {}, {})",
+                    source,
+                    name,
+                    old,
+                    name_diff::highlight_change(old, name)
+                ),
+                None => info!("[{}] Channel name changed to: {}", source, name),
+            }
+            let trigger = target.rule.evaluate(name);
+            if trigger.is_none() {
+                info!("[{}] {} does not match the trigger rule, not alarming", source, name);
+            }
+            events::emit(
+                events,
+                MonitorEvent::NameChanged {
+                    channel_id: channel_id.clone(),
+                    old_name: previous,
+                    new_name: name.clone(),
+                    changed_by: changed_by.map(str::to_string),
+                    source: source.to_string(),
+                    alarm: trigger.is_some(),
+                    trigger: trigger.flatten(),
+                },
+            );
         }
     }
 }
 
+/// Alarm on a new message in a monitored channel that passes its message filters.
+async fn handle_message(
+    target: &MonitorTarget,
+    message: &DiscordMessage,
+    names: &ChannelNames,
+    events: &EventSender,
+    source: &str,
+) {
+    let author = &message.author;
+    let Some(trigger) = target.message_rule.evaluate(&message.content, &author.id, &author.username) else {
+        return;
+    };
+    let channel = names.read().await.get(&target.channel_id).cloned().unwrap_or_else(|| target.channel_id.clone());
+    info!("[{}] Message from {} in {} passed the message filters", source, author.display_name(), channel);
+    events::emit(
+        events,
+        MonitorEvent::MessageMatched {
+            channel_id: target.channel_id.clone(),
+            channel_name: channel,
+            author: author.display_name().to_string(),
+            content: message.content.clone(),
+            trigger,
+        },
+    );
+}
+
+/// Feed a CHANNEL_CREATE or CHANNEL_UPDATE into change detection.
+///
+/// A channel of the guild that is not monitored yet but now matches the
+/// discovery pattern is monitored from here on, starting with this name.
+async fn handle_channel_update(
+    channel: Channel,
+    config: &Config,
+    names: &ChannelNames,
+    events: &EventSender,
+    health: &Health,
+    source: &str,
+) {
+    let target = match config.target(&channel.id) {
+        Some(target) => Some(target),
+        None if is_watched_guild(channel.guild_id.as_deref(), config) => discover(config, &channel),
+        None => None,
+    };
+    if let Some(target) = target {
+        check_and_notify_change(&target, channel.name, None, names, events, health, source).await;
+    }
+}
+
+/// Stop monitoring a deleted channel if discovery found it; fixed channels only warn.
+async fn handle_channel_delete(channel_id: &str, config: &Config, names: &ChannelNames) {
+    if config.discovery.as_ref().is_some_and(|d| d.remove(channel_id)) {
+        let name = names.write().await.remove(channel_id).unwrap_or_else(|| channel_id.to_string());
+        info!("[DISCOVER] Channel {} was deleted, no longer monitoring it", name);
+    } else if config.is_monitored(channel_id) {
+        warn!("[WS] Monitored channel {} was deleted", channel_id);
+    }
+}
+
+/// Start monitoring `channel` if discovery is on and its name matches the pattern.
+fn discover(config: &Config, channel: &Channel) -> Option<MonitorTarget> {
+    let discovery = config.discovery.as_ref()?;
+    let name = channel.name.as_deref().filter(|name| discovery.matches(name))?;
+    if config.is_monitored(&channel.id) {
+        return None;
+    }
+    let target = discovery.add(&channel.id)?;
+    info!("[DISCOVER] Now monitoring {} ({}), which matches {}", name, channel.id, discovery.pattern());
+    Some(target)
+}
+
+/// Alarm when a stage instance goes live in the watched guild.
+async fn handle_stage_instance_create(
+    stage: StageInstance,
+    guild_id: Option<&str>,
+    events: &EventSender,
+) {
+    if guild_id != Some(stage.guild_id.as_str()) {
+        return;
+    }
+    info!(
+        "[WS] Stage went live in channel {}: {}",
+        stage.channel_id, stage.topic
+    );
+    events::emit(events, MonitorEvent::Alert(Alert::StageLive { topic: stage.topic }));
+}
+
+/// Gateway session kept across reconnects so it can be resumed instead of re-identified.
+#[derive(Debug, Default)]
+struct GatewaySession {
+    /// Session ID from READY, if a session is open.
+    session_id: Option<String>,
+    /// `resume_gateway_url` from READY.
+    resume_url: Option<String>,
+    /// Last sequence number received, echoed in heartbeats and RESUME.
+    sequence: Option<u64>,
+}
+
+impl GatewaySession {
+    /// Forget the session so the next connection identifies from scratch.
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Record the session details sent with READY.
+    fn ready(&mut self, d: &serde_json::Value) {
+        self.session_id = d.get("session_id").and_then(|v| v.as_str()).map(str::to_string);
+        self.resume_url = d.get("resume_gateway_url").and_then(|v| v.as_str()).map(str::to_string);
+    }
+
+    /// URL to connect to: Discord's resume URL while a session is open, unless overridden.
+    fn connect_url(&self, config: &Config) -> String {
+        match (&config.gateway_url, &self.session_id, &self.resume_url) {
+            (None, Some(_), Some(url)) => format!("{}/?v=9&encoding=json", url.trim_end_matches('/')),
+            _ => gateway_url(config).to_string(),
+        }
+    }
+
+    /// First message after Hello: RESUME (op 6) while a session is open, otherwise Identify (op 2).
+    fn handshake(&self, token: &str) -> GatewayMessage {
+        let (op, d) = match &self.session_id {
+            Some(session_id) => (
+                6,
+                serde_json::to_value(ResumePayload {
+                    token: token.to_string(),
+                    session_id: session_id.clone(),
+                    seq: self.sequence,
+                }),
+            ),
+            None => (
+                2,
+                serde_json::to_value(IdentifyPayload {
+                    token: token.to_string(),
+                    properties: IdentifyProperties {
+                        os: "linux".to_string(),
+                        browser: "Chrome".to_string(),
+                        device: "Chrome".to_string(),
+                    },
+                }),
+            ),
+        };
+        GatewayMessage {
+            op,
+            s: None,
+            t: None,
+            d: Some(d.expect("Failed to serialize handshake payload")),
+        }
+    }
+}
+
+/// Whether a Gateway close code means the session can no longer be resumed.
+fn close_ends_session(code: u16) -> bool {
+    // 4007: Invalid seq, 4009: Session timed out
+    matches!(code, 4007 | 4009)
+}
+
+/// State derived from Gateway events that must survive reconnects.
+#[derive(Debug, Default)]
+struct GatewayWatchState {
+    /// Watched user is streaming in a voice channel (`self_stream`).
+    voice_streaming: bool,
+    /// Watched user has a Streaming activity in their presence.
+    presence_streaming: bool,
+    /// Voice channel the watched voice user is currently connected to.
+    voice_channel: Option<String>,
+    /// Last known permissions of roles matching the configured patterns, by role ID.
+    role_permissions: HashMap<String, String>,
+}
+
+/// Outcome of observing a role event for a watched role.
+#[derive(Debug, PartialEq)]
+enum RoleChange {
+    /// Role is new to us (created, or renamed to match a pattern).
+    Appeared,
+    /// Known role's permission bits changed.
+    PermissionsChanged { old: String },
+    Unchanged,
+}
+
+impl GatewayWatchState {
+    fn is_streaming(&self) -> bool {
+        self.voice_streaming || self.presence_streaming
+    }
+
+    /// Record the voice stream state, returning true if the user just went live.
+    fn update_voice_stream(&mut self, streaming: bool) -> bool {
+        let was_streaming = self.is_streaming();
+        self.voice_streaming = streaming;
+        !was_streaming && self.is_streaming()
+    }
+
+    /// Record the presence stream state, returning true if the user just went live.
+    fn update_presence_stream(&mut self, streaming: bool) -> bool {
+        let was_streaming = self.is_streaming();
+        self.presence_streaming = streaming;
+        !was_streaming && self.is_streaming()
+    }
+
+    /// Record the watched user's voice channel, returning the channel they just joined.
+    ///
+    /// Moving between channels counts as joining the new channel.
+    fn update_voice_channel(&mut self, channel_id: Option<String>) -> Option<String> {
+        let previous = std::mem::replace(&mut self.voice_channel, channel_id);
+        match self.voice_channel {
+            Some(ref current) if previous.as_ref() != Some(current) => Some(current.clone()),
+            _ => None,
+        }
+    }
+
+    /// Record a watched role's permissions, returning how it changed.
+    fn observe_role(&mut self, role: &Role) -> RoleChange {
+        match self
+            .role_permissions
+            .insert(role.id.clone(), role.permissions.clone())
+        {
+            None => RoleChange::Appeared,
+            Some(old) if old != role.permissions => RoleChange::PermissionsChanged { old },
+            Some(_) => RoleChange::Unchanged,
+        }
+    }
+
+    /// Seed known role permissions from a guild snapshot without alarming.
+    fn seed_roles(&mut self, guild: &GatewayGuild, config: &Config) {
+        if !is_watched_guild(Some(guild.id.as_str()), config) {
+            return;
+        }
+        for role in guild
+            .roles
+            .iter()
+            .filter(|r| matches_role_pattern(&r.name, &config.role_patterns))
+        {
+            self.role_permissions
+                .insert(role.id.clone(), role.permissions.clone());
+        }
+    }
+}
+
+/// Check whether a role name contains any of the patterns (case-insensitive).
+fn matches_role_pattern(name: &str, patterns: &[String]) -> bool {
+    let name = name.to_lowercase();
+    patterns.iter().any(|p| name.contains(&p.to_lowercase()))
+}
+
+/// Alarm when a watched role appears or its permissions change.
+async fn handle_guild_role_event(
+    event: GuildRoleEvent,
+    config: &Config,
+    state: &mut GatewayWatchState,
+    events: &EventSender,
+) {
+    if !is_watched_guild(Some(event.guild_id.as_str()), config)
+        || !matches_role_pattern(&event.role.name, &config.role_patterns)
+    {
+        return;
+    }
+    let role = event.role;
+    let alert = match state.observe_role(&role) {
+        RoleChange::Appeared => Alert::RoleAppeared { role: role.name },
+        RoleChange::PermissionsChanged { old } => Alert::RolePermissionsChanged {
+            role: role.name,
+            old,
+            new: role.permissions,
+        },
+        RoleChange::Unchanged => return,
+    };
+    info!("[WS] {}", alert.body(Language::En));
+    events::emit(events, MonitorEvent::Alert(alert));
+}
+
+/// Check that an event's guild matches the configured guild (if any).
+fn is_watched_guild(event_guild_id: Option<&str>, config: &Config) -> bool {
+    match config.guild_id.as_deref() {
+        Some(guild_id) => event_guild_id == Some(guild_id),
+        None => true,
+    }
+}
+
+/// Alarm when the watched voice user joins a voice channel in the guild.
+async fn handle_voice_join(
+    voice: &VoiceState,
+    config: &Config,
+    rest: &Arc<RestClient>,
+    state: &mut GatewayWatchState,
+    events: &EventSender,
+) {
+    if config.voice_user_id.as_deref() != Some(voice.user_id.as_str())
+        || !is_watched_guild(voice.guild_id.as_deref(), config)
+    {
+        return;
+    }
+    if let Some(channel_id) = state.update_voice_channel(voice.channel_id.clone()) {
+        // Look the name up off the Gateway loop, so heartbeats are not held up
+        let rest = Arc::clone(rest);
+        let events = events.clone();
+        let user_id = voice.user_id.clone();
+        tokio::spawn(async move {
+            let channel_name = match fetch_channel_name(&rest, &channel_id).await {
+                Ok(Some(name)) => name,
+                Ok(None) => channel_id.clone(),
+                Err(e) => {
+                    warn!("[WS] Failed to fetch voice channel name: {}", e);
+                    channel_id.clone()
+                }
+            };
+            info!("[WS] User {} joined voice channel: {}", user_id, channel_name);
+            events::emit(&events, MonitorEvent::Alert(Alert::UserJoinedVoice {
+                user_id,
+                channel: channel_name,
+            }));
+        });
+    }
+}
+
+/// Alarm when the watched user starts streaming in a voice channel.
+async fn handle_voice_stream(
+    voice: VoiceState,
+    config: &Config,
+    state: &mut GatewayWatchState,
+    events: &EventSender,
+) {
+    if config.stream_user_id.as_deref() != Some(voice.user_id.as_str())
+        || !is_watched_guild(voice.guild_id.as_deref(), config)
+    {
+        return;
+    }
+    let streaming = voice.self_stream && voice.channel_id.is_some();
+    if state.update_voice_stream(streaming) {
+        let channel_id = voice.channel_id.unwrap_or_default();
+        info!("[WS] User {} went live in voice channel {}", voice.user_id, channel_id);
+        events::emit(events, MonitorEvent::Alert(Alert::UserStreamingInVoice {
+            user_id: voice.user_id,
+            channel_id,
+        }));
+    }
+}
+
+/// Alarm when the watched user gains a Streaming activity.
+async fn handle_presence_update(
+    presence: PresenceUpdate,
+    config: &Config,
+    state: &mut GatewayWatchState,
+    events: &EventSender,
+) {
+    if config.stream_user_id.as_deref() != Some(presence.user.id.as_str())
+        || !is_watched_guild(presence.guild_id.as_deref(), config)
+    {
+        return;
+    }
+    let activity = presence
+        .activities
+        .iter()
+        .find(|a| a.kind == ACTIVITY_TYPE_STREAMING);
+    if state.update_presence_stream(activity.is_some()) {
+        let description = activity
+            .map(|a| match a.url {
+                Some(ref url) => format!("{} ({})", a.name, url),
+                None => a.name.clone(),
+            })
+            .unwrap_or_default();
+        info!("[WS] User {} started streaming: {}", presence.user.id, description);
+        events::emit(events, MonitorEvent::Alert(Alert::UserStreaming {
+            user_id: presence.user.id,
+            activity: description,
+        }));
+    }
+}
+
+/// Handle a single Gateway dispatch (op 0) event.
+#[allow(clippy::too_many_arguments)]
+async fn handle_dispatch(
+    event: &str,
+    d: serde_json::Value,
+    config: &Config,
+    rest: &Arc<RestClient>,
+    state: &mut GatewayWatchState,
+    events: &EventSender,
+    names: &ChannelNames,
+    health: &Health,
+    activity: Option<&ChannelActivity>,
+) {
+    match event {
+        "MESSAGE_CREATE" => {
+            if let Ok(message) = serde_json::from_value::<DiscordMessage>(d) {
+                if let Some(target) = config.target(&message.channel_id) {
+                    if let Some(activity) = activity {
+                        record_activity(activity, &message.channel_id, &message.id, "WS");
+                    }
+                    events::emit(
+                        events,
+                        MonitorEvent::MessageReceived {
+                            channel_id: message.channel_id.clone(),
+                            author: message.author.display_name().to_string(),
+                            content: message.content.clone(),
+                        },
+                    );
+                    handle_message(&target, &message, names, events, "WS").await;
+                }
+            }
+        }
+        "CHANNEL_CREATE" | "CHANNEL_UPDATE" => {
+            if let Ok(channel) = serde_json::from_value::<Channel>(d) {
+                handle_channel_update(channel, config, names, events, health, "WS").await;
+            }
+        }
+        "CHANNEL_DELETE" => {
+            if let Ok(channel) = serde_json::from_value::<Channel>(d) {
+                handle_channel_delete(&channel.id, config, names).await;
+            }
+        }
+        "STAGE_INSTANCE_CREATE" => {
+            if let Ok(stage) = serde_json::from_value::<StageInstance>(d) {
+                handle_stage_instance_create(stage, config.guild_id.as_deref(), events).await;
+            }
+        }
+        "READY" => {
+            if let Ok(ready) = serde_json::from_value::<Ready>(d) {
+                for guild in &ready.guilds {
+                    state.seed_roles(guild, config);
+                }
+            }
+        }
+        "GUILD_CREATE" => {
+            if let Ok(guild) = serde_json::from_value::<GatewayGuild>(d) {
+                state.seed_roles(&guild, config);
+            }
+        }
+        "GUILD_ROLE_CREATE" | "GUILD_ROLE_UPDATE" => {
+            if let Ok(event) = serde_json::from_value::<GuildRoleEvent>(d) {
+                handle_guild_role_event(event, config, state, events).await;
+            }
+        }
+        "VOICE_STATE_UPDATE" => {
+            if let Ok(voice) = serde_json::from_value::<VoiceState>(d) {
+                handle_voice_join(&voice, config, rest, state, events).await;
+                handle_voice_stream(voice, config, state, events).await;
+            }
+        }
+        "PRESENCE_UPDATE" => {
+            if let Ok(presence) = serde_json::from_value::<PresenceUpdate>(d) {
+                handle_presence_update(presence, config, state, events).await;
+            }
+        }
+        _ => {}
+    }
+}
+
 /// Fetch channel name from Discord REST API.
 ///
 /// Returns `Ok(Some(name))` if the channel exists and has a name,
 /// `Ok(None)` if the channel exists but has no name (e.g., DM channels),
 /// or an error if the request fails.
-pub async fn fetch_channel_name(
-    token: &str,
-    channel_id: &str,
-) -> Result<Option<String>, reqwest::Error> {
-    let client = reqwest::Client::new();
-    let url = format!("{}/channels/{}", DISCORD_API_BASE, channel_id);
+pub(crate) async fn fetch_channel_name(rest: &RestClient, channel_id: &str) -> Result<Option<String>, RestError> {
+    Ok(fetch_channel(rest, channel_id).await?.name)
+}
 
-    let response = client
-        .get(&url)
-        .header("Authorization", token)
-        .header("User-Agent", USER_AGENT)
-        .send()
-        .await?
-        .error_for_status()?;
+/// Fetch a channel object from Discord REST API.
+pub(crate) async fn fetch_channel(rest: &RestClient, channel_id: &str) -> Result<Channel, RestError> {
+    rest.get(&format!("/channels/{}", channel_id)).await
+}
 
-    let channel: Channel = response.json().await?;
-    Ok(channel.name)
+/// Fetch every channel of a guild from Discord REST API.
+pub(crate) async fn fetch_guild_channels(rest: &RestClient, guild_id: &str) -> Result<Vec<Channel>, RestError> {
+    rest.get(&format!("/guilds/{}/channels", guild_id)).await
 }
 
-/// Poll Discord REST API for channel name changes.
+/// List the guild's channels and bring the discovered ones up to date.
 ///
-/// This loop runs indefinitely, checking for channel name changes
-/// at the specified interval. When a change is detected, it triggers
-/// the notifier alarm.
-pub async fn poll_loop(
-    token: String,
-    channel_id: String,
-    poll_interval: f64,
-    notifier: Arc<Notifier>,
-    last_name: Arc<RwLock<Option<String>>>,
+/// With `announce`, newly found channels are reported as renames from nothing,
+/// as a CHANNEL_CREATE would be; otherwise their names are only recorded.
+async fn scan_guild_channels(
+    config: &Config,
+    rest: &RestClient,
+    guild_id: &str,
+    names: &ChannelNames,
+    events: &EventSender,
+    health: &Health,
+    announce: bool,
+) -> Result<(), RestError> {
+    let Some(discovery) = &config.discovery else {
+        return Ok(());
+    };
+    let channels = fetch_guild_channels(rest, guild_id).await?;
+    for target in discovery.targets() {
+        if !channels.iter().any(|c| c.id == target.channel_id) {
+            handle_channel_delete(&target.channel_id, config, names).await;
+        }
+    }
+    for channel in channels {
+        let Some(target) = discover(config, &channel) else {
+            continue;
+        };
+        if announce {
+            check_and_notify_change(&target, channel.name, None, names, events, health, "DISCOVER").await;
+        } else if let Some(name) = channel.name {
+            names.write().await.insert(target.channel_id, name);
+        }
+    }
+    Ok(())
+}
+
+/// Rescan the guild's channels now and then, in case the Gateway missed a change.
+pub(crate) async fn discovery_loop(
+    config: Arc<Config>,
+    rest: Arc<RestClient>,
+    guild_id: String,
+    names: ChannelNames,
+    events: EventSender,
+    health: Arc<Health>,
+    pause: Arc<Pause>,
+) {
+    let interval = Duration::from_secs(DISCOVERY_RESCAN_SECS);
+    loop {
+        clock::sleep(interval).await;
+        if pause.is_paused() {
+            continue;
+        }
+        if let Err(e) = scan_guild_channels(&config, &rest, &guild_id, &names, &events, &health, true).await {
+            error!("[DISCOVER] Failed to list the channels of guild {}: {}", guild_id, e);
+        }
+    }
+}
+
+/// Fetch a guild with approximate member counts from Discord REST API.
+pub(crate) async fn fetch_guild_counts(rest: &RestClient, guild_id: &str) -> Result<GuildWithCounts, RestError> {
+    rest.get(&format!("/guilds/{}?with_counts=true", guild_id)).await
+}
+
+/// Periodically sample the guild member count and alarm on sudden growth.
+pub(crate) async fn member_count_loop(
+    rest: Arc<RestClient>,
+    guild_id: String,
+    threshold: u64,
+    events: EventSender,
+    pause: Arc<Pause>,
 ) {
-    let interval = Duration::from_secs_f64(poll_interval);
+    let mut tracker = MemberCountTracker::new(threshold, MEMBER_JUMP_WINDOW);
+    let interval = Duration::from_secs(MEMBER_COUNT_POLL_SECS);
 
     loop {
-        tokio::time::sleep(interval).await;
+        if pause.is_paused() {
+            clock::sleep(interval).await;
+            continue;
+        }
+        match fetch_guild_counts(&rest, &guild_id).await {
+            Ok(guild) => {
+                if let Some(count) = guild.approximate_member_count {
+                    if let Some(jump) = tracker.record(clock::now(), count) {
+                        info!(
+                            "[MEMBERS] {} gained {} members in the last hour (now {})",
+                            guild.name, jump, count
+                        );
+                        events::emit(&events, MonitorEvent::Alert(Alert::MemberSurge {
+                            guild: guild.name,
+                            gained: jump,
+                            total: count,
+                        }));
+                    }
+                }
+            }
+            Err(e) => {
+                error!("[MEMBERS] Failed to fetch guild counts: {}", e);
+            }
+        }
+
+        clock::sleep(interval).await;
+    }
+}
+
+/// Fetch recent channel updates from the guild audit log.
+pub(crate) async fn fetch_audit_log(rest: &RestClient, guild_id: &str) -> Result<AuditLog, RestError> {
+    rest.get(&format!("/guilds/{}/audit-logs?action_type={}&limit=25", guild_id, AUDIT_LOG_CHANNEL_UPDATE))
+        .await
+}
 
-        match fetch_channel_name(&token, &channel_id).await {
-            Ok(current_name) => {
-                check_and_notify_change(current_name, &last_name, &notifier, "POLL").await;
+/// Read channel renames from the guild audit log, alarming with who made them.
+///
+/// Renames the Gateway or polling already reported are only logged, and an
+/// entry only applies when it starts from the name currently known, so a late
+/// page never puts back a name polling has already moved past.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn audit_log_loop(
+    config: Arc<Config>,
+    rest: Arc<RestClient>,
+    guild_id: String,
+    interval: Duration,
+    names: ChannelNames,
+    events: EventSender,
+    health: Arc<Health>,
+    pause: Arc<Pause>,
+) {
+    let channel_ids = || config.monitor_targets().into_iter().map(|t| t.channel_id).collect::<Vec<_>>();
+    let mut watcher = AuditLogWatcher::new(&channel_ids());
+
+    loop {
+        if pause.is_paused() {
+            clock::sleep(interval).await;
+            continue;
+        }
+        // Discovery may have found channels since the last page
+        watcher.set_channel_ids(&channel_ids());
+        match fetch_audit_log(&rest, &guild_id).await {
+            Ok(log) => {
+                for rename in watcher.process(&log) {
+                    let by = rename.changed_by.as_deref().unwrap_or("unknown user");
+                    info!("[AUDIT] Channel {} renamed to {} by {}", rename.channel_id, rename.name, by);
+                    let Some(target) = config.target(&rename.channel_id) else {
+                        continue;
+                    };
+                    let current = names.read().await.get(&rename.channel_id).cloned();
+                    if current.as_deref() == Some(rename.name.as_str()) {
+                        info!("[AUDIT] Rename of {} to {} was already reported", rename.channel_id, rename.name);
+                        continue;
+                    }
+                    if !rename.follows(current.as_deref()) {
+                        info!(
+                            "[AUDIT] Skipping rename of {} to {}, the channel is already called {}",
+                            rename.channel_id,
+                            rename.name,
+                            current.as_deref().unwrap_or_default()
+                        );
+                        continue;
+                    }
+                    check_and_notify_change(
+                        &target,
+                        Some(rename.name),
+                        rename.changed_by.as_deref(),
+                        &names,
+                        &events,
+                        &health,
+                        "AUDIT",
+                    )
+                    .await;
+                }
+            }
+            Err(e @ RestError::RateLimited { .. }) => {
+                warn!("[AUDIT] Audit log {}", e);
             }
             Err(e) => {
-                eprintln!("[POLL] Failed to fetch channel: {}", e);
+                error!("[AUDIT] Failed to fetch audit log: {}", e);
             }
         }
+
+        clock::sleep(interval).await;
     }
 }
 
-/// Connect to Discord Gateway and listen for CHANNEL_UPDATE events.
+/// Poll Discord REST API for channel name changes.
+///
+/// This loop runs indefinitely, checking every monitored channel for name
+/// changes at the configured interval plus jitter. When a change is detected,
+/// it triggers the notifier alarm. Rate limits hold the next poll back as long
+/// as Discord asks, and network or server errors back off exponentially.
+pub(crate) async fn poll_loop(
+    config: Arc<Config>,
+    rest: Arc<RestClient>,
+    events: EventSender,
+    names: ChannelNames,
+    health: Arc<Health>,
+    activity: Option<Arc<ChannelActivity>>,
+    pause: Arc<Pause>,
+) {
+    let mut backoff = Backoff::new(config.poll_interval);
+    let mut delay = config.poll_interval;
+
+    loop {
+        clock::sleep(rest::with_jitter(delay, config.poll_jitter)).await;
+        delay = config.poll_interval;
+        if pause.is_paused() {
+            continue;
+        }
+
+        // Read every round, as discovery adds and removes channels
+        for target in &config.monitor_targets() {
+            match fetch_channel(&rest, &target.channel_id).await {
+                Ok(channel) => {
+                    backoff.reset();
+                    trace!("[POLL] Channel {} name: {:?}", target.channel_id, channel.name);
+                    health.record_poll();
+                    if let (Some(activity), Some(message_id)) = (&activity, &channel.last_message_id) {
+                        record_activity(activity, &target.channel_id, message_id, "POLL");
+                    }
+                    check_and_notify_change(target, channel.name, None, &names, &events, &health, "POLL").await;
+                }
+                Err(e @ RestError::RateLimited { .. }) => {
+                    // The client holds the next request back until the limit resets
+                    warn!("[POLL] Channel {} {}", target.channel_id, e);
+                    health.record_poll_failure();
+                    events::emit(&events, MonitorEvent::PollError { channel_id: target.channel_id.clone(), error: e });
+                    break;
+                }
+                Err(e) if e.is_transient() => {
+                    delay = backoff.fail();
+                    error!(
+                        "[POLL] Failed to fetch channel {}: {}; backing off {:.1}s",
+                        target.channel_id,
+                        e,
+                        delay.as_secs_f64()
+                    );
+                    health.record_poll_failure();
+                    events::emit(&events, MonitorEvent::PollError { channel_id: target.channel_id.clone(), error: e });
+                    break;
+                }
+                Err(e) => {
+                    error!("[POLL] Failed to fetch channel {}: {}", target.channel_id, e);
+                    health.record_poll_failure();
+                    events::emit(&events, MonitorEvent::PollError { channel_id: target.channel_id.clone(), error: e });
+                }
+            }
+        }
+    }
+}
+
+/// Connect to Discord Gateway and listen for dispatch events.
 ///
 /// This function:
 /// 1. Connects to the Discord WebSocket Gateway
 /// 2. Handles the Hello message and extracts heartbeat interval
-/// 3. Sends Identify payload with browser spoofing
+/// 3. Resumes the previous session, or sends Identify payload with browser spoofing
 /// 4. Spawns a heartbeat task
-/// 5. Listens for CHANNEL_UPDATE events and triggers alarms on changes
-pub async fn websocket_loop(
-    token: String,
-    channel_id: String,
-    notifier: Arc<Notifier>,
-    last_name: Arc<RwLock<Option<String>>>,
+/// 5. Listens for channel, stage, role, voice, and presence events and triggers alarms
+/// 6. Closes the connection with a Close frame and returns once `shutdown` is set
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn websocket_loop(
+    config: Arc<Config>,
+    rest: Arc<RestClient>,
+    events: EventSender,
+    names: ChannelNames,
+    health: Arc<Health>,
+    activity: Option<Arc<ChannelActivity>>,
+    pause: Arc<Pause>,
+    mut shutdown: watch::Receiver<bool>,
 ) {
+    let mut watch_state = GatewayWatchState::default();
+    let mut session = GatewaySession::default();
+
     loop {
-        println!("[WS] Connecting to Discord Gateway...");
+        if *shutdown.borrow() {
+            return;
+        }
+        debug!("[WS] Connecting to Discord Gateway...");
+        // Reconnect (op 7) asks for an immediate reconnect
+        let mut reconnect_now = false;
 
-        match connect_async(DISCORD_GATEWAY_URL).await {
+        match connect_async(config.gateway_transport.apply(&session.connect_url(&config))).await {
             Ok((ws_stream, _)) => {
-                println!("[WS] Connected to Gateway");
+                info!("[WS] Connected to Gateway");
 
                 let (mut write, mut read) = ws_stream.split();
+                // Compressed messages continue one stream, so every connection gets a fresh codec
+                let mut codec = config.gateway_transport.codec();
 
-                // Wait for Hello message (op 10)
-                let heartbeat_interval = match read.next().await {
-                    Some(Ok(Message::Text(text))) => {
-                        match serde_json::from_str::<GatewayMessage>(&text) {
-                            Ok(msg) if msg.op == 10 => {
-                                if let Some(d) = msg.d {
-                                    match serde_json::from_value::<HelloPayload>(d) {
-                                        Ok(hello) => {
-                                            println!(
-                                                "[WS] Received Hello, heartbeat_interval: {}ms",
-                                                hello.heartbeat_interval
-                                            );
-                                            hello.heartbeat_interval
-                                        }
-                                        Err(e) => {
-                                            eprintln!("[WS] Failed to parse Hello payload: {}", e);
-                                            continue;
-                                        }
-                                    }
-                                } else {
-                                    eprintln!("[WS] Hello message missing 'd' field");
+                // Wait for Hello message (op 10), which may span several compressed frames
+                let hello = loop {
+                    match read.next().await {
+                        Some(Ok(msg @ (Message::Text(_) | Message::Binary(_)))) => match codec.decode(&msg) {
+                            Ok(Some(msg)) => break Ok(msg),
+                            Ok(None) => {}
+                            Err(e) => break Err(format!("Failed to parse Gateway message: {}", e)),
+                        },
+                        Some(Ok(_)) => break Err("Expected a data message for Hello".to_string()),
+                        Some(Err(e)) => break Err(format!("WebSocket error: {}", e)),
+                        None => break Err("Connection closed before Hello".to_string()),
+                    }
+                };
+                let heartbeat_interval = match hello {
+                    Ok(msg) if msg.op == 10 => {
+                        if let Some(d) = msg.d {
+                            match serde_json::from_value::<HelloPayload>(d) {
+                                Ok(hello) => {
+                                    debug!(
+                                        "[WS] Received Hello, heartbeat_interval: {}ms",
+                                        hello.heartbeat_interval
+                                    );
+                                    hello.heartbeat_interval
+                                }
+                                Err(e) => {
+                                    error!("[WS] Failed to parse Hello payload: {}", e);
                                     continue;
                                 }
                             }
-                            Ok(msg) => {
-                                eprintln!("[WS] Expected op 10, got op {}", msg.op);
-                                continue;
-                            }
-                            Err(e) => {
-                                eprintln!("[WS] Failed to parse Gateway message: {}", e);
-                                continue;
-                            }
+                        } else {
+                            error!("[WS] Hello message missing 'd' field");
+                            continue;
                         }
                     }
-                    Some(Ok(_)) => {
-                        eprintln!("[WS] Expected text message for Hello");
-                        continue;
-                    }
-                    Some(Err(e)) => {
-                        eprintln!("[WS] WebSocket error: {}", e);
+                    Ok(msg) => {
+                        error!("[WS] Expected op 10, got op {}", msg.op);
                         continue;
                     }
-                    None => {
-                        eprintln!("[WS] Connection closed before Hello");
+                    Err(e) => {
+                        error!("[WS] {}", e);
                         continue;
                     }
                 };
 
-                // Send Identify (op 2)
-                let identify = GatewayMessage {
-                    op: 2,
-                    s: None,
-                    t: None,
-                    d: Some(
-                        serde_json::to_value(IdentifyPayload {
-                            token: token.clone(),
-                            properties: IdentifyProperties {
-                                os: "linux".to_string(),
-                                browser: "Chrome".to_string(),
-                                device: "Chrome".to_string(),
-                            },
-                        })
-                        .expect("Failed to serialize identify properties"),
-                    ),
-                };
-
-                let identify_json = serde_json::to_string(&identify)
-                    .expect("Failed to serialize identify payload");
-                if let Err(e) = write.send(Message::Text(identify_json)).await {
-                    eprintln!("[WS] Failed to send Identify: {}", e);
+                // Resume the previous session if there is one, otherwise Identify
+                let resuming = session.session_id.is_some();
+                let handshake = if resuming { "Resume" } else { "Identify" };
+                if let Err(e) = write.send(codec.encode(&session.handshake(&config.token))).await {
+                    error!("[WS] Failed to send {}: {}", handshake, e);
                     continue;
                 }
-                println!("[WS] Sent Identify payload");
+                debug!("[WS] Sent {} payload (seq {:?})", handshake, session.sequence);
 
                 // Spawn heartbeat task
                 let heartbeat_interval_ms = heartbeat_interval;
@@ -197,7 +979,7 @@ pub async fn websocket_loop(
                 let heartbeat_handle = tokio::spawn(async move {
                     let interval = Duration::from_millis(heartbeat_interval_ms);
                     loop {
-                        tokio::time::sleep(interval).await;
+                        clock::sleep(interval).await;
                         if heartbeat_tx.send(()).await.is_err() {
                             break;
                         }
@@ -205,73 +987,151 @@ pub async fn websocket_loop(
                 });
 
                 // Main event loop
-                let channel_id_clone = channel_id.clone();
-                let notifier_clone = Arc::clone(&notifier);
-                let last_name_clone = Arc::clone(&last_name);
-                let mut last_sequence: Option<u64> = None;
+                let names_clone = Arc::clone(&names);
+                // When the last heartbeat was sent, until its ACK arrives
+                let mut heartbeat_sent: Option<clock::Instant> = None;
+                let mut disconnect = "connection closed".to_string();
 
                 loop {
                     tokio::select! {
+                        // Close normally, so Discord ends the session instead of waiting for it to time out
+                        _ = shutdown_requested(&mut shutdown) => {
+                            heartbeat_handle.abort();
+                            health.set_ws_connected(false);
+                            let close = CloseFrame { code: CloseCode::Normal, reason: "shutting down".into() };
+                            if let Err(e) = write.send(Message::Close(Some(close))).await {
+                                debug!("[WS] Failed to send Close frame: {}", e);
+                                return;
+                            }
+                            // Wait for the server to confirm the close
+                            let confirmed = tokio::time::timeout(WS_CLOSE_TIMEOUT, async {
+                                while let Some(Ok(msg)) = read.next().await {
+                                    if matches!(msg, Message::Close(_)) {
+                                        break;
+                                    }
+                                }
+                            })
+                            .await;
+                            match confirmed {
+                                Ok(()) => info!("[WS] Gateway connection closed"),
+                                Err(_) => warn!("[WS] Gateway did not confirm the close in time"),
+                            }
+                            return;
+                        }
+
                         // Handle heartbeat
                         Some(()) = heartbeat_rx.recv() => {
                             let heartbeat = GatewayMessage {
                                 op: 1,
                                 s: None,
                                 t: None,
-                                d: last_sequence.map(|s| serde_json::Value::Number(s.into())),
+                                d: session.sequence.map(|s| serde_json::Value::Number(s.into())),
                             };
-                            let heartbeat_json = serde_json::to_string(&heartbeat)
-                                .expect("Failed to serialize heartbeat payload");
-                            if let Err(e) = write.send(Message::Text(heartbeat_json)).await {
-                                eprintln!("[WS] Failed to send heartbeat: {}", e);
+                            if let Err(e) = write.send(codec.encode(&heartbeat)).await {
+                                error!("[WS] Failed to send heartbeat: {}", e);
+                                disconnect = format!("failed to send heartbeat: {}", e);
                                 break;
                             }
+                            heartbeat_sent = Some(clock::now());
+                            trace!("[WS] Sent heartbeat (seq {:?})", session.sequence);
                         }
 
                         // Handle incoming messages
                         msg = read.next() => {
                             match msg {
-                                Some(Ok(Message::Text(text))) => {
-                                    if let Ok(gateway_msg) = serde_json::from_str::<GatewayMessage>(&text) {
+                                Some(Ok(msg @ (Message::Text(_) | Message::Binary(_)))) => {
+                                    let payload = match codec.inflate(&msg) {
+                                        Ok(Some(payload)) => payload,
+                                        // The rest of the message is still to come
+                                        Ok(None) => continue,
+                                        Err(e) => {
+                                            error!("[WS] {}", e);
+                                            disconnect = e;
+                                            break;
+                                        }
+                                    };
+                                    if let Ok(gateway_msg) = codec.parse(&payload) {
                                         // Track sequence number
                                         if let Some(seq) = gateway_msg.s {
-                                            last_sequence = Some(seq);
+                                            session.sequence = Some(seq);
                                         }
-                                        // Handle CHANNEL_UPDATE (op 0, t: "CHANNEL_UPDATE")
+                                        // Handle dispatch events (op 0)
                                         if gateway_msg.op == 0 {
-                                            if let Some(ref t) = gateway_msg.t {
-                                                if t == "CHANNEL_UPDATE" {
-                                                    if let Some(d) = gateway_msg.d {
-                                                        if let Ok(channel) = serde_json::from_value::<Channel>(d) {
-                                                            if channel.id == channel_id_clone {
-                                                                check_and_notify_change(
-                                                                    channel.name,
-                                                                    &last_name_clone,
-                                                                    &notifier_clone,
-                                                                    "WS",
-                                                                ).await;
-                                                            }
-                                                        }
-                                                    }
+                                            if let (Some(t), Some(d)) = (gateway_msg.t, gateway_msg.d) {
+                                                trace!("[WS] Dispatch {}", t);
+                                                // The session is only usable once READY arrives
+                                                if t == "READY" {
+                                                    session.ready(&d);
+                                                    health.set_ws_connected(true);
+                                                    events::emit(&events, MonitorEvent::GatewayConnected { resumed: false });
+                                                } else if t == "RESUMED" {
+                                                    info!("[WS] Session resumed");
+                                                    health.set_ws_connected(true);
+                                                    events::emit(&events, MonitorEvent::GatewayConnected { resumed: true });
                                                 }
+                                                // While paused only seed state, so resuming needs no re-identify
+                                                if pause.is_paused() && t != "READY" && t != "GUILD_CREATE" {
+                                                    trace!("[WS] Paused, ignoring {}", t);
+                                                    continue;
+                                                }
+                                                handle_dispatch(
+                                                    &t,
+                                                    d,
+                                                    &config,
+                                                    &rest,
+                                                    &mut watch_state,
+                                                    &events,
+                                                    &names_clone,
+                                                    &health,
+                                                    activity.as_deref(),
+                                                ).await;
                                             }
                                         }
-                                        // Handle heartbeat ACK (op 11) - just acknowledge
+                                        // Handle heartbeat ACK (op 11) - record the round trip
                                         else if gateway_msg.op == 11 {
-                                            // Heartbeat acknowledged, continue
+                                            if let Some(sent) = heartbeat_sent.take() {
+                                                health.record_heartbeat_latency(sent.elapsed());
+                                            }
+                                            debug!("[WS] Heartbeat ACK");
+                                        }
+                                        // Reconnect (op 7) - reconnect and resume right away
+                                        else if gateway_msg.op == 7 {
+                                            warn!("[WS] Gateway requested a reconnect");
+                                            disconnect = "reconnect requested".to_string();
+                                            reconnect_now = true;
+                                            break;
+                                        }
+                                        // Invalid Session (op 9) - `d` says whether the session can be resumed
+                                        else if gateway_msg.op == 9 {
+                                            let resumable = gateway_msg.d.and_then(|d| d.as_bool()).unwrap_or(false);
+                                            if !resumable {
+                                                session.reset();
+                                            }
+                                            warn!("[WS] Gateway invalidated the session (resumable: {})", resumable);
+                                            disconnect = "session invalidated".to_string();
+                                            break;
                                         }
                                     }
                                 }
-                                Some(Ok(Message::Close(_))) => {
-                                    println!("[WS] Connection closed by server");
+                                Some(Ok(Message::Close(frame))) => {
+                                    warn!("[WS] Connection closed by server");
+                                    if let Some(frame) = frame {
+                                        if close_ends_session(frame.code.into()) {
+                                            session.reset();
+                                        }
+                                        disconnect = format!("closed with {}: {}", frame.code, frame.reason);
+                                        health.record_ws_error(disconnect.clone());
+                                    }
                                     break;
                                 }
                                 Some(Err(e)) => {
-                                    eprintln!("[WS] WebSocket error: {}", e);
+                                    error!("[WS] WebSocket error: {}", e);
+                                    disconnect = e.to_string();
+                                    health.record_ws_error(disconnect.clone());
                                     break;
                                 }
                                 None => {
-                                    println!("[WS] Connection closed");
+                                    warn!("[WS] Connection closed");
                                     break;
                                 }
                                 _ => {}
@@ -282,80 +1142,794 @@ pub async fn websocket_loop(
 
                 // Clean up heartbeat task
                 heartbeat_handle.abort();
+                health.set_ws_connected(false);
+                events::emit(&events, MonitorEvent::GatewayDisconnected { reason: disconnect });
             }
             Err(e) => {
-                eprintln!("[WS] Failed to connect: {}", e);
+                error!("[WS] Failed to connect: {}", e);
+                let reason = format!("failed to connect: {}", e);
+                health.record_ws_error(reason.clone());
+                events::emit(&events, MonitorEvent::GatewayDisconnected { reason });
             }
         }
 
         // Wait before reconnecting
-        println!("[WS] Reconnecting in {} seconds...", RECONNECT_DELAY_SECS);
-        tokio::time::sleep(Duration::from_secs(RECONNECT_DELAY_SECS)).await;
+        if reconnect_now {
+            info!("[WS] Reconnecting...");
+            continue;
+        }
+        info!("[WS] Reconnecting in {} seconds...", RECONNECT_DELAY_SECS);
+        tokio::select! {
+            _ = clock::sleep(Duration::from_secs(RECONNECT_DELAY_SECS)) => {}
+            _ = shutdown_requested(&mut shutdown) => return,
+        }
+    }
+}
+
+/// Resolve once `shutdown` is set; never if its sender is gone.
+async fn shutdown_requested(shutdown: &mut watch::Receiver<bool>) {
+    if shutdown.wait_for(|stop| *stop).await.is_err() {
+        std::future::pending::<()>().await;
     }
 }
 
-/// Run the complete dual-mode monitoring system.
+/// Periodically log a one-line health summary so a quiet console means healthy.
 ///
-/// This function:
-/// 1. Fetches the initial channel name
-/// 2. Runs both polling and WebSocket loops concurrently
-/// 3. Handles graceful shutdown on Ctrl+C
-pub async fn run_monitor(token: String, channel_id: String, sound_path: String) {
-    let notifier = Arc::new(Notifier::new(sound_path));
-    let last_name: Arc<RwLock<Option<String>>> = Arc::new(RwLock::new(None));
+/// The summary names the primary channel `channel_id`.
+pub(crate) async fn health_loop(
+    interval: Duration,
+    health: Arc<Health>,
+    channel_id: Option<String>,
+    names: ChannelNames,
+) {
+    loop {
+        clock::sleep(interval).await;
+        let names = names.read().await;
+        // Without a primary channel, every discovered one is shown
+        let channels: Vec<&str> = match &channel_id {
+            Some(id) => names.get(id).map(String::as_str).into_iter().collect(),
+            None => {
+                let mut channels: Vec<&str> = names.values().map(String::as_str).collect();
+                channels.sort_unstable();
+                channels
+            }
+        };
+        info!(
+            "[HEALTH] {}",
+            health::format_summary(health.ws_connected(), health.last_poll_age(), &channels)
+        );
+    }
+}
 
-    // Fetch initial channel name
-    println!("Fetching initial channel state...");
-    match fetch_channel_name(&token, &channel_id).await {
-        Ok(name) => {
-            println!("Initial channel name: {:?}", name);
-            let mut last = last_name.write().await;
-            *last = name;
+/// Record a channel's latest message ID for inactivity tracking.
+fn record_activity(activity: &ChannelActivity, channel_id: &str, message_id: &str, tag: &str) {
+    if activity.record_message(channel_id, message_id, clock::now()) {
+        info!("[{}] Channel {} activity resumed", tag, channel_id);
+    }
+}
+
+/// Alert once whenever a monitored channel stays quiet past the inactivity timeout.
+pub(crate) async fn inactivity_loop(
+    activity: Arc<ChannelActivity>,
+    config: Arc<Config>,
+    names: ChannelNames,
+    events: EventSender,
+    pause: Arc<Pause>,
+) {
+    let tick = (activity.timeout() / 10).clamp(Duration::from_secs(1), Duration::from_secs(30));
+
+    loop {
+        clock::sleep(tick).await;
+        // Nothing is observed while paused, so quiet time is not meaningful
+        if pause.is_paused() {
+            continue;
         }
-        Err(e) => {
-            eprintln!("Failed to fetch initial channel state: {}", e);
+
+        // Discovery may have found or dropped channels since the last tick
+        let channel_ids: Vec<String> = config.monitor_targets().into_iter().map(|t| t.channel_id).collect();
+        activity.set_channels(&channel_ids, clock::now());
+        for (channel_id, idle) in activity.check(clock::now()) {
+            let name = names.read().await.get(&channel_id).cloned().unwrap_or_else(|| "channel".to_string());
+            let idle_minutes = idle.as_secs() / 60;
+            info!("[IDLE] No new messages in {} for {} minutes", name, idle_minutes);
+            events::emit(
+                &events,
+                MonitorEvent::Alert(Alert::ChannelInactive {
+                    channel_id,
+                    name,
+                    idle_minutes,
+                }),
+            );
         }
     }
+}
 
-    // Run both monitoring modes concurrently
-    let poll_token = token.clone();
-    let poll_channel_id = channel_id.clone();
-    let poll_notifier = Arc::clone(&notifier);
-    let poll_last_name = Arc::clone(&last_name);
+/// Why a monitoring session ended.
+enum SessionEnd {
+    Shutdown,
+    /// [`MonitorHandle::reload`] was given this configuration.
+    Reload(Box<Config>),
+}
+
+/// Live monitor state, from [`MonitorHandle::status`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StatusSnapshot {
+    /// Last known name of the primary monitored channel, if there is one.
+    pub channel_name: Option<String>,
+    /// Last known name of every monitored channel, by ID.
+    #[serde(default)]
+    pub channels: BTreeMap<String, String>,
+    pub ws_connected: bool,
+    /// Time between the last Gateway heartbeat and its ACK, in milliseconds.
+    #[serde(default)]
+    pub heartbeat_latency_ms: Option<u64>,
+    /// Seconds since the last successful REST poll.
+    pub last_poll_secs: Option<u64>,
+    pub alarm_active: bool,
+    /// Active alarms, the ringing one first.
+    #[serde(default)]
+    pub alarms: Vec<String>,
+    /// History IDs of the active alarms, in the order of `alarms`.
+    #[serde(default)]
+    pub alarm_ids: Vec<u64>,
+    /// Whether the initial channel fetch has finished.
+    #[serde(default)]
+    pub initial_fetch_done: bool,
+    /// Why the initial channel fetch failed, if it did.
+    #[serde(default)]
+    pub initial_fetch_error: Option<String>,
+    /// Why the Gateway connection last failed or closed.
+    #[serde(default)]
+    pub ws_error: Option<String>,
+    /// Channels whose alarms are muted.
+    #[serde(default)]
+    pub muted: Vec<String>,
+    /// Whether monitoring is paused.
+    #[serde(default)]
+    pub paused: bool,
+    /// Seconds until a timed pause ends.
+    #[serde(default)]
+    pub paused_secs_left: Option<u64>,
+}
 
-    let ws_token = token;
-    let ws_channel_id = channel_id;
-    let ws_notifier = Arc::clone(&notifier);
-    let ws_last_name = Arc::clone(&last_name);
+impl StatusSnapshot {
+    /// The one-line health summary, e.g. `WS ok, last poll 2s ago, channel: open`.
+    ///
+    /// Without a primary channel name, every known channel is listed.
+    pub fn summary(&self) -> String {
+        let channels: Vec<&str> = match &self.channel_name {
+            Some(name) => vec![name],
+            None => self.channels.values().map(String::as_str).collect(),
+        };
+        health::format_summary(self.ws_connected, self.last_poll_secs.map(Duration::from_secs), &channels)
+    }
+}
 
-    println!("Starting dual-mode monitoring (REST polling + WebSocket)...");
-    println!("Press Ctrl+C to stop.");
+/// A configured monitor, ready to run.
+///
+/// ```no_run
+/// # async fn example(config: ollie_scraper::config::Config) {
+/// use ollie_scraper::events::MonitorEvent;
+/// use ollie_scraper::monitor::Monitor;
+///
+/// let monitor = Monitor::builder(config).alarms(false).build();
+/// let mut events = monitor.subscribe();
+/// tokio::spawn(monitor.run());
+/// while let Ok(event) = events.recv().await {
+///     if let MonitorEvent::NameChanged { new_name, .. } = event {
+///         println!("renamed to {}", new_name);
+///     }
+/// }
+/// # }
+/// ```
+pub struct Monitor {
+    config: Config,
+    alarms: bool,
+    history: Option<PathBuf>,
+    pid_file: Option<PathBuf>,
+    handle: MonitorHandle,
+}
 
-    // Use tokio::select! to handle graceful shutdown
-    tokio::select! {
-        _ = poll_loop(poll_token, poll_channel_id, POLL_INTERVAL_SECS, poll_notifier, poll_last_name) => {
-            println!("Poll loop ended unexpectedly");
+/// What a [`MonitorHandle`] needs of the current monitoring session.
+#[derive(Clone)]
+struct Session {
+    config: Arc<Config>,
+    notifier: Arc<Notifier>,
+    reload: mpsc::Sender<Config>,
+}
+
+/// Controls a [`Monitor`] from elsewhere, from [`Monitor::handle`].
+///
+/// The monitor does not listen for signals itself; the CLI calls
+/// [`shutdown`](Self::shutdown) on Ctrl+C or SIGTERM, and serves its control
+/// socket with the other methods.
+#[derive(Clone)]
+pub struct MonitorHandle {
+    events: EventSender,
+    names: ChannelNames,
+    health: Arc<Health>,
+    pause: Arc<Pause>,
+    session: Arc<Mutex<Option<Session>>>,
+    shutdown: Arc<watch::Sender<bool>>,
+}
+
+impl MonitorHandle {
+    /// Ask the monitor to shut down; before [`Monitor::run`], it stops right after starting.
+    pub fn shutdown(&self) {
+        self.shutdown.send_replace(true);
+    }
+
+    fn session(&self) -> Result<Session, String> {
+        let session = self.session.lock().unwrap_or_else(|e| e.into_inner());
+        session.clone().ok_or_else(|| "The monitor is not running".to_string())
+    }
+
+    /// The configuration the monitor is running with.
+    pub fn config(&self) -> Result<Arc<Config>, String> {
+        Ok(self.session()?.config)
+    }
+
+    /// The monitor's live state.
+    pub async fn status(&self) -> Result<StatusSnapshot, String> {
+        let Session { config, notifier, .. } = self.session()?;
+        let names = self.names.read().await;
+        let health = &self.health;
+        Ok(StatusSnapshot {
+            channel_name: config.channel_id.as_ref().and_then(|id| names.get(id)).cloned(),
+            channels: names.iter().map(|(id, name)| (id.clone(), name.clone())).collect(),
+            ws_connected: health.ws_connected(),
+            heartbeat_latency_ms: health.heartbeat_latency().map(|latency| latency.as_millis() as u64),
+            last_poll_secs: health.last_poll_age().map(|age| age.as_secs()),
+            alarm_active: notifier.is_running(),
+            alarms: notifier.active_alarms(),
+            alarm_ids: notifier.active_alarm_ids(),
+            initial_fetch_done: health.initial_fetch().is_some(),
+            initial_fetch_error: health.initial_fetch().and_then(Result::err),
+            ws_error: health.last_ws_error(),
+            muted: notifier.muted(),
+            paused: self.pause.is_paused(),
+            paused_secs_left: self.pause.remaining().flatten().map(|left| left.as_secs()),
+        })
+    }
+
+    /// Counters since the monitor started.
+    pub fn stats(&self) -> Stats {
+        self.health.stats()
+    }
+
+    /// Silence the ringing alarm; returns whether one was ringing.
+    pub fn acknowledge(&self, via: AckSource) -> Result<bool, String> {
+        Ok(self.session()?.notifier.acknowledge(via))
+    }
+
+    /// Stop polling and ignore dispatches, for `duration` or until [`resume`](Self::resume).
+    pub fn pause(&self, duration: Option<Duration>) {
+        self.pause.pause(duration);
+        match duration {
+            Some(duration) => info!("[PAUSE] Monitoring paused for {}s", duration.as_secs()),
+            None => info!("[PAUSE] Monitoring paused until resumed"),
+        }
+    }
+
+    /// End a pause; returns whether monitoring was paused.
+    pub fn resume(&self) -> bool {
+        let resumed = self.pause.resume();
+        if resumed {
+            info!("[PAUSE] Monitoring resumed");
+        }
+        resumed
+    }
+
+    /// Stop alarming for a channel, by ID or name; returns false if it already was muted.
+    pub fn mute(&self, channel: &str) -> Result<bool, String> {
+        let muted = self.session()?.notifier.mute(channel);
+        if muted {
+            info!("[MUTE] Muted {}", channel);
         }
-        _ = websocket_loop(ws_token, ws_channel_id, ws_notifier, ws_last_name) => {
-            println!("WebSocket loop ended unexpectedly");
+        Ok(muted)
+    }
+
+    /// Resume alarming for a muted channel; returns false if it was not muted.
+    pub fn unmute(&self, channel: &str) -> Result<bool, String> {
+        let unmuted = self.session()?.notifier.unmute(channel);
+        if unmuted {
+            info!("[MUTE] Unmuted {}", channel);
         }
-        _ = tokio::signal::ctrl_c() => {
-            println!("\nReceived Ctrl+C, shutting down gracefully...");
+        Ok(unmuted)
+    }
+
+    /// Restart monitoring with `config`.
+    ///
+    /// Known channel names, health, pauses and mutes carry over; a ringing alarm is stopped.
+    pub fn reload(&self, config: Config) -> Result<(), String> {
+        if self.session()?.reload.try_send(config).is_err() {
+            return Err("A reload is already in progress".to_string());
         }
+        info!("[CTL] Reloading configuration");
+        Ok(())
     }
 
-    println!("Shutdown complete.");
+    /// Feed a CHANNEL_UPDATE renaming `channel_id` (by default the first
+    /// monitored channel) to `name` through the change pipeline, as if Discord
+    /// had sent it; returns the channel's ID.
+    pub async fn simulate(&self, channel_id: Option<String>, name: &str) -> Result<String, String> {
+        let config = self.session()?.config;
+        let channel_id = channel_id
+            .or_else(|| config.monitor_targets().first().map(|t| t.channel_id.clone()))
+            .unwrap_or_default();
+        let payload = serde_json::json!({ "id": channel_id, "name": name });
+        let channel = serde_json::from_value::<Channel>(payload)
+            .map_err(|e| format!("Invalid simulated channel: {}", e))?;
+        if !config.is_monitored(&channel.id) {
+            let monitored: Vec<String> = config.monitor_targets().into_iter().map(|t| t.channel_id).collect();
+            return Err(format!("Channel {} is not monitored (monitoring {})", channel.id, monitored.join(", ")));
+        }
+
+        info!("[SIM] Injecting CHANNEL_UPDATE for {}: {}", channel.id, name);
+        handle_channel_update(channel, &config, &self.names, &self.events, &self.health, "SIM").await;
+        Ok(channel_id)
+    }
+}
+
+/// Options for a [`Monitor`], from [`Monitor::builder`].
+pub struct MonitorBuilder {
+    config: Config,
+    alarms: bool,
+    history: Option<PathBuf>,
+    pid_file: Option<PathBuf>,
 }
 
+impl MonitorBuilder {
+    /// Ring alarms and send notifications for events (on by default). When
+    /// off, events only go to subscribers and no hooks run.
+    pub fn alarms(mut self, enabled: bool) -> Self {
+        self.alarms = enabled;
+        self
+    }
+
+    /// Append renames, alarms and acknowledgements to the [`history`] file at `path` (off by default).
+    pub fn history(mut self, path: PathBuf) -> Self {
+        self.history = Some(path);
+        self
+    }
+
+    /// Delete the PID file at `path` on shutdown, if it names this process (off by default).
+    pub fn pid_file(mut self, path: PathBuf) -> Self {
+        self.pid_file = Some(path);
+        self
+    }
+
+    pub fn build(self) -> Monitor {
+        Monitor {
+            config: self.config,
+            alarms: self.alarms,
+            history: self.history,
+            pid_file: self.pid_file,
+            handle: MonitorHandle {
+                events: events::channel(),
+                names: Arc::new(RwLock::new(HashMap::new())),
+                health: Arc::new(Health::default()),
+                pause: Arc::new(Pause::default()),
+                session: Arc::new(Mutex::new(None)),
+                shutdown: Arc::new(watch::channel(false).0),
+            },
+        }
+    }
+}
+
+impl Monitor {
+    /// Start configuring a monitor for `config`.
+    pub fn builder(config: Config) -> MonitorBuilder {
+        MonitorBuilder {
+            config,
+            alarms: true,
+            history: None,
+            pid_file: None,
+        }
+    }
+
+    /// Receive every event published from now on; subscribe before
+    /// [`run`](Self::run) to see them all.
+    pub fn subscribe(&self) -> broadcast::Receiver<MonitorEvent> {
+        self.handle.events.subscribe()
+    }
+
+    /// A handle to control the monitor from elsewhere, e.g. to stop it on a signal or at the end of a test.
+    pub fn handle(&self) -> MonitorHandle {
+        self.handle.clone()
+    }
+
+    /// Run the complete dual-mode monitoring system until [`MonitorHandle::shutdown`].
+    ///
+    /// This function:
+    /// 1. Fetches the initial channel name
+    /// 2. Runs both polling and WebSocket loops concurrently
+    /// 3. Restarts them with the new configuration on [`MonitorHandle::reload`]
+    /// 4. On shutdown, closes the Gateway connection, stops the alarm and
+    ///    removes its own PID file, if given one
+    pub async fn run(self) {
+        let Monitor {
+            config,
+            alarms,
+            history,
+            pid_file,
+            handle,
+        } = self;
+        run_monitor(config, alarms, history, pid_file, handle).await
+    }
+}
+
+async fn run_monitor(
+    config: Config,
+    alarms: bool,
+    history: Option<PathBuf>,
+    pid_file: Option<PathBuf>,
+    handle: MonitorHandle,
+) {
+    let MonitorHandle {
+        events,
+        names,
+        health,
+        pause,
+        session,
+        shutdown,
+    } = handle;
+    let history = history.map(|path| Arc::new(History::new(path)));
+    health.mark_started();
+
+    let shutdown = shutdown.subscribe();
+
+    if let Err(e) = audio::check_player(config.notifications.audio_backend).await {
+        warn!("Alarm sound unavailable: {}", e);
+    }
+
+    // The metrics endpoint outlives reloads; changing METRICS_ADDR needs a restart
+    let metrics_task = match config.metrics_addr {
+        Some(addr) => match tokio::net::TcpListener::bind(addr).await {
+            Ok(listener) => {
+                info!("Serving metrics on http://{}/metrics", addr);
+                Some(tokio::spawn(metrics::serve(listener, Arc::clone(&health), Arc::clone(&names))))
+            }
+            Err(e) => {
+                error!("Failed to serve metrics on {}: {}", addr, e);
+                None
+            }
+        },
+        None => None,
+    };
+
+    let mut config = config;
+    let mut muted: Vec<String> = Vec::new();
+    loop {
+        let mut notifier = Notifier::from_settings(&config.notifications)
+            .with_health(Arc::clone(&health))
+            .with_compound(config.compound_rule.clone())
+            .with_targets(&config.monitor_targets())
+            .with_events(events.clone());
+        if let Some(channel_id) = &config.channel_id {
+            notifier = notifier.with_channel_id(channel_id);
+        }
+        if let Some(history) = &history {
+            notifier = notifier.with_history(Arc::clone(history));
+        }
+        let notifier = Arc::new(notifier);
+        for channel in &muted {
+            notifier.mute(channel);
+        }
+        // Hooks run on the rename alarms the notifier raises
+        let hooks_task = config
+            .hooks
+            .is_enabled()
+            .then(|| tokio::spawn(Arc::new(Hooks::new(config.hooks.clone())).listen(events.subscribe())));
+
+        let session_config = Arc::new(config);
+        let (reload_tx, reload_rx) = mpsc::channel(1);
+        *session.lock().unwrap_or_else(|e| e.into_inner()) = Some(Session {
+            config: Arc::clone(&session_config),
+            notifier: Arc::clone(&notifier),
+            reload: reload_tx,
+        });
+        let end = monitor_session(
+            session_config,
+            events.clone(),
+            alarms.then(|| Arc::clone(&notifier)),
+            Arc::clone(&names),
+            Arc::clone(&health),
+            Arc::clone(&pause),
+            reload_rx,
+            shutdown.clone(),
+        )
+        .await;
+        if matches!(end, SessionEnd::Shutdown) && notifier.is_running() {
+            info!("Stopping the ringing alarm");
+        }
+        notifier.stop();
+        if let Some(task) = hooks_task {
+            task.abort();
+        }
+
+        match end {
+            SessionEnd::Reload(new_config) => {
+                info!("[CTL] Configuration reloaded, restarting monitoring");
+                muted = notifier.muted();
+                names.write().await.retain(|id, _| new_config.is_monitored(id));
+                health.set_ws_connected(false);
+                health.record_reload();
+                events::emit(&events, MonitorEvent::Reloaded);
+                config = *new_config;
+            }
+            SessionEnd::Shutdown => break,
+        }
+    }
+    *session.lock().unwrap_or_else(|e| e.into_inner()) = None;
+
+    if let Some(task) = metrics_task {
+        task.abort();
+    }
+    // A newer daemon may have taken the PID file over
+    if let Some(path) = pid_file {
+        let own = std::fs::read_to_string(&path).is_ok_and(|pid| pid.trim() == std::process::id().to_string());
+        if own {
+            if let Err(e) = std::fs::remove_file(&path) {
+                warn!("Failed to delete PID file: {}", e);
+            }
+        }
+    }
+
+    info!("Shutdown complete.");
+    // Log files and the history are written unbuffered; only the console may hold output
+    let _ = std::io::stdout().flush();
+    let _ = std::io::stderr().flush();
+}
+
+/// Monitor with one configuration until shutdown or a reload.
+///
+/// `alarms` is the notifier subscribed to this session's events, if alarms are on.
+#[allow(clippy::too_many_arguments)]
+async fn monitor_session(
+    config: Arc<Config>,
+    events: EventSender,
+    alarms: Option<Arc<Notifier>>,
+    names: ChannelNames,
+    health: Arc<Health>,
+    pause: Arc<Pause>,
+    mut reload: mpsc::Receiver<Config>,
+    shutdown: watch::Receiver<bool>,
+) -> SessionEnd {
+    // Subscribe before any loop can publish
+    let alarm_task = alarms.map(|alarms| alarms.listen(events.subscribe()));
+
+    let activity = config.inactivity_timeout.map(|timeout| {
+        let channel_ids: Vec<String> = config.monitor_targets().into_iter().map(|t| t.channel_id).collect();
+        Arc::new(ChannelActivity::new(timeout, &channel_ids, clock::now()))
+    });
+
+    // One client for every REST loop, so connections and rate limits are shared
+    let rest = Arc::new(RestClient::new(api_base(&config), &config.token).with_health(Arc::clone(&health)));
+
+    // Fetch initial channel names
+    info!("Fetching initial channel state...");
+    let mut initial_fetch = Ok(());
+    for target in config.monitor_targets() {
+        match fetch_channel(&rest, &target.channel_id).await {
+            Ok(channel) => {
+                info!("Initial channel name: {:?}", channel.name);
+                if let (Some(activity), Some(message_id)) = (&activity, &channel.last_message_id) {
+                    record_activity(activity, &target.channel_id, message_id, "INIT");
+                }
+                if let Some(name) = channel.name {
+                    names.write().await.insert(target.channel_id, name);
+                }
+            }
+            Err(e) => {
+                error!("Failed to fetch initial state of channel {}: {}", target.channel_id, e);
+                if initial_fetch.is_ok() {
+                    initial_fetch = Err(format!("channel {}: {}", target.channel_id, e));
+                }
+            }
+        }
+    }
+    if let (Some(discovery), Some(guild_id)) = (&config.discovery, &config.guild_id) {
+        match scan_guild_channels(&config, &rest, guild_id, &names, &events, &health, false).await {
+            Ok(()) => info!("Discovered {} channel(s) matching {}", discovery.targets().len(), discovery.pattern()),
+            Err(e) => {
+                error!("Failed to list the channels of guild {}: {}", guild_id, e);
+                if initial_fetch.is_ok() {
+                    initial_fetch = Err(format!("guild {} channels: {}", guild_id, e));
+                }
+            }
+        }
+    }
+    health.record_initial_fetch(initial_fetch);
+
+    // Run both monitoring modes concurrently
+    let poll_config = Arc::clone(&config);
+    let poll_rest = Arc::clone(&rest);
+    let poll_events = events.clone();
+    let poll_names = Arc::clone(&names);
+    let poll_health = Arc::clone(&health);
+    let poll_activity = activity.clone();
+    let poll_pause = Arc::clone(&pause);
+
+    let ws_config = Arc::clone(&config);
+    let ws_rest = Arc::clone(&rest);
+    let ws_events = events.clone();
+    let ws_names = Arc::clone(&names);
+    let ws_health = Arc::clone(&health);
+    let ws_activity = activity.clone();
+    let ws_pause = Arc::clone(&pause);
+    let ws_shutdown = shutdown.clone();
+
+    // Member-count tracking is optional and needs a guild to watch
+    let member_config = Arc::clone(&config);
+    let member_rest = Arc::clone(&rest);
+    let member_events = events.clone();
+    let member_pause = Arc::clone(&pause);
+    let member_task = async move {
+        match (member_config.guild_id.clone(), member_config.member_jump_threshold) {
+            (Some(guild_id), Some(threshold)) => {
+                member_count_loop(member_rest, guild_id, threshold, member_events, member_pause).await
+            }
+            _ => std::future::pending().await,
+        }
+    };
+
+    // Audit log reading is optional and needs a guild
+    let audit_config = Arc::clone(&config);
+    let audit_rest = Arc::clone(&rest);
+    let audit_names = Arc::clone(&names);
+    let audit_events = events.clone();
+    let audit_health = Arc::clone(&health);
+    let audit_pause = Arc::clone(&pause);
+    let audit_task = async move {
+        match (audit_config.guild_id.clone(), audit_config.audit_log_interval) {
+            (Some(guild_id), Some(interval)) => {
+                audit_log_loop(
+                    audit_config,
+                    audit_rest,
+                    guild_id,
+                    interval,
+                    audit_names,
+                    audit_events,
+                    audit_health,
+                    audit_pause,
+                )
+                .await
+            }
+            (None, Some(_)) => {
+                warn!("AUDIT_LOG_INTERVAL is set but GUILD_ID is not; audit log reading is disabled");
+                std::future::pending().await
+            }
+            _ => std::future::pending().await,
+        }
+    };
+
+    // Discovery rescans need a pattern and a guild
+    let discovery_config = Arc::clone(&config);
+    let discovery_rest = Arc::clone(&rest);
+    let discovery_names = Arc::clone(&names);
+    let discovery_events = events.clone();
+    let discovery_health = Arc::clone(&health);
+    let discovery_pause = Arc::clone(&pause);
+    let discovery_task = async move {
+        match (discovery_config.discovery.is_some(), discovery_config.guild_id.clone()) {
+            (true, Some(guild_id)) => {
+                discovery_loop(
+                    discovery_config,
+                    discovery_rest,
+                    guild_id,
+                    discovery_names,
+                    discovery_events,
+                    discovery_health,
+                    discovery_pause,
+                )
+                .await
+            }
+            _ => std::future::pending().await,
+        }
+    };
+
+    // Inactivity alerts are optional
+    let idle_config = Arc::clone(&config);
+    let idle_names = Arc::clone(&names);
+    let idle_events = events.clone();
+    let idle_pause = Arc::clone(&pause);
+    let idle_task = async move {
+        match activity {
+            Some(activity) => {
+                inactivity_loop(activity, idle_config, idle_names, idle_events, idle_pause).await
+            }
+            None => std::future::pending().await,
+        }
+    };
+
+    // The health line is only shown when requested (foreground on a terminal)
+    let health_channel_id = config.channel_id.clone();
+    let health_names = Arc::clone(&names);
+    let health_state = Arc::clone(&health);
+    let health_interval = config.health_interval;
+    let health_task = async move {
+        match health_interval {
+            Some(interval) => health_loop(interval, health_state, health_channel_id, health_names).await,
+            None => std::future::pending().await,
+        }
+    };
+
+    let alarm_task = async move {
+        match alarm_task {
+            Some(task) => task.await,
+            None => std::future::pending().await,
+        }
+    };
+
+    info!("Starting dual-mode monitoring (REST polling + WebSocket)...");
+
+    // The Gateway loop is kept past the select so it can close its connection
+    let ws_task =
+        websocket_loop(ws_config, ws_rest, ws_events, ws_names, ws_health, ws_activity, ws_pause, ws_shutdown);
+    tokio::pin!(ws_task);
+    let mut shutdown = shutdown;
+
+    // Checked first, so the Gateway loop returning on shutdown is not reported as unexpected
+    tokio::select! {
+        biased;
+        _ = shutdown_requested(&mut shutdown) => {
+            // The other loops are simply dropped; only the Gateway has a goodbye to say
+            if tokio::time::timeout(WS_CLOSE_TIMEOUT * 2, &mut ws_task).await.is_err() {
+                warn!("[WS] Gave up closing the Gateway connection");
+            }
+        }
+        Some(new_config) = reload.recv() => return SessionEnd::Reload(Box::new(new_config)),
+        _ = poll_loop(poll_config, poll_rest, poll_events, poll_names, poll_health, poll_activity, poll_pause) => {
+            error!("Poll loop ended unexpectedly");
+        }
+        _ = &mut ws_task => {
+            error!("WebSocket loop ended unexpectedly");
+        }
+        _ = member_task => {
+            error!("Member count loop ended unexpectedly");
+        }
+        _ = audit_task => {
+            error!("Audit log loop ended unexpectedly");
+        }
+        _ = discovery_task => {
+            error!("Discovery loop ended unexpectedly");
+        }
+        _ = idle_task => {
+            error!("Inactivity loop ended unexpectedly");
+        }
+        _ = health_task => {
+            error!("Health loop ended unexpectedly");
+        }
+        _ = alarm_task => {
+            error!("Alarm listener ended unexpectedly");
+        }
+    }
+    SessionEnd::Shutdown
+}
 #[cfg(test)]
 mod tests {
     use super::*;
+    use history::HistoryEvent;
+    use crate::trigger::{self, TriggerRule};
+
+    #[test]
+    fn test_status_summary_lists_discovered_channels_without_a_primary() {
+        let mut status: StatusSnapshot = serde_json::from_str(
+            r#"{"channel_name":null,"channels":{"100":"order-a","200":"order-b"},"ws_connected":true,"last_poll_secs":null,"alarm_active":false}"#,
+        )
+        .unwrap();
+        assert_eq!(status.summary(), "WS ok, no successful poll yet, channels: order-a, order-b");
+
+        status.channel_name = Some("order-a".to_string());
+        assert_eq!(status.summary(), "WS ok, no successful poll yet, channel: order-a");
+    }
 
     #[test]
     fn test_constants() {
         assert!(DISCORD_API_BASE.starts_with("https://"));
         assert!(DISCORD_GATEWAY_URL.starts_with("wss://"));
-        assert!(USER_AGENT.contains("Mozilla"));
+        assert!(rest::USER_AGENT.contains("Mozilla"));
     }
 
     #[test]
@@ -366,25 +1940,331 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_last_name_rwlock_behavior() {
-        let last_name: Arc<RwLock<Option<String>>> = Arc::new(RwLock::new(None));
+    async fn test_stage_instance_in_other_guild_is_ignored() {
+        let events = events::channel();
+        let mut received = events.subscribe();
+        let stage = StageInstance {
+            guild_id: "other-guild".to_string(),
+            channel_id: "2".to_string(),
+            topic: "Not ours".to_string(),
+        };
+
+        handle_stage_instance_create(stage, Some("watched-guild"), &events).await;
+        assert!(received.try_recv().is_err());
+
+        let stage = StageInstance {
+            guild_id: "other-guild".to_string(),
+            channel_id: "2".to_string(),
+            topic: "No guild configured".to_string(),
+        };
+        handle_stage_instance_create(stage, None, &events).await;
+        assert!(received.try_recv().is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_inactivity_loop_alerts_after_timeout() {
+        let events = events::channel();
+        let mut received = events.subscribe();
+        let names: ChannelNames = Arc::new(RwLock::new(HashMap::from([
+            ("100".to_string(), "drops".to_string()),
+            ("200".to_string(), "news".to_string()),
+        ])));
+        let config = Arc::new(Config {
+            channel_id: Some("100".to_string()),
+            targets: vec![MonitorTarget::new("100"), MonitorTarget::new("200")],
+            ..Default::default()
+        });
+        let timeout = Duration::from_secs(30 * 60);
+        let activity = Arc::new(ChannelActivity::new(timeout, &["100".to_string(), "200".to_string()], clock::now()));
+        record_activity(&activity, "100", "1", "TEST");
+        record_activity(&activity, "200", "5", "TEST");
+
+        let pause = Arc::new(Pause::default());
+        let idle = tokio::spawn(inactivity_loop(
+            Arc::clone(&activity),
+            config,
+            names,
+            events.clone(),
+            Arc::clone(&pause),
+        ));
+
+        // A new message 20 minutes in restarts the timer of its channel only
+        clock::sleep(Duration::from_secs(20 * 60)).await;
+        record_activity(&activity, "100", "2", "TEST");
+        clock::sleep(Duration::from_secs(11 * 60)).await;
+        assert!(matches!(
+            received.try_recv(),
+            Ok(MonitorEvent::Alert(Alert::ChannelInactive { ref channel_id, ref name, .. }))
+                if channel_id == "200" && name == "news"
+        ));
+        clock::sleep(Duration::from_secs(18 * 60)).await;
+        assert!(received.try_recv().is_err());
+
+        // Quiet time is not reported while paused
+        pause.pause(Some(Duration::from_secs(10 * 60)));
+        clock::sleep(Duration::from_secs(2 * 60)).await;
+        assert!(received.try_recv().is_err());
+
+        clock::sleep(Duration::from_secs(9 * 60)).await;
+        assert!(matches!(
+            received.try_recv(),
+            Ok(MonitorEvent::Alert(Alert::ChannelInactive { ref name, .. })) if name == "drops"
+        ));
+
+        idle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_simulate_rejects_unmonitored_channel() {
+        let config = Config {
+            channel_id: Some("100".to_string()),
+            ..Default::default()
+        };
+        let handle = Monitor::builder(config.clone()).build().handle();
+        assert_eq!(handle.simulate(None, "open").await, Err("The monitor is not running".to_string()));
+
+        let (reload, _) = mpsc::channel(1);
+        *handle.session.lock().unwrap() = Some(Session {
+            config: Arc::new(config),
+            notifier: Arc::new(Notifier::new("/nonexistent/path.mp3".to_string())),
+            reload,
+        });
+        let e = handle.simulate(Some("200".to_string()), "open").await.unwrap_err();
+        assert!(e.contains("is not monitored"), "{}", e);
+        assert!(handle.names.read().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_trigger_rule_filters_renames() {
+        let names: ChannelNames = Arc::new(RwLock::new(HashMap::new()));
+        let history_path = std::env::temp_dir().join(format!("ollie-change-history-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&history_path);
+        let notifier = Arc::new(
+            Notifier::new("/nonexistent/path.mp3".to_string()).with_history(Arc::new(History::new(history_path.clone()))),
+        );
+        let health = Health::default();
+        let events = events::channel();
+        let listener = tokio::spawn(Arc::clone(&notifier).listen(events.subscribe()));
+        let target = MonitorTarget {
+            channel_id: "100".to_string(),
+            rule: TriggerRule {
+                trigger: Some(trigger::compile("TRIGGER", "✅").unwrap()),
+                ignore: None,
+            },
+            ..Default::default()
+        };
+
+        // The name is still tracked, but a rename the rule rejects does not alarm
+        check_and_notify_change(&target, Some("order-❌".to_string()), None, &names, &events, &health, "TEST").await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!notifier.is_running());
+        assert_eq!(names.read().await.get("100").map(String::as_str), Some("order-❌"));
+
+        check_and_notify_change(&target, Some("order-✅".to_string()), None, &names, &events, &health, "TEST").await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(notifier.is_running());
+        assert!(notifier.active_alarms()[0].ends_with("\nTrigger: ✅ (✅)"));
+
+        notifier.stop();
+        listener.abort();
+
+        // Both renames are recorded, with whether they alarmed
+        let changes: Vec<(Option<String>, String, bool)> = history::read_events(&history_path)
+            .unwrap()
+            .into_iter()
+            .filter_map(|event| match event {
+                HistoryEvent::Change { old_name, new_name, alarm, .. } => Some((old_name, new_name, alarm)),
+                _ => None,
+            })
+            .collect();
+        std::fs::remove_file(&history_path).ok();
+        assert_eq!(
+            changes,
+            [
+                (None, "order-❌".to_string(), false),
+                (Some("order-❌".to_string()), "order-✅".to_string(), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_gateway_session_resumes_until_reset() {
+        let config = Config::default();
+        let mut session = GatewaySession::default();
+        assert_eq!(session.handshake("token").op, 2);
+        assert_eq!(session.connect_url(&config), DISCORD_GATEWAY_URL);
+
+        session.ready(&serde_json::json!({
+            "session_id": "abc",
+            "resume_gateway_url": "wss://gateway-us-east1-b.discord.gg",
+        }));
+        session.sequence = Some(42);
+        let resume = session.handshake("token");
+        assert_eq!(resume.op, 6);
+        let d = resume.d.unwrap();
+        assert_eq!((d["session_id"].as_str(), d["seq"].as_u64()), (Some("abc"), Some(42)));
+        assert_eq!(session.connect_url(&config), "wss://gateway-us-east1-b.discord.gg/?v=9&encoding=json");
+
+        // An explicit Gateway URL always wins
+        let mock = Config {
+            gateway_url: Some("ws://127.0.0.1:9001".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(session.connect_url(&mock), "ws://127.0.0.1:9001");
+
+        assert!(close_ends_session(4009));
+        assert!(!close_ends_session(1001));
+        session.reset();
+        assert_eq!(session.handshake("token").op, 2);
+        assert_eq!(session.sequence, None);
+    }
+
+    #[test]
+    fn test_stream_state_alerts_only_on_go_live() {
+        let mut state = GatewayWatchState::default();
+
+        // Going live via voice triggers once
+        assert!(state.update_voice_stream(true));
+        assert!(!state.update_voice_stream(true));
+
+        // Presence streaming while already live in voice does not re-trigger
+        assert!(!state.update_presence_stream(true));
+
+        // Still live via presence after voice stream ends
+        assert!(!state.update_voice_stream(false));
+        assert!(state.is_streaming());
+
+        // Fully offline, then live again triggers
+        assert!(!state.update_presence_stream(false));
+        assert!(state.update_presence_stream(true));
+    }
+
+    #[test]
+    fn test_voice_channel_join_detection() {
+        let mut state = GatewayWatchState::default();
+
+        assert_eq!(state.update_voice_channel(None), None);
+        assert_eq!(state.update_voice_channel(Some("10".to_string())), Some("10".to_string()));
+        // Mute/deafen updates repeat the same channel
+        assert_eq!(state.update_voice_channel(Some("10".to_string())), None);
+        // Switching channels counts as a new join
+        assert_eq!(state.update_voice_channel(Some("11".to_string())), Some("11".to_string()));
+        assert_eq!(state.update_voice_channel(None), None);
+        assert_eq!(state.update_voice_channel(Some("11".to_string())), Some("11".to_string()));
+    }
+
+    #[test]
+    fn test_matches_role_pattern_case_insensitive() {
+        let patterns = vec!["customer".to_string(), "Access".to_string()];
+        assert!(matches_role_pattern("Verified Customer", &patterns));
+        assert!(matches_role_pattern("EARLY ACCESS", &patterns));
+        assert!(!matches_role_pattern("Moderator", &patterns));
+        assert!(!matches_role_pattern("Customer", &[]));
+    }
+
+    #[test]
+    fn test_observe_role_tracks_permission_changes() {
+        let mut state = GatewayWatchState::default();
+        let mut role = Role {
+            id: "1".to_string(),
+            name: "Customer".to_string(),
+            permissions: "0".to_string(),
+        };
+
+        assert_eq!(state.observe_role(&role), RoleChange::Appeared);
+        assert_eq!(state.observe_role(&role), RoleChange::Unchanged);
+
+        role.permissions = "1024".to_string();
+        assert_eq!(
+            state.observe_role(&role),
+            RoleChange::PermissionsChanged { old: "0".to_string() }
+        );
+    }
+
+    #[test]
+    fn test_seeded_roles_do_not_appear_again() {
+        let config = Config {
+            role_patterns: vec!["access".to_string()],
+            ..Default::default()
+        };
+        let role = Role {
+            id: "7".to_string(),
+            name: "Early Access".to_string(),
+            permissions: "8".to_string(),
+        };
+        let guild = GatewayGuild {
+            id: "42".to_string(),
+            roles: vec![role.clone()],
+        };
+
+        let mut state = GatewayWatchState::default();
+        state.seed_roles(&guild, &config);
+        assert_eq!(state.observe_role(&role), RoleChange::Unchanged);
+    }
+
+    #[test]
+    fn test_is_watched_guild() {
+        let mut config = Config::default();
+        assert!(is_watched_guild(Some("any"), &config));
+        assert!(is_watched_guild(None, &config));
+
+        config.guild_id = Some("42".to_string());
+        assert!(is_watched_guild(Some("42"), &config));
+        assert!(!is_watched_guild(Some("43"), &config));
+        assert!(!is_watched_guild(None, &config));
+    }
+
+    #[tokio::test]
+    async fn test_channel_names_rwlock_behavior() {
+        let names: ChannelNames = Arc::new(RwLock::new(HashMap::new()));
 
         // Initial state
         {
-            let read = last_name.read().await;
-            assert!(read.is_none());
+            let read = names.read().await;
+            assert!(read.get("100").is_none());
         }
 
         // Write new value
         {
-            let mut write = last_name.write().await;
-            *write = Some("test-channel".to_string());
+            let mut write = names.write().await;
+            write.insert("100".to_string(), "test-channel".to_string());
         }
 
         // Read new value
         {
-            let read = last_name.read().await;
-            assert_eq!(*read, Some("test-channel".to_string()));
+            let read = names.read().await;
+            assert_eq!(read.get("100").map(String::as_str), Some("test-channel"));
         }
     }
+
+    #[tokio::test]
+    async fn test_renames_are_tracked_per_channel() {
+        let names: ChannelNames = Arc::new(RwLock::new(HashMap::from([("100".to_string(), "orders".to_string())])));
+        let health = Arc::new(Health::default());
+        let events = events::channel();
+        let mut received = events.subscribe();
+
+        // A name seen again for the same channel does not alarm
+        let orders = MonitorTarget::new("100");
+        check_and_notify_change(&orders, Some("orders".to_string()), None, &names, &events, &health, "TEST").await;
+        assert!(received.try_recv().is_err());
+        assert_eq!(health.stats().renames, 0);
+
+        let drops = MonitorTarget::new("200");
+        check_and_notify_change(&drops, Some("orders".to_string()), None, &names, &events, &health, "TEST").await;
+        assert_eq!(
+            received.try_recv().unwrap(),
+            MonitorEvent::NameChanged {
+                channel_id: "200".to_string(),
+                old_name: None,
+                new_name: "orders".to_string(),
+                changed_by: None,
+                source: "TEST".to_string(),
+                alarm: true,
+                trigger: None,
+            }
+        );
+        assert_eq!(names.read().await.len(), 2);
+        assert_eq!(health.stats().renames, 1);
+    }
 }
@@ -0,0 +1,320 @@
+//! Append-only event history.
+//!
+//! Events are stored one JSON object per line in the file given to
+//! [`MonitorBuilder::history`](super::MonitorBuilder::history), so they
+//! survive restarts and can be read without asking the running monitor.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// How a ringing alarm was silenced.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AckSource {
+    /// The "Silence" action on the desktop notification.
+    Notification,
+    /// `ollie-scraper ack` over the control socket.
+    Cli,
+    /// The alarm rang for its maximum duration.
+    Timeout,
+}
+
+impl AckSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AckSource::Notification => "notification",
+            AckSource::Cli => "cli",
+            AckSource::Timeout => "timeout",
+        }
+    }
+}
+
+/// A recorded event.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum HistoryEvent {
+    /// An alarm started ringing.
+    Alarm {
+        id: u64,
+        at: DateTime<Utc>,
+        title: String,
+        body: String,
+    },
+    /// A ringing alarm was silenced.
+    Ack {
+        alarm_id: u64,
+        at: DateTime<Utc>,
+        via: AckSource,
+    },
+    /// An alert for a muted channel, detected but not alarmed.
+    Muted {
+        at: DateTime<Utc>,
+        channel: String,
+        title: String,
+        body: String,
+    },
+    /// A monitored channel was renamed.
+    Change {
+        at: DateTime<Utc>,
+        channel_id: String,
+        /// Where the rename was seen, e.g. `POLL` or `WS`.
+        source: String,
+        old_name: Option<String>,
+        new_name: String,
+        /// Whether the new name passed the trigger rule and raised an alarm
+        /// (which mutes and alarm budgets may still have silenced).
+        alarm: bool,
+    },
+}
+
+/// Which recorded renames `history` lists.
+#[derive(Debug, Clone, Default)]
+pub struct ChangeFilter {
+    pub channel_id: Option<String>,
+    /// Source such as `ws`, case-insensitive.
+    pub source: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    /// Only renames that raised an alarm.
+    pub alarms_only: bool,
+}
+
+impl ChangeFilter {
+    /// Whether `event` is a rename this filter keeps.
+    pub fn matches(&self, event: &HistoryEvent) -> bool {
+        let HistoryEvent::Change { at, channel_id, source, alarm, .. } = event else {
+            return false;
+        };
+        self.channel_id.as_ref().is_none_or(|id| id == channel_id)
+            && self.source.as_ref().is_none_or(|s| s.eq_ignore_ascii_case(source))
+            && self.since.is_none_or(|since| *at >= since)
+            && (*alarm || !self.alarms_only)
+    }
+}
+
+/// Aggregate statistics over recorded renames.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChangeStats {
+    pub changes: usize,
+    pub alarms: usize,
+    /// Renames per day.
+    pub per_day: BTreeMap<NaiveDate, usize>,
+    /// How long each channel stayed open: from a rename that alarmed to the
+    /// next rename of the same channel that did not.
+    pub open_durations: Vec<chrono::Duration>,
+}
+
+impl ChangeStats {
+    /// Mean open duration, if any opening has closed.
+    pub fn average_open(&self) -> Option<chrono::Duration> {
+        let count = i32::try_from(self.open_durations.len()).ok().filter(|&n| n > 0)?;
+        Some(self.open_durations.iter().sum::<chrono::Duration>() / count)
+    }
+}
+
+/// Summarize the renames among `events`, which are in recorded order.
+///
+/// `day` maps a timestamp to the calendar day it is counted under.
+pub fn change_stats<'a>(
+    events: impl IntoIterator<Item = &'a HistoryEvent>,
+    day: impl Fn(DateTime<Utc>) -> NaiveDate,
+) -> ChangeStats {
+    let mut stats = ChangeStats::default();
+    let mut opened: HashMap<&str, DateTime<Utc>> = HashMap::new();
+    for event in events {
+        let HistoryEvent::Change { at, channel_id, alarm, .. } = event else {
+            continue;
+        };
+        stats.changes += 1;
+        *stats.per_day.entry(day(*at)).or_default() += 1;
+        if *alarm {
+            stats.alarms += 1;
+            opened.entry(channel_id).or_insert(*at);
+        } else if let Some(since) = opened.remove(channel_id.as_str()) {
+            stats.open_durations.push(*at - since);
+        }
+    }
+    stats
+}
+
+/// Writer for the history file.
+pub struct History {
+    path: PathBuf,
+    // Serializes appends from concurrent alarms
+    lock: Mutex<()>,
+}
+
+impl History {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Append one event as a JSON line.
+    pub fn append(&self, event: &HistoryEvent) -> Result<(), String> {
+        let mut line = serde_json::to_string(event).map_err(|e| format!("Failed to encode event: {}", e))?;
+        line.push('\n');
+
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut f| f.write_all(line.as_bytes()))
+            .map_err(|e| format!("Failed to write {:?}: {}", self.path, e))
+    }
+}
+
+/// Read all events, skipping lines that fail to parse.
+pub fn read_events(path: &Path) -> Result<Vec<HistoryEvent>, String> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read {:?}: {}", path, e)),
+    };
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Alarms that have no matching acknowledgement, oldest first.
+pub fn unacknowledged(events: &[HistoryEvent]) -> Vec<&HistoryEvent> {
+    let acknowledged: HashSet<u64> = events
+        .iter()
+        .filter_map(|event| match event {
+            HistoryEvent::Ack { alarm_id, .. } => Some(*alarm_id),
+            _ => None,
+        })
+        .collect();
+    events
+        .iter()
+        .filter(|event| matches!(event, HistoryEvent::Alarm { id, .. } if !acknowledged.contains(id)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alarm(id: u64) -> HistoryEvent {
+        HistoryEvent::Alarm {
+            id,
+            at: Utc::now(),
+            title: "CHANNEL OPEN".to_string(),
+            body: format!("Channel is now: {}", id),
+        }
+    }
+
+    fn ack(alarm_id: u64, via: AckSource) -> HistoryEvent {
+        HistoryEvent::Ack {
+            alarm_id,
+            at: Utc::now(),
+            via,
+        }
+    }
+
+    fn change(minute: u32, channel_id: &str, source: &str, alarm: bool) -> HistoryEvent {
+        HistoryEvent::Change {
+            at: DateTime::parse_from_rfc3339(&format!("2025-01-24T23:{:02}:00Z", minute)).unwrap().to_utc(),
+            channel_id: channel_id.to_string(),
+            source: source.to_string(),
+            old_name: None,
+            new_name: if alarm { "order-✅" } else { "order-❌" }.to_string(),
+            alarm,
+        }
+    }
+
+    #[test]
+    fn test_event_wire_format() {
+        let json = serde_json::to_string(&ack(7, AckSource::Cli)).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(value["event"], "ack");
+        assert_eq!(value["alarm_id"], 7);
+        assert_eq!(value["via"], "cli");
+    }
+
+    #[test]
+    fn test_unacknowledged_alarms() {
+        let muted = HistoryEvent::Muted {
+            at: Utc::now(),
+            channel: "100".to_string(),
+            title: "CHANNEL OPEN".to_string(),
+            body: "Channel is now: open".to_string(),
+        };
+        let events = vec![alarm(1), alarm(2), ack(1, AckSource::Notification), muted, alarm(3)];
+
+        let pending = unacknowledged(&events);
+        assert_eq!(pending, vec![&events[1], &events[4]]);
+    }
+
+    #[test]
+    fn test_append_and_read_round_trip() {
+        let path = std::env::temp_dir().join(format!("ollie-history-test-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let history = History::new(path.clone());
+
+        history.append(&alarm(1)).unwrap();
+        history.append(&ack(1, AckSource::Timeout)).unwrap();
+        // Corrupt lines (e.g. from a crash mid-write) are skipped
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .and_then(|mut f| f.write_all(b"{\"event\":\n"))
+            .unwrap();
+
+        let events = read_events(&path).unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(unacknowledged(&events).is_empty());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_change_filter() {
+        let events = [change(0, "100", "POLL", true), change(5, "200", "WS", false)];
+        let kept = |filter: ChangeFilter| events.iter().filter(|e| filter.matches(e)).count();
+
+        assert_eq!(kept(ChangeFilter::default()), 2);
+        assert_eq!(kept(ChangeFilter { channel_id: Some("200".to_string()), ..Default::default() }), 1);
+        assert_eq!(kept(ChangeFilter { source: Some("poll".to_string()), ..Default::default() }), 1);
+        assert_eq!(kept(ChangeFilter { alarms_only: true, ..Default::default() }), 1);
+        let since = DateTime::parse_from_rfc3339("2025-01-24T23:01:00Z").unwrap().to_utc();
+        assert_eq!(kept(ChangeFilter { since: Some(since), ..Default::default() }), 1);
+        assert!(!ChangeFilter::default().matches(&alarm(1)));
+    }
+
+    #[test]
+    fn test_change_stats_open_durations() {
+        let events = vec![
+            change(0, "100", "WS", true),
+            // Still open, and another channel opens meanwhile
+            change(10, "100", "WS", true),
+            change(15, "200", "POLL", true),
+            change(30, "100", "WS", false),
+            alarm(1),
+            change(45, "200", "POLL", false),
+            // Closing a channel that is not open counts no duration
+            change(50, "200", "POLL", false),
+        ];
+        let stats = change_stats(&events, |at| at.date_naive());
+
+        assert_eq!(stats.changes, 6);
+        assert_eq!(stats.alarms, 3);
+        assert_eq!(stats.per_day.values().copied().collect::<Vec<_>>(), [6]);
+        assert_eq!(stats.open_durations, [chrono::Duration::minutes(30), chrono::Duration::minutes(30)]);
+        assert_eq!(stats.average_open(), Some(chrono::Duration::minutes(30)));
+        assert_eq!(ChangeStats::default().average_open(), None);
+    }
+
+    #[test]
+    fn test_missing_history_is_empty() {
+        let path = std::env::temp_dir().join("ollie-history-does-not-exist.jsonl");
+        assert!(read_events(&path).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,63 @@
+//! Highlighting what changed between two channel names.
+
+/// Mark the segment that differs between `old` and `new`.
+///
+/// Shared leading and trailing characters are kept as-is and the differing
+/// middle is shown as `[old → new]`, e.g. `〖start-order-[❌ → ✅]〗`.
+pub fn highlight_change(old: &str, new: &str) -> String {
+    let old_chars: Vec<char> = old.chars().collect();
+    let new_chars: Vec<char> = new.chars().collect();
+
+    let prefix = old_chars
+        .iter()
+        .zip(&new_chars)
+        .take_while(|(a, b)| a == b)
+        .count();
+    // The suffix must not overlap the prefix in either name
+    let max_suffix = old_chars.len().min(new_chars.len()) - prefix;
+    let suffix = old_chars
+        .iter()
+        .rev()
+        .zip(new_chars.iter().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let collect = |chars: &[char]| chars.iter().collect::<String>();
+    format!(
+        "{}[{} → {}]{}",
+        collect(&new_chars[..prefix]),
+        collect(&old_chars[prefix..old_chars.len() - suffix]),
+        collect(&new_chars[prefix..new_chars.len() - suffix]),
+        collect(&new_chars[new_chars.len() - suffix..]),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlights_changed_emoji() {
+        assert_eq!(
+            highlight_change("〖start-order-❌〗", "〖start-order-✅〗"),
+            "〖start-order-[❌ → ✅]〗"
+        );
+    }
+
+    #[test]
+    fn test_highlights_insertions_and_removals() {
+        assert_eq!(highlight_change("orders", "orders-open"), "orders[ → -open]");
+        assert_eq!(highlight_change("orders-open", "orders"), "orders[-open → ]");
+    }
+
+    #[test]
+    fn test_repeated_characters_do_not_overlap() {
+        assert_eq!(highlight_change("aa", "aaa"), "aa[ → a]");
+    }
+
+    #[test]
+    fn test_completely_different_names() {
+        assert_eq!(highlight_change("closed", "open"), "[closed → open]");
+    }
+}
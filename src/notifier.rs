@@ -1,29 +1,307 @@
 //! Notification and audio alarm system for channel status changes.
 //!
-//! This module provides desktop notifications via `notify-send` and audio alerts
-//! via `mpv` that loop until explicitly stopped.
+//! This module provides desktop notifications (`notify-send` on Linux,
+//! `osascript` on macOS, a PowerShell toast on Windows), audio alerts via `mpv`
+//! or the native player that loop until explicitly stopped, and optional remote
+//! pushes to the configured [sinks](crate::sinks).
+//!
+//! Each alarm and how it was silenced is recorded in the event history when one
+//! is attached. Simultaneous alarms are queued by priority so only one plays
+//! sound at a time, and popups during a burst are collapsed into one summary.
+//! A source that is already ringing, cooling down, or over its budget only
+//! shows the alert, and alarms left unacknowledged can escalate.
 
+use crate::alarm_queue::{self, AlarmQueue, QueuedAlarm};
+use crate::audio::{self, AudioBackend};
+use crate::budget::{AlarmBudget, ALARM_BUDGET_WINDOW};
+use crate::clock;
+use crate::compound::{CompoundRule, CompoundTrigger};
+use crate::config::{EscalationSettings, NotificationSettings};
+use crate::events::{self, EventSender, MonitorEvent};
+use crate::grouping::{Burst, Popup, PopupGroup, RenameTally};
+use crate::health::Health;
+use crate::monitor::history::{AckSource, History, HistoryEvent};
+use crate::i18n::{Alert, Language};
+use crate::models::MonitorTarget;
+use crate::playlist::{self, Playlist, SoundOrder, SoundRotation};
+use crate::sinks::{self, NotificationSink};
+use crate::trigger::TriggerMatch;
+use futures_util::future::join_all;
 use tokio::process::Command;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use tokio::sync::broadcast::{self, error::RecvError};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+/// Key of the "Silence" action on desktop notifications.
+const ACK_ACTION: &str = "ack";
+/// PowerShell script showing a toast with the `OLLIE_TITLE` and `OLLIE_BODY` variables.
+const WINDOWS_TOAST_SCRIPT: &str = r#"
+[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] | Out-Null
+$xml = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02)
+$text = $xml.GetElementsByTagName('text')
+$text.Item(0).AppendChild($xml.CreateTextNode($env:OLLIE_TITLE)) | Out-Null
+$text.Item(1).AppendChild($xml.CreateTextNode($env:OLLIE_BODY)) | Out-Null
+$app = '{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\WindowsPowerShell\v1.0\powershell.exe'
+[Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier($app).Show([Windows.UI.Notifications.ToastNotification]::new($xml))
+"#;
 
-/// Notifier handles desktop notifications and looping audio alarms.
+/// Notifier handles desktop notifications, looping audio alarms, and remote pushes.
 pub struct Notifier {
     sound_path: String,
+    playlist: Playlist,
+    sound_order: SoundOrder,
+    sound_rotation: SoundRotation,
+    audio_backend: AudioBackend,
+    alarm_volume: Option<u8>,
+    /// Remote sinks every alert is sent to, unless its channel narrows them.
+    sinks: Vec<Arc<dyn NotificationSink>>,
+    language: Language,
+    alarm_timeout: Option<Duration>,
+    escalation: Option<EscalationSettings>,
+    priorities: Vec<(String, i32)>,
+    budget: Mutex<AlarmBudget>,
+    /// Popup grouping during bursts; `None` shows every popup.
+    popups: Option<Mutex<PopupGroup<HeldPopup>>>,
     running: Arc<AtomicBool>,
+    /// Active alarms; the head plays sound.
+    queue: Arc<Mutex<AlarmQueue>>,
+    last_alarm_id: AtomicU64,
+    history: Option<Arc<History>>,
+    /// Where rung alarms are counted.
+    health: Option<Arc<Health>>,
+    /// When set, renames only alarm together with a keyword message.
+    compound: Option<Mutex<CompoundTrigger>>,
+    /// ID of the primary monitored channel, so mutes can name it.
+    channel_id: Option<String>,
+    /// Title and sound overrides of monitored channels, by channel ID.
+    channel_alarms: HashMap<String, ChannelAlarm>,
+    /// Channels (by ID or name) whose alerts are recorded but not alarmed.
+    muted: Mutex<BTreeSet<String>>,
+    /// Where raised alarms are announced as [`MonitorEvent::AlarmRaised`].
+    events: Option<EventSender>,
+}
+
+/// An alert held for a burst's grouped summary.
+struct HeldPopup {
+    summary: String,
+    /// A ringing alarm already showed its own popup, so only its remote
+    /// messages are grouped.
+    shown: bool,
+    /// The sinks its channel sends to.
+    remote: Vec<Arc<dyn NotificationSink>>,
+}
+
+/// The held alerts to summarize for each sink, in the order the sinks first
+/// appear.
+fn group_by_sink(held: &[HeldPopup]) -> Vec<(Arc<dyn NotificationSink>, Vec<String>)> {
+    let mut groups: Vec<(Arc<dyn NotificationSink>, Vec<String>)> = Vec::new();
+    for popup in held {
+        for sink in &popup.remote {
+            match groups.iter_mut().find(|(known, _)| Arc::ptr_eq(known, sink)) {
+                Some((_, alerts)) => alerts.push(popup.summary.clone()),
+                None => groups.push((Arc::clone(sink), vec![popup.summary.clone()])),
+            }
+        }
+    }
+    groups
+}
+
+/// Alarm overrides for one monitored channel.
+struct ChannelAlarm {
+    title: Option<String>,
+    playlist: Option<Playlist>,
+    sinks: Option<Vec<Arc<dyn NotificationSink>>>,
+}
+
+/// Silence alarm `id` (or the ringing one when `None`) and record how,
+/// returning false if there was no such alarm.
+fn acknowledge_alarm(
+    running: &AtomicBool,
+    queue: &Mutex<AlarmQueue>,
+    history: Option<&History>,
+    id: Option<u64>,
+    via: AckSource,
+) -> bool {
+    let mut queue = queue.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(alarm) = queue.remove(id) else {
+        return false;
+    };
+    if queue.is_empty() {
+        running.store(false, Ordering::SeqCst);
+    }
+    drop(queue);
+    info!("[ALARM] Acknowledged via {}: {}", via.as_str(), alarm.title);
+
+    if let Some(history) = history {
+        let event = HistoryEvent::Ack {
+            alarm_id: alarm.id,
+            at: chrono::Utc::now(),
+            via,
+        };
+        if let Err(e) = history.append(&event) {
+            warn!("Failed to record event history: {}", e);
+        }
+    }
+    true
 }
 
 impl Notifier {
     /// Create a new Notifier with the specified sound file path.
+    #[cfg(test)]
     pub fn new(sound_path: String) -> Self {
-        Self {
+        Self::from_settings(&NotificationSettings {
             sound_path,
+            ..Default::default()
+        })
+    }
+
+    /// Create a Notifier with all backends from the notification settings.
+    pub fn from_settings(settings: &NotificationSettings) -> Self {
+        // Settings are validated on load, so this only fails for hand-built ones
+        let sinks = sinks::from_settings(settings, &reqwest::Client::new()).unwrap_or_else(|e| {
+            error!("Remote alerts disabled: {}", e);
+            Vec::new()
+        });
+        Self {
+            sound_path: settings.sound_path.clone(),
+            playlist: Playlist::new(playlist::resolve_sounds(&settings.sound_path), settings.sound_order),
+            sound_order: settings.sound_order,
+            sound_rotation: settings.sound_rotation,
+            audio_backend: settings.audio_backend,
+            alarm_volume: settings.alarm_volume,
+            sinks,
+            language: settings.language,
+            alarm_timeout: settings.alarm_timeout,
+            escalation: settings.escalation.clone(),
+            priorities: settings.alarm_priorities.clone(),
+            budget: Mutex::new(
+                AlarmBudget::new(settings.alarm_channel_limit, settings.alarm_global_limit, ALARM_BUDGET_WINDOW)
+                    .with_cooldown(settings.alarm_cooldown),
+            ),
+            popups: settings.group_window.map(|window| Mutex::new(PopupGroup::new(window))),
             running: Arc::new(AtomicBool::new(false)),
+            queue: Arc::new(Mutex::new(AlarmQueue::default())),
+            last_alarm_id: AtomicU64::new(0),
+            history: None,
+            health: None,
+            compound: None,
+            channel_id: None,
+            channel_alarms: HashMap::new(),
+            muted: Mutex::new(BTreeSet::new()),
+            events: None,
         }
     }
 
+    /// Record alarms and acknowledgements in `history`.
+    pub fn with_history(mut self, history: Arc<History>) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// Announce every raised alarm on `events`.
+    pub fn with_events(mut self, events: EventSender) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Count rung alarms in `health`.
+    pub fn with_health(mut self, health: Arc<Health>) -> Self {
+        self.health = Some(health);
+        self
+    }
+
+    /// Hold channel alarms until `rule` is fully matched.
+    pub fn with_compound(mut self, rule: Option<CompoundRule>) -> Self {
+        self.compound = rule.map(|rule| Mutex::new(CompoundTrigger::new(rule)));
+        self
+    }
+
+    /// Identify the primary monitored channel, so muting its ID silences its alerts.
+    pub fn with_channel_id(mut self, channel_id: &str) -> Self {
+        self.channel_id = Some(channel_id.to_string());
+        self
+    }
+
+    /// Use each target's title and sound, where set, for its channel's alarms.
+    pub fn with_targets(mut self, targets: &[MonitorTarget]) -> Self {
+        for target in targets {
+            let alarm = ChannelAlarm {
+                title: target.title.clone(),
+                playlist: target
+                    .sound_path
+                    .as_deref()
+                    .map(|path| Playlist::new(playlist::resolve_sounds(path), self.sound_order)),
+                sinks: target.sinks.as_ref().map(|names| {
+                    self.sinks.iter().filter(|sink| names.iter().any(|n| n == sink.name())).cloned().collect()
+                }),
+            };
+            self.channel_alarms.insert(target.channel_id.clone(), alarm);
+        }
+        self
+    }
+
+    /// Stop alarming for a channel; returns false if it was already muted.
+    pub fn mute(&self, channel: &str) -> bool {
+        self.muted.lock().unwrap_or_else(|e| e.into_inner()).insert(channel.to_string())
+    }
+
+    /// Resume alarming for a channel; returns false if it was not muted.
+    pub fn unmute(&self, channel: &str) -> bool {
+        self.muted.lock().unwrap_or_else(|e| e.into_inner()).remove(channel)
+    }
+
+    /// Muted channels, sorted.
+    pub fn muted(&self) -> Vec<String> {
+        self.muted.lock().unwrap_or_else(|e| e.into_inner()).iter().cloned().collect()
+    }
+
+    /// The muted channel an alert belongs to, if any.
+    ///
+    /// Monitored-channel alerts belong to `channel_id`, or the primary channel when `None`.
+    fn muted_channel(&self, alert: &Alert, channel_id: Option<&str>) -> Option<String> {
+        let channels: Vec<&str> = match alert {
+            Alert::ChannelOpen { name, .. }
+            | Alert::ChannelOpenWithMessage { name, .. }
+            | Alert::MessagePosted { channel: name, .. } => {
+                channel_id.or(self.channel_id.as_deref()).into_iter().chain([name.as_str()]).collect()
+            }
+            Alert::ChannelInactive { channel_id, name, .. } => vec![channel_id, name],
+            Alert::UserStreamingInVoice { channel_id, .. } => vec![channel_id],
+            Alert::UserJoinedVoice { channel, .. } => vec![channel],
+            _ => Vec::new(),
+        };
+        let muted = self.muted.lock().unwrap_or_else(|e| e.into_inner());
+        channels.into_iter().find(|c| muted.contains(*c)).map(str::to_string)
+    }
+
+    /// The configured `SOUND_PATH` value.
+    pub fn sound_path(&self) -> &str {
+        &self.sound_path
+    }
+
+    /// Every sound file the alarm rotates through.
+    pub fn sounds(&self) -> &[String] {
+        self.playlist.sounds()
+    }
+
+    /// The configured remote sink called `name`, if any.
+    pub fn sink(&self, name: &str) -> Option<&dyn NotificationSink> {
+        self.sinks.iter().find(|sink| sink.name() == name).map(|sink| sink.as_ref())
+    }
+
+    /// The remote sinks alerted for `channel_id`'s alerts, or for other alerts.
+    fn sinks_for(&self, channel_id: Option<&str>) -> &[Arc<dyn NotificationSink>] {
+        channel_id
+            .and_then(|id| self.channel_alarms.get(id))
+            .and_then(|overrides| overrides.sinks.as_deref())
+            .unwrap_or(&self.sinks)
+    }
+
     /// Get a clone of the running flag for external control.
+    #[cfg(test)]
     pub fn running_flag(&self) -> Arc<AtomicBool> {
         Arc::clone(&self.running)
     }
@@ -33,77 +311,677 @@ impl Notifier {
         self.running.load(Ordering::SeqCst)
     }
 
-    /// Send a desktop notification using notify-send.
-    pub async fn send_notification(channel_name: &str) -> std::io::Result<std::process::Output> {
-        Command::new("notify-send")
-            .args(["-u", "critical", "CHANNEL OPEN", &format!("Channel is now: {}", channel_name)])
-            .output()
-            .await
+    /// Active alarms as `TITLE: body`, the ringing one first.
+    pub fn active_alarms(&self) -> Vec<String> {
+        let queue = self.queue.lock().unwrap_or_else(|e| e.into_inner());
+        queue.iter().map(|a| format!("{}: {}", a.title, a.body)).collect()
     }
 
-    /// Play the alarm sound once using mpv.
-    pub async fn play_sound(&self) -> std::io::Result<std::process::Output> {
-        Command::new("mpv")
-            .args(["--no-video", "--really-quiet", &self.sound_path])
-            .output()
-            .await
+    /// History IDs of the active alarms, in the order of [`active_alarms`](Self::active_alarms).
+    pub fn active_alarm_ids(&self) -> Vec<u64> {
+        let queue = self.queue.lock().unwrap_or_else(|e| e.into_inner());
+        queue.iter().map(|a| a.id).collect()
+    }
+
+    /// Render an alert as (title, body) in the configured language.
+    pub fn render(&self, alert: &Alert) -> (String, String) {
+        (alert.title(self.language), alert.body(self.language))
+    }
+
+    /// Send a desktop notification with an arbitrary title and body.
+    pub async fn send_alert_notification(title: &str, body: &str) -> std::io::Result<std::process::Output> {
+        Self::notification_command(title, body).output().await
+    }
+
+    /// The desktop notification command for this platform.
+    fn notification_command(title: &str, body: &str) -> Command {
+        if cfg!(target_os = "macos") {
+            let mut command = Command::new("osascript");
+            command.args(Self::build_osascript_args(title, body));
+            command
+        } else if cfg!(windows) {
+            let mut command = Command::new("powershell");
+            command
+                .args(["-NoProfile", "-NonInteractive", "-Command", WINDOWS_TOAST_SCRIPT])
+                .env("OLLIE_TITLE", title)
+                .env("OLLIE_BODY", body);
+            command
+        } else {
+            let mut command = Command::new("notify-send");
+            command.args(Self::build_alert_args(title, body));
+            command
+        }
+    }
+
+    /// Build the osascript arguments for a notification on macOS.
+    ///
+    /// The title and body are passed as script arguments, so they need no quoting.
+    pub fn build_osascript_args(title: &str, body: &str) -> Vec<String> {
+        [
+            "-e",
+            "on run argv",
+            "-e",
+            "display notification (item 2 of argv) with title (item 1 of argv)",
+            "-e",
+            "end run",
+            title,
+            body,
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect()
+    }
+
+    /// Play the first alarm sound once with the configured backend.
+    pub async fn play_sound(&self) -> Result<(), String> {
+        let first = self.playlist.sounds().first().map(String::as_str).unwrap_or_default();
+        audio::play(self.audio_backend, first, self.alarm_volume).await
     }
 
     /// Build the notify-send command arguments (for testing).
+    #[cfg(test)]
     pub fn build_notification_args(channel_name: &str) -> Vec<String> {
+        let alert = Alert::ChannelOpen {
+            name: channel_name.to_string(),
+            previous: None,
+            changed_by: None,
+            trigger: None,
+        };
+        Self::build_alert_args(&alert.title(Language::En), &alert.body(Language::En))
+    }
+
+    /// Build the notify-send command arguments for a titled alert.
+    pub fn build_alert_args(title: &str, body: &str) -> Vec<String> {
         vec![
             "-u".to_string(),
             "critical".to_string(),
-            "CHANNEL OPEN".to_string(),
-            format!("Channel is now: {}", channel_name),
+            title.to_string(),
+            body.to_string(),
         ]
     }
 
-    /// Build the mpv command arguments (for testing).
+    /// Build notify-send arguments for an alert with a "Silence" action.
+    ///
+    /// With `--wait`, notify-send prints the action key when it is clicked.
+    pub fn build_action_args(title: &str, body: &str) -> Vec<String> {
+        let mut args = Self::build_alert_args(title, body);
+        args.push(format!("--action={}=Silence", ACK_ACTION));
+        args.push("--wait".to_string());
+        args
+    }
+
+    /// Show the alarm's desktop notification in the background, acknowledging
+    /// the alarm if its "Silence" action is clicked.
+    fn spawn_action_notification(&self, alarm_id: u64, title: &str, body: &str) {
+        let running = Arc::clone(&self.running);
+        let queue = Arc::clone(&self.queue);
+        let history = self.history.clone();
+        let (title, body) = (title.to_string(), body.to_string());
+
+        tokio::spawn(async move {
+            // Only notify-send offers a "Silence" button
+            if cfg!(any(target_os = "macos", windows)) {
+                if let Err(e) = Self::send_alert_notification(&title, &body).await {
+                    error!("Failed to send notification: {}", e);
+                }
+                return;
+            }
+
+            let child = Command::new("notify-send")
+                .args(Self::build_action_args(&title, &body))
+                .stdout(std::process::Stdio::piped())
+                .stderr(std::process::Stdio::null())
+                // Close the notification process once the alarm is over
+                .kill_on_drop(true)
+                .spawn();
+            let child = match child {
+                Ok(child) => child,
+                Err(e) => {
+                    error!("Failed to send notification: {}", e);
+                    return;
+                }
+            };
+
+            let active = || {
+                running.load(Ordering::SeqCst) && queue.lock().unwrap_or_else(|e| e.into_inner()).contains(alarm_id)
+            };
+            let stopped = async {
+                while active() {
+                    clock::sleep(Duration::from_millis(100)).await;
+                }
+            };
+            tokio::select! {
+                output = child.wait_with_output() => match output {
+                    Ok(output) if output.status.success() => {
+                        if String::from_utf8_lossy(&output.stdout).trim() == ACK_ACTION {
+                            acknowledge_alarm(&running, &queue, history.as_deref(), Some(alarm_id), AckSource::Notification);
+                        }
+                    }
+                    _ => {
+                        // Older notify-send without --action support
+                        debug!("Notification actions unsupported, sending a plain notification");
+                        if let Err(e) = Self::send_alert_notification(&title, &body).await {
+                            error!("Failed to send notification: {}", e);
+                        }
+                    }
+                },
+                _ = stopped => {}
+            }
+        });
+    }
+
+    /// Build the mpv command arguments for the first sound (for testing).
+    #[cfg(test)]
     pub fn build_sound_args(&self) -> Vec<String> {
-        vec![
-            "--no-video".to_string(),
-            "--really-quiet".to_string(),
-            self.sound_path.clone(),
-        ]
+        let first = self.playlist.sounds().first().map(String::as_str).unwrap_or_default();
+        audio::mpv_args(first, self.alarm_volume)
+    }
+
+    /// Start the alarm loop for a rename of `channel_id`. Sends notification once,
+    /// then loops audio every 3 seconds. This runs until `stop()` is called. The
+    /// notification shows `previous` and the changed segment when the old name is
+    /// known, `changed_by` when the rename was attributed, and the trigger rule
+    /// the new name matched.
+    ///
+    /// The compound rule only holds renames of the primary channel.
+    #[cfg(test)]
+    pub async fn start_alarm(
+        &self,
+        channel_id: &str,
+        previous: Option<&str>,
+        channel_name: &str,
+        changed_by: Option<&str>,
+        trigger: Option<TriggerMatch>,
+    ) {
+        if let Some(alert) = self.rename_alert(channel_id, previous, channel_name, changed_by, trigger) {
+            self.raise(&alert, Some(channel_id), None).await;
+        }
+    }
+
+    /// The alert for a rename that passed the trigger rule, or `None` while
+    /// the compound rule waits for its message.
+    fn rename_alert(
+        &self,
+        channel_id: &str,
+        previous: Option<&str>,
+        channel_name: &str,
+        changed_by: Option<&str>,
+        trigger: Option<TriggerMatch>,
+    ) -> Option<Alert> {
+        let compound = match self.channel_id.as_deref() {
+            Some(primary) if primary != channel_id => None,
+            _ => self.compound.as_ref(),
+        };
+        match compound {
+            Some(compound) => {
+                let alert = compound
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .on_rename(clock::now(), previous, channel_name, changed_by);
+                if alert.is_none() {
+                    info!("[COMPOUND] Rename to {} is waiting for a matching message", channel_name);
+                }
+                alert
+            }
+            None => Some(Alert::ChannelOpen {
+                name: channel_name.to_string(),
+                previous: previous.map(str::to_string),
+                changed_by: changed_by.map(str::to_string),
+                trigger,
+            }),
+        }
+    }
+
+    /// The compound alert completed by a message, if any.
+    fn message_alert(&self, content: &str) -> Option<Alert> {
+        let alert = self
+            .compound
+            .as_ref()?
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .on_message(clock::now(), content);
+        if alert.is_some() {
+            info!("[COMPOUND] Keyword message arrived, rule matched");
+        }
+        alert
+    }
+
+    /// Alarm on the monitor's events until the channel closes.
+    ///
+    /// Rule state is updated in event order, while each alarm runs in its
+    /// own task so a ringing alarm never holds back the next event.
+    pub async fn listen(self: Arc<Self>, mut events: broadcast::Receiver<MonitorEvent>) {
+        loop {
+            match events.recv().await {
+                Ok(event) => self.handle_event(event),
+                Err(RecvError::Lagged(missed)) => warn!("[ALARM] Fell behind and missed {} events", missed),
+                Err(RecvError::Closed) => return,
+            }
+        }
     }
 
-    /// Start the alarm loop. Sends notification once, then loops audio every 3 seconds.
+    /// Record an event and start its alarm, if it has one.
+    fn handle_event(self: &Arc<Self>, event: MonitorEvent) {
+        let mut seen_by = None;
+        let (alert, channel_id) = match event {
+            MonitorEvent::NameChanged {
+                channel_id,
+                old_name,
+                new_name,
+                changed_by,
+                source,
+                alarm,
+                trigger,
+            } => {
+                self.record(HistoryEvent::Change {
+                    at: chrono::Utc::now(),
+                    channel_id: channel_id.clone(),
+                    source: source.clone(),
+                    old_name: old_name.clone(),
+                    new_name: new_name.clone(),
+                    alarm,
+                });
+                self.count_rename(alarm);
+                if !alarm {
+                    return;
+                }
+                seen_by = Some(source);
+                let alert = self.rename_alert(&channel_id, old_name.as_deref(), &new_name, changed_by.as_deref(), trigger);
+                (alert, Some(channel_id))
+            }
+            MonitorEvent::MessageReceived { channel_id, content, .. } => {
+                // The compound rule only watches the primary channel
+                if self.channel_id.as_deref().is_some_and(|primary| primary != channel_id) {
+                    return;
+                }
+                (self.message_alert(&content), self.channel_id.clone())
+            }
+            MonitorEvent::MessageMatched {
+                channel_id,
+                channel_name,
+                author,
+                content,
+                trigger,
+            } => {
+                let alert = Alert::MessagePosted {
+                    channel: channel_name,
+                    author,
+                    content,
+                    trigger,
+                };
+                (Some(alert), Some(channel_id))
+            }
+            MonitorEvent::Alert(alert) => (Some(alert), None),
+            _ => return,
+        };
+        if let Some(alert) = alert {
+            let notifier = Arc::clone(self);
+            tokio::spawn(async move { notifier.raise(&alert, channel_id.as_deref(), seen_by.as_deref()).await });
+        }
+    }
+
+    /// Start the alarm loop for an alert, localized to the configured language.
     /// This runs until `stop()` is called.
-    pub async fn start_alarm(&self, channel_name: &str) {
+    ///
+    /// Once the alarm budget for the alert's source is spent, the alert is sent
+    /// as a silent notification instead and returns immediately. Alerts for a
+    /// muted channel are only recorded in the history.
+    #[cfg(test)]
+    pub async fn start_alert(&self, alert: &Alert) {
+        self.raise(alert, None, None).await;
+    }
+
+    /// Start the alarm for an alert, with the title and sound overrides of
+    /// `channel_id` when it is a monitored channel's alert.
+    ///
+    /// Budgets, cooldowns and duplicate checks for such alerts are kept per
+    /// channel (`channel:<id>`). Unless muted, the alert is announced as
+    /// [`MonitorEvent::AlarmRaised`] with `seen_by` as its source.
+    async fn raise(&self, alert: &Alert, channel_id: Option<&str>, seen_by: Option<&str>) {
+        let (mut title, body) = self.render(alert);
+        if let Some(channel) = self.muted_channel(alert, channel_id) {
+            info!("[MUTE] {} is muted, not alarming: {}: {}", channel, title, body);
+            self.record(HistoryEvent::Muted {
+                at: chrono::Utc::now(),
+                channel,
+                title,
+                body,
+            });
+            return;
+        }
+        if let Some(events) = &self.events {
+            let raised = MonitorEvent::AlarmRaised {
+                channel_id: channel_id.map(str::to_string),
+                alert: alert.clone(),
+                source: seen_by.map(str::to_string),
+            };
+            events::emit(events, raised);
+        }
+        let mut source = alert.source();
+        let mut sounds = &self.playlist;
+        if let Some(id) = channel_id {
+            source = format!("{}:{}", source, id);
+            if let Some(overrides) = self.channel_alarms.get(id) {
+                if let Some(custom) = &overrides.title {
+                    title = custom.clone();
+                }
+                if let Some(playlist) = &overrides.playlist {
+                    sounds = playlist;
+                }
+            }
+        }
+        let remote = self.sinks_for(channel_id);
+
+        // Check and queue under one lock, so a burst of repeats rings once
+        let queued = {
+            let now = clock::now();
+            let mut queue = self.queue.lock().unwrap_or_else(|e| e.into_inner());
+            let mut budget = self.budget.lock().unwrap_or_else(|e| e.into_inner());
+            if queue.has_source(&source) {
+                Err(format!("{} is already ringing", source))
+            } else if budget.cooling_down(&source, now) {
+                Err(format!("{} is cooling down", source))
+            } else if !budget.try_spend(&source, now) {
+                Err(format!("Budget for {} exhausted", source))
+            } else {
+                let alarm_id = self.next_alarm_id();
+                let ahead = queue.push(QueuedAlarm {
+                    id: alarm_id,
+                    priority: alarm_queue::priority_for(&self.priorities, &source),
+                    source,
+                    title: title.clone(),
+                    body: body.clone(),
+                    escalated: false,
+                });
+                Ok((alarm_id, ahead))
+            }
+        };
+        match queued {
+            Ok((alarm_id, ahead)) => self.run_alarm(alarm_id, ahead, &title, &body, sounds, remote).await,
+            Err(reason) => {
+                info!("[ALARM] {}, sending silently: {}: {}", reason, title, body);
+                self.send_silent(&title, &body, remote).await;
+            }
+        }
+    }
+
+    /// Notify without sound or a ringing alarm.
+    async fn send_silent(&self, title: &str, body: &str, remote: &[Arc<dyn NotificationSink>]) {
+        self.popup(None, title, body, remote).await;
+    }
+
+    /// Tally a rename for the burst's summary, scheduling the summary once the
+    /// burst has more than one.
+    fn count_rename(self: &Arc<Self>, matched: bool) {
+        let Some(group) = &self.popups else {
+            return;
+        };
+        let flush_after = group.lock().unwrap_or_else(|e| e.into_inner()).count_rename(clock::now(), matched);
+        if let Some(delay) = flush_after {
+            let notifier = Arc::clone(self);
+            tokio::spawn(async move {
+                clock::sleep(delay).await;
+                notifier.flush_popups().await;
+            });
+        }
+    }
+
+    /// Show an alert's desktop popup and send it to the `remote` sinks, or hold
+    /// it for a grouped summary if a burst is under way. Each sink's summary
+    /// only lists the alerts that would have been sent to it.
+    ///
+    /// A ringing alarm (`alarm_id`) always shows its popup right away, as it
+    /// carries the "Silence" action; only its remote messages are grouped.
+    async fn popup(&self, alarm_id: Option<u64>, title: &str, body: &str, remote: &[Arc<dyn NotificationSink>]) {
+        if let Some(id) = alarm_id {
+            self.spawn_action_notification(id, title, body);
+        }
+        let held = HeldPopup {
+            summary: format!("{}: {}", title, body),
+            shown: alarm_id.is_some(),
+            remote: remote.to_vec(),
+        };
+        let decision = match &self.popups {
+            Some(group) => group.lock().unwrap_or_else(|e| e.into_inner()).offer(clock::now(), held),
+            None => Popup::Now,
+        };
+
+        match decision {
+            Popup::Now => {
+                if alarm_id.is_none() {
+                    if let Err(e) = Self::send_alert_notification(title, body).await {
+                        error!("Failed to send notification: {}", e);
+                    }
+                }
+                sinks::send_all(remote, title, body).await;
+            }
+            Popup::Grouped { flush_after: Some(delay) } => {
+                clock::sleep(delay).await;
+                self.flush_popups().await;
+            }
+            // Another alert or rename in this burst sends the summary
+            Popup::Grouped { flush_after: None } => {}
+        }
+    }
+
+    /// Send the summary of the burst under way: the desktop gets the alerts it
+    /// has not shown yet, each sink those meant for it, and all of them how
+    /// many channels were renamed.
+    async fn flush_popups(&self) {
+        let Burst { held, renames } = match &self.popups {
+            Some(group) => group.lock().unwrap_or_else(|e| e.into_inner()).flush(clock::now()),
+            None => return,
+        };
+        info!(
+            "[ALARM] Grouping {} alerts and {} renames ({} matched) into one notification",
+            held.len(),
+            renames.changed,
+            renames.matched
+        );
+        let RenameTally { changed, matched } = renames;
+        let unshown: Vec<String> = held.iter().filter(|h| !h.shown).map(|h| h.summary.clone()).collect();
+        if !unshown.is_empty() || changed > 1 {
+            let (title, body) = self.render(&Alert::Grouped {
+                alerts: unshown,
+                changed,
+                matched,
+            });
+            if let Err(e) = Self::send_alert_notification(&title, &body).await {
+                error!("Failed to send notification: {}", e);
+            }
+        }
+        let mut groups = group_by_sink(&held);
+        if changed > 1 {
+            for sink in &self.sinks {
+                if !groups.iter().any(|(known, _)| Arc::ptr_eq(known, sink)) {
+                    groups.push((Arc::clone(sink), Vec::new()));
+                }
+            }
+        }
+        join_all(groups.into_iter().map(|(sink, alerts)| async move {
+            let (title, body) = self.render(&Alert::Grouped {
+                alerts,
+                changed,
+                matched,
+            });
+            sinks::send_all(std::slice::from_ref(&sink), &title, &body).await;
+        }))
+        .await;
+    }
+
+    /// Unique, increasing alarm ID based on the current time in milliseconds.
+    fn next_alarm_id(&self) -> u64 {
+        let now = chrono::Utc::now().timestamp_millis() as u64;
+        let last = self.last_alarm_id.fetch_max(now, Ordering::SeqCst);
+        if now > last {
+            now
+        } else {
+            self.last_alarm_id.fetch_add(1, Ordering::SeqCst) + 1
+        }
+    }
+
+    /// Check whether alarm `id` is still active, and whether it holds the audio device.
+    fn alarm_state(&self, id: u64) -> (bool, bool) {
+        let queue = self.queue.lock().unwrap_or_else(|e| e.into_inner());
+        (queue.contains(id), queue.head().is_some_and(|a| a.id == id))
+    }
+
+    /// Notify once about queued alarm `alarm_id`, then loop the alarm sound
+    /// until `stop()` is called, the alarm is acknowledged, or the alarm
+    /// timeout elapses.
+    ///
+    /// While a higher-priority alarm is ringing (`ahead` of it) this one stays
+    /// queued and silent.
+    async fn run_alarm(
+        &self,
+        alarm_id: u64,
+        ahead: usize,
+        title: &str,
+        body: &str,
+        sounds: &Playlist,
+        remote: &[Arc<dyn NotificationSink>],
+    ) {
+        self.record(HistoryEvent::Alarm {
+            id: alarm_id,
+            at: chrono::Utc::now(),
+            title: title.to_string(),
+            body: body.to_string(),
+        });
+
+        if let Some(health) = &self.health {
+            health.record_alarm();
+        }
+
         // Set running flag
         self.running.store(true, Ordering::SeqCst);
-
-        // Send notification once at the start
-        if let Err(e) = Self::send_notification(channel_name).await {
-            eprintln!("Failed to send notification: {}", e);
+        if ahead == 0 {
+            info!("[ALARM] {}: {}", title, body);
+        } else {
+            info!("[ALARM] Queued behind {} higher-priority alarm(s): {}: {}", ahead, title, body);
         }
 
-        // Loop playing the sound until stopped
+        // Notify once while the sound starts
+        tokio::join!(
+            self.popup(Some(alarm_id), title, body, remote),
+            self.ring(alarm_id, sounds, title, body, remote)
+        );
+    }
+
+    /// Loop the alarm sound while alarm `alarm_id` holds the audio device,
+    /// until it is stopped, acknowledged, or times out. Escalates once when
+    /// it rings unacknowledged for the escalation delay.
+    async fn ring(&self, alarm_id: u64, sounds: &Playlist, title: &str, body: &str, remote: &[Arc<dyn NotificationSink>]) {
+        let started = clock::now();
+        let mut sound = sounds.next().to_string();
+        let mut first = true;
+        let mut volume = self.alarm_volume;
+        let mut escalated = false;
         while self.running.load(Ordering::SeqCst) {
-            if let Err(e) = self.play_sound().await {
-                eprintln!("Failed to play sound: {}", e);
+            let (active, ringing) = self.alarm_state(alarm_id);
+            if !active {
+                break;
+            }
+            if self.alarm_timeout.is_some_and(|timeout| started.elapsed() >= timeout) {
+                info!("[ALARM] Not acknowledged within {}s, silencing", started.elapsed().as_secs());
+                acknowledge_alarm(
+                    &self.running,
+                    &self.queue,
+                    self.history.as_deref(),
+                    Some(alarm_id),
+                    AckSource::Timeout,
+                );
+                break;
+            }
+
+            if let Some(escalation) = &self.escalation {
+                if ringing && !escalated && started.elapsed() >= escalation.after {
+                    escalated = true;
+                    volume = escalation.volume.or(self.alarm_volume);
+                    self.escalate(alarm_id, title, body, started.elapsed(), remote).await;
+                }
+            }
+
+            if ringing {
+                if self.sound_rotation == SoundRotation::Repeat && !first {
+                    sound = sounds.next().to_string();
+                }
+                first = false;
+                // Cut the sound short once the alarm is silenced or preempted
+                tokio::select! {
+                    result = audio::play(self.audio_backend, &sound, volume) => {
+                        if let Err(e) = result {
+                            error!("Failed to play sound {}: {}", sound, e);
+                        }
+                    }
+                    _ = self.until_silenced(alarm_id) => {}
+                }
             }
 
             // Wait 3 seconds before playing again, but check running flag more frequently
             for _ in 0..30 {
-                if !self.running.load(Ordering::SeqCst) {
+                if !self.running.load(Ordering::SeqCst) || !self.alarm_state(alarm_id).0 {
                     break;
                 }
-                tokio::time::sleep(Duration::from_millis(100)).await;
+                clock::sleep(Duration::from_millis(100)).await;
+            }
+        }
+    }
+
+    /// Re-notify about alarm `alarm_id`, unacknowledged for `elapsed`.
+    async fn escalate(
+        &self,
+        alarm_id: u64,
+        title: &str,
+        body: &str,
+        elapsed: Duration,
+        remote: &[Arc<dyn NotificationSink>],
+    ) {
+        self.queue.lock().unwrap_or_else(|e| e.into_inner()).escalate(alarm_id);
+        let (title, body) = self.render(&Alert::Unacknowledged {
+            title: title.to_string(),
+            body: body.to_string(),
+            minutes: elapsed.as_secs() / 60,
+        });
+        warn!("[ALARM] Escalating: {}: {}", title, body);
+        self.spawn_action_notification(alarm_id, &title, &body);
+        sinks::send_all(remote, &title, &body).await;
+    }
+
+    /// Wait until alarm `alarm_id` stops ringing.
+    async fn until_silenced(&self, alarm_id: u64) {
+        while self.running.load(Ordering::SeqCst) && self.alarm_state(alarm_id).1 {
+            clock::sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    /// Append an event to the history, if one is attached.
+    pub fn record(&self, event: HistoryEvent) {
+        if let Some(history) = &self.history {
+            if let Err(e) = history.append(&event) {
+                warn!("Failed to record event history: {}", e);
             }
         }
     }
 
-    /// Stop the alarm loop.
+    /// Silence the ringing alarm and record how it was acknowledged.
+    ///
+    /// The next queued alarm, if any, starts ringing. Returns false if no
+    /// alarm was ringing.
+    pub fn acknowledge(&self, via: AckSource) -> bool {
+        acknowledge_alarm(&self.running, &self.queue, self.history.as_deref(), None, via)
+    }
+
+    /// Stop all alarms without acknowledging them (e.g. on shutdown).
     pub fn stop(&self) {
         self.running.store(false, Ordering::SeqCst);
+        self.queue.lock().unwrap_or_else(|e| e.into_inner()).clear();
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::NtfySettings;
 
     #[test]
     fn test_notification_args_construction() {
@@ -123,6 +1001,157 @@ mod tests {
         assert_eq!(args[3], "Channel is now: voice-chat-123");
     }
 
+    #[test]
+    fn test_alert_args_construction() {
+        let args = Notifier::build_alert_args("STAGE LIVE", "Stage is live: drop Q&A");
+
+        assert_eq!(args.len(), 4);
+        assert_eq!(args[2], "STAGE LIVE");
+        assert_eq!(args[3], "Stage is live: drop Q&A");
+    }
+
+    #[test]
+    fn test_osascript_args_pass_text_unquoted() {
+        let args = Notifier::build_osascript_args("CHANNEL OPEN", "Channel is now: \"open\"");
+
+        assert_eq!(args[1], "on run argv");
+        assert_eq!(&args[6..], ["CHANNEL OPEN", "Channel is now: \"open\""]);
+    }
+
+    #[test]
+    fn test_unconfigured_remote_backends() {
+        let notifier = Notifier::new("/test/boom.mp3".to_string());
+
+        assert!(notifier.sink("telegram").is_none());
+        assert!(notifier.sink("webhook").is_none());
+        assert!(notifier.sinks_for(Some("123")).is_empty());
+    }
+
+    #[test]
+    fn test_channel_sinks_narrow_remote_alerts() {
+        let targets = [
+            MonitorTarget {
+                channel_id: "1".to_string(),
+                sinks: Some(vec!["ntfy".to_string()]),
+                ..Default::default()
+            },
+            MonitorTarget {
+                channel_id: "2".to_string(),
+                sinks: Some(Vec::new()),
+                ..Default::default()
+            },
+            MonitorTarget::new("3"),
+        ];
+        let notifier = Notifier::from_settings(&NotificationSettings {
+            webhook_url: Some("https://example.com/api/webhooks/1/abc".to_string()),
+            ntfy: Some(NtfySettings {
+                url: "https://ntfy.sh/alarms".to_string(),
+                token: None,
+            }),
+            ..Default::default()
+        })
+        .with_targets(&targets);
+        let names = |channel_id| notifier.sinks_for(channel_id).iter().map(|s| s.name()).collect::<Vec<_>>();
+
+        assert!(notifier.sink("ntfy").is_some());
+        assert_eq!(names(Some("1")), ["ntfy"]);
+        assert!(names(Some("2")).is_empty());
+        assert_eq!(names(Some("3")), ["webhook", "ntfy"]);
+        assert_eq!(names(None), ["webhook", "ntfy"]);
+    }
+
+    /// A sink that keeps what it was sent.
+    struct RecordingSink(Mutex<Vec<String>>);
+
+    #[async_trait::async_trait]
+    impl NotificationSink for RecordingSink {
+        fn name(&self) -> &'static str {
+            "recording"
+        }
+
+        async fn send(&self, _title: &str, body: &str) -> Result<(), String> {
+            self.0.lock().unwrap().push(body.to_string());
+            Ok(())
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_grouped_summaries_respect_channel_sinks() {
+        let mut notifier = Notifier::from_settings(&NotificationSettings {
+            sound_path: "/nonexistent/path.mp3".to_string(),
+            group_window: Some(Duration::from_secs(5)),
+            ..Default::default()
+        });
+        let everyone = Arc::new(RecordingSink(Mutex::default()));
+        let private = Arc::new(RecordingSink(Mutex::default()));
+        let all: Vec<Arc<dyn NotificationSink>> = vec![everyone.clone(), private.clone()];
+        let narrowed: Vec<Arc<dyn NotificationSink>> = vec![everyone.clone()];
+        notifier.sinks = all.clone();
+
+        notifier.popup(None, "A", "first", &all).await;
+        tokio::join!(
+            notifier.popup(None, "B", "public", &narrowed),
+            notifier.popup(None, "C", "everywhere", &all),
+        );
+
+        let everyone = everyone.0.lock().unwrap().clone();
+        assert_eq!(everyone.len(), 2);
+        assert_eq!(everyone[0], "first");
+        assert!(everyone[1].contains("B: public") && everyone[1].contains("C: everywhere"));
+        let private = private.0.lock().unwrap().clone();
+        assert_eq!(private.len(), 2);
+        assert!(!private[1].contains("B: public") && private[1].contains("C: everywhere"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rename_burst_is_summarized_with_unmatched_renames() {
+        let mut notifier = Notifier::from_settings(&NotificationSettings {
+            sound_path: "/nonexistent/path.mp3".to_string(),
+            group_window: Some(Duration::from_secs(5)),
+            ..Default::default()
+        });
+        let sink = Arc::new(RecordingSink(Mutex::default()));
+        notifier.sinks = vec![sink.clone()];
+        let notifier = Arc::new(notifier);
+
+        for channel in 1..=7 {
+            notifier.handle_event(MonitorEvent::NameChanged {
+                channel_id: channel.to_string(),
+                old_name: Some("order-❌".to_string()),
+                new_name: if channel == 4 { "order-✅" } else { "order-🔧" }.to_string(),
+                changed_by: None,
+                source: "WS".to_string(),
+                alarm: channel == 4,
+                trigger: None,
+            });
+        }
+        clock::sleep(Duration::from_secs(6)).await;
+        notifier.stop();
+
+        let sent = sink.0.lock().unwrap().clone();
+        assert_eq!(sent.len(), 2, "{:?}", sent);
+        assert!(sent[0].contains("order-✅"));
+        assert_eq!(sent[1], "7 channels changed, 1 matched open rules");
+    }
+
+    #[test]
+    fn test_render_uses_configured_language() {
+        let notifier = Notifier::from_settings(&NotificationSettings {
+            sound_path: "/test/boom.mp3".to_string(),
+            language: Language::Es,
+            ..Default::default()
+        });
+        let (title, body) = notifier.render(&Alert::ChannelOpen {
+            name: "abierto".to_string(),
+            previous: None,
+            changed_by: None,
+            trigger: None,
+        });
+
+        assert_eq!(title, "CANAL ABIERTO");
+        assert_eq!(body, "El canal ahora es: abierto");
+    }
+
     #[test]
     fn test_sound_args_construction() {
         let notifier = Notifier::new("/path/to/sound.mp3".to_string());
@@ -134,6 +1163,14 @@ mod tests {
         assert_eq!(args[2], "/path/to/sound.mp3");
     }
 
+    #[test]
+    fn test_notifier_resolves_sound_list() {
+        let notifier = Notifier::new("/a.mp3,/b.mp3".to_string());
+
+        assert_eq!(notifier.sounds(), ["/a.mp3".to_string(), "/b.mp3".to_string()]);
+        assert_eq!(notifier.build_sound_args()[2], "/a.mp3");
+    }
+
     #[test]
     fn test_notifier_creation() {
         let notifier = Notifier::new("/test/path/boom.mp3".to_string());
@@ -183,7 +1220,7 @@ mod tests {
 
         // Start alarm in background
         let handle = tokio::spawn(async move {
-            notifier_clone.start_alarm("test-channel").await;
+            notifier_clone.start_alarm("100", None, "test-channel", None, None).await;
         });
 
         // Give it a moment to start
@@ -202,4 +1239,318 @@ mod tests {
         // Verify it's stopped
         assert!(!notifier.is_running());
     }
+
+    #[tokio::test]
+    async fn test_acknowledge_records_history() {
+        let path = std::env::temp_dir().join(format!("ollie-notifier-ack-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let notifier = Arc::new(
+            Notifier::new("/nonexistent/path.mp3".to_string()).with_history(Arc::new(History::new(path.clone()))),
+        );
+
+        // Nothing is ringing yet
+        assert!(!notifier.acknowledge(AckSource::Cli));
+
+        let notifier_clone = Arc::clone(&notifier);
+        let handle = tokio::spawn(async move {
+            notifier_clone.start_alarm("100", None, "test-channel", None, None).await;
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert!(notifier.acknowledge(AckSource::Cli));
+        let result = tokio::time::timeout(Duration::from_secs(1), handle).await;
+        assert!(result.is_ok(), "Alarm should stop once acknowledged");
+
+        let events = crate::monitor::history::read_events(&path).unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(events[1], HistoryEvent::Ack { via: AckSource::Cli, .. }));
+        assert!(crate::monitor::history::unacknowledged(&events).is_empty());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_muted_channel_is_recorded_without_alarming() {
+        let path = std::env::temp_dir().join(format!("ollie-notifier-mute-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let notifier = Notifier::new("/nonexistent/path.mp3".to_string())
+            .with_history(Arc::new(History::new(path.clone())))
+            .with_channel_id("100");
+
+        assert!(notifier.mute("100"));
+        assert!(!notifier.mute("100"));
+        assert_eq!(notifier.muted(), vec!["100".to_string()]);
+
+        // Returns straight away instead of ringing
+        tokio::time::timeout(Duration::from_secs(1), notifier.start_alarm("100", None, "order-✅", None, None))
+            .await
+            .expect("Muted alarm should not ring");
+        assert!(!notifier.is_running());
+
+        let events = crate::monitor::history::read_events(&path).unwrap();
+        assert!(matches!(&events[..], [HistoryEvent::Muted { channel, .. }] if channel == "100"));
+
+        // Other channels still alarm
+        let alert = Alert::UserStreamingInVoice {
+            user_id: "1".to_string(),
+            channel_id: "200".to_string(),
+        };
+        assert_eq!(notifier.muted_channel(&alert, None), None);
+        assert!(notifier.unmute("100"));
+        assert!(!notifier.unmute("100"));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    fn renamed(name: &str) -> MonitorEvent {
+        MonitorEvent::NameChanged {
+            channel_id: "100".to_string(),
+            old_name: Some("order-❌".to_string()),
+            new_name: name.to_string(),
+            changed_by: None,
+            source: "WS".to_string(),
+            alarm: true,
+            trigger: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_muted_alarm_is_not_announced() {
+        let events = crate::events::channel();
+        let mut raised = events.subscribe();
+        let notifier = Arc::new(
+            Notifier::new("/nonexistent/path.mp3".to_string())
+                .with_channel_id("100")
+                .with_events(events.clone()),
+        );
+        notifier.mute("100");
+
+        let alert = Alert::ChannelOpen {
+            name: "order-✅".to_string(),
+            previous: None,
+            changed_by: None,
+            trigger: None,
+        };
+        notifier.raise(&alert, Some("100"), Some("WS")).await;
+        assert!(!notifier.is_running());
+        assert!(raised.try_recv().is_err(), "A muted channel's alarm should not reach the hooks");
+
+        notifier.unmute("100");
+        let ringing = {
+            let notifier = Arc::clone(&notifier);
+            tokio::spawn(async move { notifier.raise(&alert, Some("100"), Some("WS")).await })
+        };
+        let event = tokio::time::timeout(Duration::from_secs(1), raised.recv()).await.unwrap().unwrap();
+        assert!(matches!(
+            event,
+            MonitorEvent::AlarmRaised { channel_id: Some(id), source: Some(source), .. } if id == "100" && source == "WS"
+        ));
+        notifier.stop();
+        assert!(tokio::time::timeout(Duration::from_secs(1), ringing).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_compound_rename_is_announced_with_its_message() {
+        let events = crate::events::channel();
+        let mut raised = events.subscribe();
+        let notifier = Arc::new(
+            Notifier::new("/nonexistent/path.mp3".to_string())
+                .with_channel_id("100")
+                .with_compound(Some(CompoundRule {
+                    name_contains: Some("✅".to_string()),
+                    keyword: "orders open".to_string(),
+                    window: Duration::from_secs(60),
+                }))
+                .with_events(events.clone()),
+        );
+
+        // The rename waits for its message, so nothing is raised yet
+        notifier.handle_event(renamed("order-✅"));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(raised.try_recv().is_err(), "A pending compound rename should not reach the hooks");
+        assert!(!notifier.is_running());
+
+        notifier.handle_event(MonitorEvent::MessageReceived {
+            channel_id: "100".to_string(),
+            author: "shopbot".to_string(),
+            content: "Orders open now".to_string(),
+        });
+        let event = tokio::time::timeout(Duration::from_secs(1), raised.recv()).await.unwrap().unwrap();
+        assert!(matches!(
+            event,
+            MonitorEvent::AlarmRaised {
+                alert: Alert::ChannelOpenWithMessage { ref name, .. },
+                ..
+            } if name == "order-✅"
+        ));
+        notifier.stop();
+    }
+
+    #[tokio::test]
+    async fn test_channel_title_override() {
+        let targets = [
+            MonitorTarget::new("100"),
+            MonitorTarget {
+                channel_id: "200".to_string(),
+                title: Some("DROP OPEN".to_string()),
+                sound_path: Some("/nonexistent/drop.mp3".to_string()),
+                ..Default::default()
+            },
+        ];
+        let notifier = Arc::new(Notifier::new("/nonexistent/path.mp3".to_string()).with_targets(&targets));
+
+        let alarms: Vec<_> = [("100", "orders-✅"), ("200", "drops-✅")]
+            .into_iter()
+            .map(|(id, name)| {
+                let notifier = Arc::clone(&notifier);
+                tokio::spawn(async move { notifier.start_alarm(id, None, name, None, None).await })
+            })
+            .collect();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let mut active = notifier.active_alarms();
+        active.sort();
+        assert_eq!(
+            active,
+            vec!["CHANNEL OPEN: Channel is now: orders-✅".to_string(), "DROP OPEN: Channel is now: drops-✅".to_string()]
+        );
+
+        notifier.stop();
+        for alarm in alarms {
+            assert!(tokio::time::timeout(Duration::from_secs(1), alarm).await.is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_higher_priority_alarm_rings_first() {
+        let notifier = Arc::new(Notifier::from_settings(&NotificationSettings {
+            sound_path: "/nonexistent/path.mp3".to_string(),
+            alarm_priorities: vec![("stage".to_string(), 10)],
+            ..Default::default()
+        }));
+
+        let low = Arc::clone(&notifier);
+        let low = tokio::spawn(async move { low.start_alarm("100", None, "open", None, None).await });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let high = Arc::clone(&notifier);
+        let high = tokio::spawn(async move {
+            high.start_alert(&Alert::StageLive {
+                topic: "drop".to_string(),
+            })
+            .await
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(
+            notifier.active_alarms(),
+            vec!["STAGE LIVE: Stage is live: drop".to_string(), "CHANNEL OPEN: Channel is now: open".to_string()]
+        );
+
+        // Acknowledging the stage alarm hands the audio device to the channel alarm
+        assert!(notifier.acknowledge(AckSource::Cli));
+        assert!(tokio::time::timeout(Duration::from_secs(1), high).await.is_ok());
+        assert!(notifier.is_running());
+        assert_eq!(notifier.active_alarms().len(), 1);
+
+        assert!(notifier.acknowledge(AckSource::Cli));
+        assert!(tokio::time::timeout(Duration::from_secs(1), low).await.is_ok());
+        assert!(!notifier.is_running());
+    }
+
+    #[tokio::test]
+    async fn test_repeat_and_cooldown_alert_silently() {
+        let notifier = Arc::new(Notifier::from_settings(&NotificationSettings {
+            sound_path: "/nonexistent/path.mp3".to_string(),
+            alarm_cooldown: Some(Duration::from_secs(120)),
+            ..Default::default()
+        }));
+
+        let first = Arc::clone(&notifier);
+        let first = tokio::spawn(async move { first.start_alarm("100", None, "order-✅", None, None).await });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // Renamed back and forth while ringing: shown, but not queued as a second alarm
+        let repeat = tokio::time::timeout(Duration::from_secs(1), notifier.start_alarm("100", None, "order-❌", None, None));
+        assert!(repeat.await.is_ok(), "A repeat of a ringing source should not ring");
+        assert_eq!(notifier.active_alarms(), vec!["CHANNEL OPEN: Channel is now: order-✅".to_string()]);
+
+        assert!(notifier.acknowledge(AckSource::Cli));
+        assert!(tokio::time::timeout(Duration::from_secs(1), first).await.is_ok());
+        // Still within the cooldown after being acknowledged
+        let again = tokio::time::timeout(Duration::from_secs(1), notifier.start_alarm("100", None, "order-✅", None, None));
+        assert!(again.await.is_ok(), "A cooling-down source should not ring");
+        assert!(!notifier.is_running());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_unacknowledged_alarm_escalates_once() {
+        let notifier = Arc::new(Notifier::from_settings(&NotificationSettings {
+            sound_path: "/nonexistent/path.mp3".to_string(),
+            escalation: Some(EscalationSettings {
+                after: Duration::from_secs(300),
+                volume: Some(100),
+            }),
+            ..Default::default()
+        }));
+        let escalated = |notifier: &Notifier| {
+            notifier.queue.lock().unwrap().iter().map(|a| a.escalated).collect::<Vec<_>>()
+        };
+
+        let alarm = {
+            let notifier = Arc::clone(&notifier);
+            tokio::spawn(async move { notifier.start_alarm("100", None, "test-channel", None, None).await })
+        };
+        clock::sleep(Duration::from_secs(290)).await;
+        assert_eq!(escalated(&notifier), [false]);
+        clock::sleep(Duration::from_secs(15)).await;
+        assert_eq!(escalated(&notifier), [true]);
+
+        notifier.stop();
+        alarm.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_budget_sends_silently() {
+        let notifier = Notifier::from_settings(&NotificationSettings {
+            sound_path: "/nonexistent/path.mp3".to_string(),
+            alarm_channel_limit: Some(0),
+            ..Default::default()
+        });
+
+        // Returns without entering the alarm loop
+        let result = tokio::time::timeout(Duration::from_secs(1), notifier.start_alarm("100", None, "test-channel", None, None)).await;
+        assert!(result.is_ok(), "Over-budget alerts should not ring");
+        assert!(!notifier.is_running());
+    }
+
+    #[tokio::test]
+    async fn test_alarm_times_out() {
+        let notifier = Notifier::from_settings(&NotificationSettings {
+            sound_path: "/nonexistent/path.mp3".to_string(),
+            alarm_timeout: Some(Duration::ZERO),
+            ..Default::default()
+        });
+
+        let result = tokio::time::timeout(Duration::from_secs(1), notifier.start_alarm("100", None, "test-channel", None, None)).await;
+        assert!(result.is_ok(), "Alarm should silence itself after the timeout");
+        assert!(!notifier.is_running());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_alarm_rings_until_timeout_on_paused_clock() {
+        let notifier = Arc::new(Notifier::from_settings(&NotificationSettings {
+            sound_path: "/nonexistent/path.mp3".to_string(),
+            alarm_timeout: Some(Duration::from_secs(600)),
+            ..Default::default()
+        }));
+
+        let alarm = {
+            let notifier = Arc::clone(&notifier);
+            tokio::spawn(async move { notifier.start_alarm("100", None, "test-channel", None, None).await })
+        };
+        clock::sleep(Duration::from_secs(599)).await;
+        assert!(notifier.is_running());
+
+        clock::sleep(Duration::from_secs(5)).await;
+        assert!(!notifier.is_running());
+        alarm.await.unwrap();
+    }
 }
@@ -0,0 +1,81 @@
+//! Deliberate pauses of monitoring, e.g. during maintenance windows.
+//!
+//! While paused the detection loops stop polling and Gateway dispatches are
+//! ignored, but the Gateway connection stays up and the last known channel
+//! name is kept, so resuming needs no reconnect or re-seeding.
+
+use crate::clock::{self, Instant};
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::info;
+
+/// Shared pause switch checked by the monitoring loops.
+#[derive(Debug, Default)]
+pub struct Pause {
+    /// `Some(None)` pauses until resumed, `Some(Some(at))` until `at`.
+    paused: Mutex<Option<Option<Instant>>>,
+}
+
+impl Pause {
+    /// Pause monitoring, for `duration` or until resumed.
+    pub fn pause(&self, duration: Option<Duration>) {
+        *self.paused.lock().unwrap_or_else(|e| e.into_inner()) = Some(duration.map(|d| clock::now() + d));
+    }
+
+    /// Resume monitoring; returns false if it was not paused.
+    pub fn resume(&self) -> bool {
+        let was_paused = self.remaining().is_some();
+        *self.paused.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        was_paused
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.remaining().is_some()
+    }
+
+    /// `None` while running, `Some(None)` when paused until resumed, or the time left.
+    ///
+    /// A timed pause that has run out ends here.
+    pub fn remaining(&self) -> Option<Option<Duration>> {
+        let mut paused = self.paused.lock().unwrap_or_else(|e| e.into_inner());
+        let now = clock::now();
+        match *paused {
+            Some(Some(until)) if until <= now => {
+                *paused = None;
+                info!("[PAUSE] Pause ended, monitoring resumed");
+                None
+            }
+            Some(until) => Some(until.map(|until| until - now)),
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_timed_pause_ends_by_itself() {
+        let pause = Pause::default();
+        assert!(!pause.is_paused());
+
+        pause.pause(Some(Duration::from_secs(3600)));
+        clock::sleep(Duration::from_secs(600)).await;
+        assert_eq!(pause.remaining(), Some(Some(Duration::from_secs(3000))));
+
+        clock::sleep(Duration::from_secs(3000)).await;
+        assert!(!pause.is_paused());
+        assert!(!pause.resume());
+    }
+
+    #[test]
+    fn test_pause_until_resumed() {
+        let pause = Pause::default();
+
+        pause.pause(None);
+        assert_eq!(pause.remaining(), Some(None));
+        assert!(pause.resume());
+        assert!(!pause.is_paused());
+    }
+}
@@ -0,0 +1,213 @@
+//! Alarm sound selection.
+//!
+//! `SOUND_PATH` may name a single file, a directory of sounds, or a
+//! comma-separated list. Rotating through several tones keeps the alarm from
+//! fading into the background after the first few events.
+
+use rand::Rng;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// File extensions picked up when `SOUND_PATH` is a directory.
+const AUDIO_EXTENSIONS: &[&str] = &["mp3", "wav", "ogg", "flac", "m4a", "opus"];
+
+/// Order in which sounds are picked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SoundOrder {
+    #[default]
+    Sequential,
+    Random,
+}
+
+impl SoundOrder {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "sequential" => Some(SoundOrder::Sequential),
+            "random" => Some(SoundOrder::Random),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SoundOrder::Sequential => "sequential",
+            SoundOrder::Random => "random",
+        }
+    }
+}
+
+/// When to move on to the next sound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SoundRotation {
+    /// One sound per alarm, the next alarm gets the next sound.
+    #[default]
+    Event,
+    /// A new sound on every repetition within an alarm.
+    Repeat,
+}
+
+impl SoundRotation {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "event" => Some(SoundRotation::Event),
+            "repeat" => Some(SoundRotation::Repeat),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SoundRotation::Event => "event",
+            SoundRotation::Repeat => "repeat",
+        }
+    }
+}
+
+/// Expand a `SOUND_PATH` value into the list of sound files it names.
+///
+/// Directories contribute their audio files in name order. A directory without
+/// any is kept as-is so the player reports a clear error.
+pub fn resolve_sounds(spec: &str) -> Vec<String> {
+    let mut sounds = Vec::new();
+    for entry in spec.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        let path = Path::new(entry);
+        let mut files: Vec<String> = match std::fs::read_dir(path) {
+            Ok(dir) => dir
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| {
+                    p.extension()
+                        .and_then(|ext| ext.to_str())
+                        .is_some_and(|ext| AUDIO_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+                })
+                .map(|p| p.to_string_lossy().to_string())
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+
+        if files.is_empty() {
+            sounds.push(entry.to_string());
+        } else {
+            files.sort();
+            sounds.append(&mut files);
+        }
+    }
+    sounds
+}
+
+/// A set of alarm sounds with a rotating cursor.
+pub struct Playlist {
+    sounds: Vec<String>,
+    order: SoundOrder,
+    cursor: AtomicUsize,
+}
+
+impl Playlist {
+    pub fn new(sounds: Vec<String>, order: SoundOrder) -> Self {
+        let cursor = match order {
+            SoundOrder::Sequential => 0,
+            // Nothing picked yet
+            SoundOrder::Random => usize::MAX,
+        };
+        Self {
+            sounds,
+            order,
+            cursor: AtomicUsize::new(cursor),
+        }
+    }
+
+    pub fn sounds(&self) -> &[String] {
+        &self.sounds
+    }
+
+    /// Pick the next sound. Random order never repeats the previous pick
+    /// unless there is only one sound.
+    pub fn next(&self) -> &str {
+        let len = self.sounds.len();
+        if len == 0 {
+            return "";
+        }
+
+        let index = match self.order {
+            SoundOrder::Sequential => self.cursor.fetch_add(1, Ordering::Relaxed) % len,
+            SoundOrder::Random if len == 1 => 0,
+            SoundOrder::Random => {
+                let last = self.cursor.load(Ordering::Relaxed);
+                let mut index = rand::thread_rng().gen_range(0..len - 1);
+                if last < len && index >= last {
+                    index += 1;
+                }
+                self.cursor.store(index, Ordering::Relaxed);
+                index
+            }
+        };
+        &self.sounds[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn playlist(sounds: &[&str], order: SoundOrder) -> Playlist {
+        Playlist::new(sounds.iter().map(|s| s.to_string()).collect(), order)
+    }
+
+    #[test]
+    fn test_sequential_rotation_wraps() {
+        let playlist = playlist(&["a.mp3", "b.mp3", "c.mp3"], SoundOrder::Sequential);
+        let picks: Vec<&str> = (0..4).map(|_| playlist.next()).collect();
+
+        assert_eq!(picks, vec!["a.mp3", "b.mp3", "c.mp3", "a.mp3"]);
+    }
+
+    #[test]
+    fn test_random_never_repeats_back_to_back() {
+        let playlist = playlist(&["a.mp3", "b.mp3"], SoundOrder::Random);
+        let mut last = playlist.next().to_string();
+        for _ in 0..20 {
+            let pick = playlist.next().to_string();
+            assert_ne!(pick, last);
+            last = pick;
+        }
+    }
+
+    #[test]
+    fn test_single_and_empty_playlists() {
+        let single = playlist(&["boom.mp3"], SoundOrder::Random);
+        assert_eq!(single.next(), "boom.mp3");
+        assert_eq!(single.next(), "boom.mp3");
+
+        let empty = playlist(&[], SoundOrder::Sequential);
+        assert_eq!(empty.next(), "");
+    }
+
+    #[test]
+    fn test_resolve_list_and_directory() {
+        let dir = std::env::temp_dir().join(format!("ollie-playlist-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["b.wav", "a.MP3", "notes.txt"] {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+
+        let spec = format!("/sounds/boom.mp3, {}", dir.display());
+        let sounds = resolve_sounds(&spec);
+
+        assert_eq!(
+            sounds,
+            vec![
+                "/sounds/boom.mp3".to_string(),
+                dir.join("a.MP3").to_string_lossy().to_string(),
+                dir.join("b.wav").to_string_lossy().to_string(),
+            ]
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_settings() {
+        assert_eq!(SoundOrder::parse("Random"), Some(SoundOrder::Random));
+        assert_eq!(SoundOrder::parse("shuffle"), None);
+        assert_eq!(SoundRotation::parse("repeat"), Some(SoundRotation::Repeat));
+        assert_eq!(SoundRotation::parse(""), None);
+    }
+}
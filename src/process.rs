@@ -0,0 +1,183 @@
+//! Process checks for the daemon commands.
+//!
+//! Backed by sysinfo, so `run --daemon`, `stop` and `status` behave the same on
+//! Linux, macOS and Windows. The daemon's PID file lives here too; `run` has
+//! the monitor remove it on its way out. Next to it the daemon's launch
+//! arguments are saved, so `upgrade --restart` can relaunch it the same way.
+
+use crate::logging::warn;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use sysinfo::{Pid, Process, ProcessStatus, ProcessesToUpdate, Signal, System};
+
+const PID_FILE: &str = "scraper.pid";
+const ARGS_FILE: &str = "scraper.args";
+
+/// Get the path to the PID file (in the same directory as the executable).
+pub fn get_pid_file_path() -> PathBuf {
+    std::env::current_exe()
+        .ok()
+        .and_then(|p| p.parent().map(|p| p.to_path_buf()))
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(PID_FILE)
+}
+
+/// Read PID from the PID file.
+pub fn read_pid() -> Option<u32> {
+    let pid_path = get_pid_file_path();
+    fs::read_to_string(&pid_path)
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+}
+
+/// Write PID to the PID file.
+pub fn write_pid(pid: u32) -> std::io::Result<()> {
+    let pid_path = get_pid_file_path();
+    fs::write(&pid_path, pid.to_string())
+}
+
+/// Delete the PID file.
+pub fn delete_pid_file() -> std::io::Result<()> {
+    let pid_path = get_pid_file_path();
+    if pid_path.exists() {
+        fs::remove_file(&pid_path)
+    } else {
+        Ok(())
+    }
+}
+
+/// Get the path to the daemon's saved launch arguments, next to the PID file.
+pub fn get_args_file_path() -> PathBuf {
+    get_pid_file_path().with_file_name(ARGS_FILE)
+}
+
+/// Read the arguments the daemon was last launched with.
+pub fn read_daemon_args() -> Option<Vec<String>> {
+    let contents = fs::read_to_string(get_args_file_path()).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+/// Save the arguments the daemon is launched with.
+pub fn write_daemon_args(args: &[String]) -> std::io::Result<()> {
+    fs::write(get_args_file_path(), serde_json::to_string(args)?)
+}
+
+/// Wait for Ctrl+C or, on Unix, SIGTERM (what `stop` sends), and name the one that arrived.
+pub async fn shutdown_signal() -> &'static str {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => tokio::select! {
+                _ = tokio::signal::ctrl_c() => "Ctrl+C",
+                _ = terminate.recv() => "SIGTERM",
+            },
+            Err(e) => {
+                warn!("Failed to listen for SIGTERM: {}", e);
+                let _ = tokio::signal::ctrl_c().await;
+                "Ctrl+C"
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+        "Ctrl+C"
+    }
+}
+
+/// Resource usage of a running process.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProcessInfo {
+    /// Resident memory in bytes.
+    pub memory: u64,
+    pub uptime: Duration,
+}
+
+/// Look up a single live process and apply `f` to it.
+fn with_process<T>(pid: u32, f: impl FnOnce(&Process) -> T) -> Option<T> {
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+    system
+        .process(pid)
+        // A zombie has exited and only waits to be reaped
+        .filter(|process| process.status() != ProcessStatus::Zombie)
+        .map(f)
+}
+
+/// Check if a process with the given PID is running.
+pub fn is_running(pid: u32) -> bool {
+    with_process(pid, |_| ()).is_some()
+}
+
+/// Memory and uptime of a running process.
+pub fn info(pid: u32) -> Option<ProcessInfo> {
+    with_process(pid, |process| ProcessInfo {
+        memory: process.memory(),
+        uptime: Duration::from_secs(process.run_time()),
+    })
+}
+
+/// Ask a process to exit (SIGTERM).
+///
+/// Returns `Ok(false)` on platforms without a graceful stop signal, such as
+/// Windows, where `stop` asks the monitor over the control socket instead.
+pub fn terminate(pid: u32) -> Result<bool, String> {
+    match with_process(pid, |process| process.kill_with(Signal::Term)) {
+        None => Err(format!("Process {} is not running", pid)),
+        Some(None) => Ok(false),
+        Some(Some(true)) => Ok(true),
+        Some(Some(false)) => Err(format!("Failed to send SIGTERM to process {}", pid)),
+    }
+}
+
+/// Kill a process immediately (SIGKILL, or TerminateProcess on Windows).
+pub fn kill(pid: u32) -> Result<(), String> {
+    match with_process(pid, Process::kill) {
+        None => Err(format!("Process {} is not running", pid)),
+        Some(true) => Ok(()),
+        Some(false) => Err(format!("Failed to kill process {}", pid)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reports_own_process() {
+        assert!(is_running(std::process::id()));
+        let own = info(std::process::id()).expect("Own process not found");
+        assert!(own.memory > 0);
+
+        // PIDs above the kernel's pid_max never exist
+        assert!(!is_running(u32::MAX));
+        assert_eq!(info(u32::MAX), None);
+        assert!(terminate(u32::MAX).is_err());
+    }
+
+    #[test]
+    fn test_daemon_args_round_trip() {
+        assert_eq!(get_args_file_path().parent(), get_pid_file_path().parent());
+        let args = ["run", "--log-file", "--log-level", "debug", "--config", "/etc/ollie scraper.toml"]
+            .map(String::from);
+
+        write_daemon_args(&args).unwrap();
+        assert_eq!(read_daemon_args().as_deref(), Some(&args[..]));
+        fs::remove_file(get_args_file_path()).unwrap();
+        assert_eq!(read_daemon_args(), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_shutdown_signal_on_sigterm() {
+        let mut signal = Box::pin(shutdown_signal());
+        // The first poll installs the handler, before the default one could kill the tests
+        assert!(futures_util::poll!(&mut signal).is_pending());
+        assert!(terminate(std::process::id()).unwrap());
+        let name = tokio::time::timeout(Duration::from_secs(5), signal).await.unwrap();
+        assert_eq!(name, "SIGTERM");
+    }
+}
@@ -0,0 +1,308 @@
+//! Discord REST requests that respect rate limits.
+//!
+//! One [`RestClient`] is shared by every polling loop so connections are
+//! reused. It reads Discord's rate-limit headers and holds requests back
+//! until a drained bucket resets, and a 429 pauses the route (or every
+//! route, when the limit is global) for the `retry_after` Discord asks for.
+//! Network errors and 5xx responses are left to the caller to retry with
+//! [`Backoff`].
+
+use crate::clock::{self, Instant};
+use crate::health::Health;
+use rand::Rng;
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+pub const USER_AGENT: &str = "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+/// Wait after a 429 that doesn't say how long to wait.
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(5);
+/// Longest wait between retries after repeated failures.
+pub const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Why a REST request failed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RestError {
+    /// HTTP 429: nothing is sent on the route (or at all, when `global`)
+    /// until `retry_after` has passed.
+    RateLimited { retry_after: Duration, global: bool },
+    /// A network error or 5xx response, worth retrying after a backoff.
+    Transient(String),
+    /// Any other failure, e.g. 401 or 404, which retrying won't fix.
+    Failed(String),
+}
+
+impl RestError {
+    /// Whether this failure should be retried with backoff.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, RestError::Transient(_))
+    }
+}
+
+impl fmt::Display for RestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RestError::RateLimited { retry_after, global } => write!(
+                f,
+                "{}rate limited, retry after {:.1}s",
+                if *global { "globally " } else { "" },
+                retry_after.as_secs_f64()
+            ),
+            RestError::Transient(e) | RestError::Failed(e) => f.write_str(e),
+        }
+    }
+}
+
+/// Rate-limit state reported by one response.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RateLimit {
+    /// Time until the route may be used again, when its bucket is drained.
+    pub wait: Option<Duration>,
+    /// Whether the wait applies to every route.
+    pub global: bool,
+}
+
+impl RateLimit {
+    /// Read the `X-RateLimit-*` headers of a successful response.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).map(str::trim);
+        let drained = header("x-ratelimit-remaining").and_then(|v| v.parse::<u64>().ok()) == Some(0);
+        Self {
+            wait: header("x-ratelimit-reset-after").and_then(parse_secs).filter(|_| drained),
+            global: header("x-ratelimit-global").is_some(),
+        }
+    }
+
+    /// Read a 429 response, preferring the body's `retry_after` over the headers.
+    pub fn from_429(headers: &HeaderMap, body: &str) -> Self {
+        let body: serde_json::Value = serde_json::from_str(body).unwrap_or_default();
+        let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).and_then(parse_secs);
+        let wait = body["retry_after"]
+            .as_f64()
+            .and_then(|secs| parse_secs(&secs.to_string()))
+            .or_else(|| header("retry-after"))
+            .or_else(|| header("x-ratelimit-reset-after"))
+            .unwrap_or(DEFAULT_RETRY_AFTER);
+        Self {
+            wait: Some(wait),
+            global: body["global"].as_bool().unwrap_or(false) || headers.contains_key("x-ratelimit-global"),
+        }
+    }
+}
+
+fn parse_secs(value: &str) -> Option<Duration> {
+    let secs: f64 = value.trim().parse().ok()?;
+    (secs.is_finite() && secs >= 0.0).then(|| Duration::from_secs_f64(secs))
+}
+
+/// HTTP client behind every [`RestClient`], so they all share one connection pool.
+fn http_client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| reqwest::Client::builder().user_agent(USER_AGENT).build().unwrap_or_default())
+}
+
+/// Authenticated Discord REST client shared by the polling loops.
+pub struct RestClient {
+    client: reqwest::Client,
+    api_base: String,
+    token: String,
+    /// When each route (request path) may be used again.
+    routes: Mutex<HashMap<String, Instant>>,
+    /// When any route may be used again, after a global limit.
+    global: Mutex<Option<Instant>>,
+    /// Where 429 responses are counted.
+    health: Option<Arc<Health>>,
+}
+
+impl RestClient {
+    pub fn new(api_base: &str, token: &str) -> Self {
+        Self {
+            client: http_client().clone(),
+            api_base: api_base.to_string(),
+            token: token.to_string(),
+            routes: Mutex::new(HashMap::new()),
+            global: Mutex::new(None),
+            health: None,
+        }
+    }
+
+    /// Count 429 responses in `health`.
+    pub fn with_health(mut self, health: Arc<Health>) -> Self {
+        self.health = Some(health);
+        self
+    }
+
+    /// How long a request on `route` has to wait for rate limits.
+    fn blocked_for(&self, route: &str) -> Duration {
+        let route_until = self.routes.lock().unwrap_or_else(|e| e.into_inner()).get(route).copied();
+        let global_until = *self.global.lock().unwrap_or_else(|e| e.into_inner());
+        let now = clock::now();
+        [route_until, global_until]
+            .into_iter()
+            .flatten()
+            .map(|until| until.saturating_duration_since(now))
+            .max()
+            .unwrap_or_default()
+    }
+
+    fn record(&self, route: &str, limit: RateLimit) {
+        let Some(wait) = limit.wait else {
+            return;
+        };
+        let until = clock::now() + wait;
+        if limit.global {
+            *self.global.lock().unwrap_or_else(|e| e.into_inner()) = Some(until);
+        } else {
+            self.routes.lock().unwrap_or_else(|e| e.into_inner()).insert(route.to_string(), until);
+        }
+    }
+
+    /// GET `path` (e.g. `/channels/123`) and decode the JSON response.
+    ///
+    /// Waits first if the route is rate limited.
+    pub async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, RestError> {
+        let route = path.split('?').next().unwrap_or(path);
+        let wait = self.blocked_for(route);
+        if !wait.is_zero() {
+            clock::sleep(wait).await;
+        }
+
+        let response = self
+            .client
+            .get(format!("{}{}", self.api_base, path))
+            .header("Authorization", &self.token)
+            .send()
+            .await
+            .map_err(|e| RestError::Transient(format!("request failed: {}", e.without_url())))?;
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            let headers = response.headers().clone();
+            let body = response.text().await.unwrap_or_default();
+            let limit = RateLimit::from_429(&headers, &body);
+            self.record(route, limit);
+            if let Some(health) = &self.health {
+                health.record_rate_limit();
+            }
+            return Err(RestError::RateLimited {
+                retry_after: limit.wait.unwrap_or(DEFAULT_RETRY_AFTER),
+                global: limit.global,
+            });
+        }
+        self.record(route, RateLimit::from_headers(response.headers()));
+        if status.is_server_error() {
+            return Err(RestError::Transient(format!("server error {}", status)));
+        }
+        if !status.is_success() {
+            return Err(RestError::Failed(format!("HTTP {}", status)));
+        }
+        response
+            .json()
+            .await
+            .map_err(|e| RestError::Failed(format!("invalid response: {}", e.without_url())))
+    }
+}
+
+/// Exponential backoff after failed requests.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    base: Duration,
+    failures: u32,
+}
+
+impl Backoff {
+    pub fn new(base: Duration) -> Self {
+        Self { base, failures: 0 }
+    }
+
+    /// Record a failure and return how long to wait before retrying:
+    /// twice the base, doubling each time, up to [`MAX_BACKOFF`].
+    pub fn fail(&mut self) -> Duration {
+        self.failures = self.failures.saturating_add(1);
+        self.base.saturating_mul(1 << self.failures.min(16)).min(MAX_BACKOFF)
+    }
+
+    /// Forget past failures after a success.
+    pub fn reset(&mut self) {
+        self.failures = 0;
+    }
+}
+
+/// `interval` plus a random extra of up to `jitter`, so polls don't tick like a bot.
+pub fn with_jitter(interval: Duration, jitter: Option<Duration>) -> Duration {
+    match jitter {
+        Some(jitter) if !jitter.is_zero() => interval + rand::thread_rng().gen_range(Duration::ZERO..=jitter),
+        _ => interval,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn test_rate_limit_headers() {
+        let drained = headers(&[("X-RateLimit-Remaining", "0"), ("X-RateLimit-Reset-After", "2.5")]);
+        assert_eq!(RateLimit::from_headers(&drained).wait, Some(Duration::from_millis(2500)));
+        // Requests are left in the bucket, so there is nothing to wait for
+        let open = headers(&[("X-RateLimit-Remaining", "3"), ("X-RateLimit-Reset-After", "2.5")]);
+        assert_eq!(RateLimit::from_headers(&open), RateLimit::default());
+
+        let body = r#"{"message": "You are being rate limited.", "retry_after": 1.5, "global": true}"#;
+        let limit = RateLimit::from_429(&headers(&[("Retry-After", "9")]), body);
+        assert_eq!(limit.wait, Some(Duration::from_millis(1500)));
+        assert!(limit.global);
+        let limit = RateLimit::from_429(&headers(&[("Retry-After", "9")]), "not json");
+        assert_eq!(limit.wait, Some(Duration::from_secs(9)));
+        assert!(!limit.global);
+        assert_eq!(RateLimit::from_429(&HeaderMap::new(), "").wait, Some(DEFAULT_RETRY_AFTER));
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        let mut backoff = Backoff::new(Duration::from_millis(1500));
+        assert_eq!(backoff.fail(), Duration::from_secs(3));
+        assert_eq!(backoff.fail(), Duration::from_secs(6));
+        for _ in 0..40 {
+            backoff.fail();
+        }
+        assert_eq!(backoff.fail(), MAX_BACKOFF);
+        backoff.reset();
+        assert_eq!(backoff.fail(), Duration::from_secs(3));
+    }
+
+    #[test]
+    fn test_with_jitter_stays_in_range() {
+        let interval = Duration::from_secs(1);
+        assert_eq!(with_jitter(interval, None), interval);
+        for _ in 0..100 {
+            let delay = with_jitter(interval, Some(Duration::from_millis(500)));
+            assert!(delay >= interval && delay <= Duration::from_millis(1500));
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_limits_block_routes() {
+        let rest = RestClient::new("http://localhost", "token");
+        rest.record("/channels/1", RateLimit { wait: Some(Duration::from_secs(2)), global: false });
+        assert_eq!(rest.blocked_for("/channels/1"), Duration::from_secs(2));
+        assert_eq!(rest.blocked_for("/channels/2"), Duration::ZERO);
+
+        rest.record("/channels/1", RateLimit { wait: Some(Duration::from_secs(5)), global: true });
+        assert_eq!(rest.blocked_for("/channels/2"), Duration::from_secs(5));
+        clock::sleep(Duration::from_secs(5)).await;
+        assert_eq!(rest.blocked_for("/channels/1"), Duration::ZERO);
+    }
+}
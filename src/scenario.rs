@@ -0,0 +1,243 @@
+//! Scripted mock Discord sessions, replayed by `run --mock`.
+//!
+//! Built with the `mock-discord` feature. A scenario file names the channels
+//! the mock serves (the first is the default target of each step) and the
+//! steps played once the monitor has connected:
+//!
+//! ```json
+//! {
+//!   "channels": [{ "id": "100", "name": "start-order-❌" }],
+//!   "steps": [
+//!     { "wait": "1s" },
+//!     { "rename": { "name": "start-order-✅" } },
+//!     { "close": { "code": 4000 } },
+//!     { "message": { "author": "shopbot", "content": "orders open" } },
+//!     { "dispatch": { "event": "TYPING_START", "data": { "channel_id": "100" } } },
+//!     "reconnect",
+//!     { "wait": "3s" }
+//!   ]
+//! }
+//! ```
+//!
+//! `close` ends every session with a Gateway close code and `reconnect` asks
+//! for a reconnect (op 7); either waits for the monitor to come back before the
+//! next step, so nothing is dispatched into the gap.
+
+use crate::logging::info;
+use ollie_scraper::config;
+use ollie_scraper::mock_discord::MockDiscord;
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+use std::path::Path;
+use std::time::Duration;
+
+/// How long the monitor has to connect or come back after a close.
+const SESSION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A channel served by the mock.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ScenarioChannel {
+    pub id: String,
+    pub name: String,
+}
+
+/// One scripted step.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Step {
+    /// Pause, e.g. `"2s"`.
+    Wait(#[serde(deserialize_with = "duration")] Duration),
+    /// Rename a channel over REST and the Gateway.
+    Rename {
+        #[serde(default)]
+        channel: Option<String>,
+        name: String,
+    },
+    /// Post a message as `author`.
+    Message {
+        #[serde(default)]
+        channel: Option<String>,
+        author: String,
+        content: String,
+    },
+    /// Send any dispatch event.
+    Dispatch { event: String, data: Value },
+    /// Ask every session to reconnect (op 7).
+    Reconnect,
+    /// Close every session with a Gateway close code.
+    Close { code: u16 },
+}
+
+/// Channels and steps read from a scenario file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Scenario {
+    pub channels: Vec<ScenarioChannel>,
+    #[serde(default)]
+    pub steps: Vec<Step>,
+}
+
+fn duration<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    config::parse_duration(&value)
+        .ok_or_else(|| serde::de::Error::custom(format!("invalid duration '{}', expected e.g. '2s' or '1m'", value)))
+}
+
+impl Scenario {
+    /// Read and check a scenario file.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read scenario {}: {}", path.display(), e))?;
+        Self::parse(&text).map_err(|e| format!("Invalid scenario {}: {}", path.display(), e))
+    }
+
+    /// Parse a scenario, checking that every step targets one of its channels.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let scenario: Scenario = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        if scenario.channels.is_empty() {
+            return Err("at least one channel is required".to_string());
+        }
+        for (i, step) in scenario.steps.iter().enumerate() {
+            if let Step::Rename { channel: Some(id), .. } | Step::Message { channel: Some(id), .. } = step {
+                if !scenario.channels.iter().any(|channel| &channel.id == id) {
+                    return Err(format!("step {} targets unknown channel '{}'", i + 1, id));
+                }
+            }
+        }
+        Ok(scenario)
+    }
+
+    /// Value for `CHANNEL_ID`.
+    pub fn channel_ids(&self) -> String {
+        self.channels.iter().map(|channel| channel.id.as_str()).collect::<Vec<_>>().join(",")
+    }
+
+    /// Give the mock the scenario's channels.
+    pub fn prepare(&self, mock: &MockDiscord) {
+        for channel in &self.channels {
+            mock.set_channel(&channel.id, &channel.name);
+        }
+    }
+
+    /// Play the steps once a client has connected to `mock`.
+    pub async fn play(&self, mock: &MockDiscord) -> Result<(), String> {
+        wait_for_session(mock, 0).await.map_err(|e| format!("The monitor never connected: {}", e))?;
+        let primary = self.channels[0].id.as_str();
+        for (i, step) in self.steps.iter().enumerate() {
+            info!("[MOCK] Step {}/{}: {:?}", i + 1, self.steps.len(), step);
+            match step {
+                Step::Wait(duration) => tokio::time::sleep(*duration).await,
+                Step::Rename { channel, name } => mock.rename_channel(channel.as_deref().unwrap_or(primary), name),
+                Step::Message {
+                    channel,
+                    author,
+                    content,
+                } => mock.post_message(channel.as_deref().unwrap_or(primary), author, content),
+                Step::Dispatch { event, data } => mock.dispatch(event, data.clone()),
+                Step::Reconnect | Step::Close { .. } => {
+                    let sessions = mock.sessions();
+                    match step {
+                        Step::Close { code } => mock.close_sessions(*code),
+                        _ => mock.request_reconnect(),
+                    }
+                    wait_for_session(mock, sessions)
+                        .await
+                        .map_err(|e| format!("Step {}: the monitor did not reconnect: {}", i + 1, e))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Wait for a session beyond the first `sessions`.
+async fn wait_for_session(mock: &MockDiscord, sessions: usize) -> Result<(), String> {
+    tokio::time::timeout(SESSION_TIMEOUT, mock.sessions_above(sessions))
+        .await
+        .map_err(|_| format!("no session within {}s", SESSION_TIMEOUT.as_secs()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ollie_scraper::config::Config;
+    use ollie_scraper::events::MonitorEvent;
+    use ollie_scraper::models::MonitorTarget;
+    use ollie_scraper::monitor::Monitor;
+
+    #[test]
+    fn test_parse_scenario() {
+        let scenario = Scenario::parse(
+            r#"{
+                "channels": [{ "id": "100", "name": "order-❌" }, { "id": "200", "name": "news" }],
+                "steps": [
+                    { "wait": "2s" },
+                    { "rename": { "name": "order-✅" } },
+                    { "message": { "channel": "200", "author": "bot", "content": "hi" } },
+                    "reconnect",
+                    { "close": { "code": 4000 } }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(scenario.channel_ids(), "100,200");
+        assert_eq!(scenario.steps[0], Step::Wait(Duration::from_secs(2)));
+        assert_eq!(scenario.steps[3], Step::Reconnect);
+        assert_eq!(scenario.steps[4], Step::Close { code: 4000 });
+
+        let err = Scenario::parse(r#"{ "channels": [{ "id": "1", "name": "a" }], "steps": [{ "wait": "soon" }] }"#);
+        assert!(err.unwrap_err().contains("invalid duration 'soon'"));
+        let err = Scenario::parse(
+            r#"{ "channels": [{ "id": "1", "name": "a" }], "steps": [{ "rename": { "channel": "2", "name": "b" } }] }"#,
+        );
+        assert_eq!(err.unwrap_err(), "step 1 targets unknown channel '2'");
+        assert!(Scenario::parse(r#"{ "channels": [] }"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_replays_scenario_through_monitor() {
+        let scenario = Scenario::parse(
+            r#"{
+                "channels": [{ "id": "100", "name": "order-❌" }],
+                "steps": [
+                    { "rename": { "name": "order-⏳" } },
+                    { "close": { "code": 4000 } },
+                    { "rename": { "name": "order-✅" } },
+                    { "wait": "1s" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let mock = MockDiscord::start(0).await.unwrap();
+        scenario.prepare(&mock);
+        let config = Config {
+            token: "mock-token".to_string(),
+            channel_id: Some("100".to_string()),
+            targets: vec![MonitorTarget::new("100")],
+            api_base: Some(mock.api_base()),
+            gateway_url: Some(mock.gateway_url()),
+            poll_interval: Duration::from_secs(60),
+            ..Default::default()
+        };
+        let monitor = Monitor::builder(config).alarms(false).build();
+        let mut events = monitor.subscribe();
+        let shutdown = monitor.handle();
+        let run = tokio::spawn(monitor.run());
+
+        tokio::time::timeout(Duration::from_secs(30), scenario.play(&mock)).await.unwrap().unwrap();
+        shutdown.shutdown();
+        tokio::time::timeout(Duration::from_secs(10), run).await.unwrap().unwrap();
+
+        let mut names = Vec::new();
+        let mut resumed = false;
+        while let Ok(event) = events.try_recv() {
+            match event {
+                MonitorEvent::NameChanged { new_name, .. } => names.push(new_name),
+                MonitorEvent::GatewayConnected { resumed: true } => resumed = true,
+                _ => {}
+            }
+        }
+        assert!(resumed, "the session was not resumed after close 4000");
+        assert_eq!(names.last().map(String::as_str), Some("order-✅"));
+        assert!(names.iter().any(|name| name == "order-⏳"));
+    }
+}
@@ -0,0 +1,61 @@
+//! Display timezone for logged and printed timestamps.
+//!
+//! Defaults to the system's local time. Setting `TIMEZONE` pins it to an IANA
+//! zone so a daemon started from cron or systemd (often UTC) shows the same
+//! times as the user's desktop.
+
+use chrono::{DateTime, FixedOffset, Local, TimeZone, Utc};
+use chrono_tz::Tz;
+use std::sync::OnceLock;
+
+static TIMEZONE: OnceLock<Option<Tz>> = OnceLock::new();
+
+/// Set the process-wide display timezone; `None` uses local time. Only the
+/// first call has an effect.
+pub fn set(tz: Option<Tz>) {
+    let _ = TIMEZONE.set(tz);
+}
+
+/// Configured timezone name, or `local` when using the system timezone.
+pub fn name() -> String {
+    match TIMEZONE.get().copied().flatten() {
+        Some(tz) => tz.name().to_string(),
+        None => "local".to_string(),
+    }
+}
+
+/// Convert a UTC instant into `tz`, or local time when `None`.
+fn convert(at: DateTime<Utc>, tz: Option<Tz>) -> DateTime<FixedOffset> {
+    match tz {
+        Some(tz) => tz.from_utc_datetime(&at.naive_utc()).fixed_offset(),
+        None => at.with_timezone(&Local).fixed_offset(),
+    }
+}
+
+/// Convert a UTC instant into the display timezone.
+pub fn to_display(at: DateTime<Utc>) -> DateTime<FixedOffset> {
+    convert(at, TIMEZONE.get().copied().flatten())
+}
+
+/// Format a UTC instant as `YYYY-MM-DD HH:MM:SS` in the display timezone.
+pub fn format(at: DateTime<Utc>) -> String {
+    to_display(at).format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_to_named_timezone() {
+        let at = Utc.with_ymd_and_hms(2025, 1, 24, 12, 0, 0).unwrap();
+
+        let tokyo = convert(at, Some(chrono_tz::Asia::Tokyo));
+        assert_eq!(tokyo.format("%Y-%m-%d %H:%M:%S %:z").to_string(), "2025-01-24 21:00:00 +09:00");
+
+        // Daylight saving time is applied per date
+        let summer = Utc.with_ymd_and_hms(2025, 7, 1, 12, 0, 0).unwrap();
+        let new_york = convert(summer, Some(chrono_tz::America::New_York));
+        assert_eq!(new_york.format("%H:%M %:z").to_string(), "08:00 -04:00");
+    }
+}
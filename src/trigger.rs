@@ -0,0 +1,175 @@
+//! Trigger rules deciding which channel renames and messages raise an alarm.
+//!
+//! Without a rule every rename alarms. With one, the new name must match the
+//! `trigger` pattern (if set) and must not match the `ignore` pattern (if set).
+//! Patterns are regular expressions, so plain text matches as a substring and
+//! `✅|open` matches either.
+//!
+//! New messages only alarm once a channel has a message keyword or author
+//! filter, since most channels are far too busy to alarm on every message.
+
+use regex::Regex;
+
+/// Trigger and ignore patterns for one channel.
+#[derive(Debug, Clone, Default)]
+pub struct TriggerRule {
+    /// Pattern the new name must match; `None` accepts any name.
+    pub trigger: Option<Regex>,
+    /// Pattern that vetoes the rename even if the trigger matched.
+    pub ignore: Option<Regex>,
+}
+
+/// Keyword and author filters for one channel's new messages.
+#[derive(Debug, Clone, Default)]
+pub struct MessageRule {
+    /// Pattern the message content must match; `None` accepts any content.
+    pub keyword: Option<Regex>,
+    /// User IDs or usernames the message must come from; empty accepts anyone.
+    pub authors: Vec<String>,
+}
+
+/// The trigger pattern a rename or message matched, and the text it matched.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TriggerMatch {
+    pub pattern: String,
+    pub text: String,
+}
+
+impl PartialEq for TriggerRule {
+    fn eq(&self, other: &Self) -> bool {
+        self.trigger_pattern() == other.trigger_pattern() && self.ignore_pattern() == other.ignore_pattern()
+    }
+}
+
+/// Compile a pattern, naming the setting it came from on error.
+pub fn compile(setting: &str, pattern: &str) -> Result<Regex, String> {
+    Regex::new(pattern).map_err(|e| format!("{} pattern '{}' is not a valid regex: {}", setting, pattern, e))
+}
+
+impl PartialEq for MessageRule {
+    fn eq(&self, other: &Self) -> bool {
+        self.keyword_pattern() == other.keyword_pattern() && self.authors == other.authors
+    }
+}
+
+impl TriggerRule {
+    pub fn trigger_pattern(&self) -> Option<&str> {
+        self.trigger.as_ref().map(Regex::as_str)
+    }
+
+    pub fn ignore_pattern(&self) -> Option<&str> {
+        self.ignore.as_ref().map(Regex::as_str)
+    }
+
+    /// Whether a rename to `name` should alarm.
+    ///
+    /// `Some(None)` alarms on any rename, `Some(Some(m))` alarms because the
+    /// trigger matched, and `None` skips the rename.
+    pub fn evaluate(&self, name: &str) -> Option<Option<TriggerMatch>> {
+        if self.ignore.as_ref().is_some_and(|ignore| ignore.is_match(name)) {
+            return None;
+        }
+        match &self.trigger {
+            None => Some(None),
+            Some(trigger) => trigger.find(name).map(|m| {
+                Some(TriggerMatch {
+                    pattern: trigger.as_str().to_string(),
+                    text: m.as_str().to_string(),
+                })
+            }),
+        }
+    }
+}
+
+impl MessageRule {
+    pub fn keyword_pattern(&self) -> Option<&str> {
+        self.keyword.as_ref().map(Regex::as_str)
+    }
+
+    /// Whether the channel's messages are watched at all.
+    pub fn is_enabled(&self) -> bool {
+        self.keyword.is_some() || !self.authors.is_empty()
+    }
+
+    /// Whether a message from `author_id` (`author_name`) should alarm.
+    ///
+    /// Like [`TriggerRule::evaluate`], `Some(Some(m))` carries the keyword match
+    /// and `None` skips the message. Usernames match case-insensitively.
+    pub fn evaluate(&self, content: &str, author_id: &str, author_name: &str) -> Option<Option<TriggerMatch>> {
+        if !self.is_enabled() {
+            return None;
+        }
+        if !self.authors.is_empty()
+            && !self.authors.iter().any(|a| a == author_id || a.eq_ignore_ascii_case(author_name))
+        {
+            return None;
+        }
+        match &self.keyword {
+            None => Some(None),
+            Some(keyword) => keyword.find(content).map(|m| {
+                Some(TriggerMatch {
+                    pattern: keyword.as_str().to_string(),
+                    text: m.as_str().to_string(),
+                })
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_without_patterns_every_rename_alarms() {
+        let rule = TriggerRule::default();
+        assert_eq!(rule.evaluate("start-order-❌"), Some(None));
+    }
+
+    #[test]
+    fn test_trigger_and_ignore_patterns() {
+        let rule = TriggerRule {
+            trigger: Some(compile("TRIGGER", "✅|(?i)open").unwrap()),
+            ignore: Some(compile("IGNORE", "closed").unwrap()),
+        };
+
+        assert_eq!(rule.evaluate("start-order-❌"), None);
+        assert_eq!(
+            rule.evaluate("start-order-✅"),
+            Some(Some(TriggerMatch {
+                pattern: "✅|(?i)open".to_string(),
+                text: "✅".to_string(),
+            }))
+        );
+        assert_eq!(rule.evaluate("shop-OPEN").unwrap().unwrap().text, "OPEN");
+        // Ignore wins over trigger
+        assert_eq!(rule.evaluate("✅ open soon, closed now"), None);
+    }
+
+    #[test]
+    fn test_message_rule_filters_keyword_and_author() {
+        assert_eq!(MessageRule::default().evaluate("orders open", "1", "shopbot"), None);
+
+        let rule = MessageRule {
+            keyword: Some(compile("MESSAGE_TRIGGER", "(?i)orders open").unwrap()),
+            authors: vec!["42".to_string(), "ShopBot".to_string()],
+        };
+        assert_eq!(rule.evaluate("ORDERS OPEN now", "42", "someone").unwrap().unwrap().text, "ORDERS OPEN");
+        assert!(rule.evaluate("orders open", "7", "shopbot").is_some());
+        // Right words from the wrong author, or the wrong words from the right one
+        assert_eq!(rule.evaluate("orders open", "7", "impostor"), None);
+        assert_eq!(rule.evaluate("orders closed", "42", "shopbot"), None);
+
+        let any_message = MessageRule {
+            keyword: None,
+            authors: vec!["42".to_string()],
+        };
+        assert_eq!(any_message.evaluate("hello", "42", "shopbot"), Some(None));
+    }
+
+    #[test]
+    fn test_invalid_pattern_names_the_setting() {
+        let err = compile("CHANNEL_TRIGGERS", "(open").unwrap_err();
+        assert!(err.starts_with("CHANNEL_TRIGGERS pattern '(open' is not a valid regex"));
+    }
+}
@@ -0,0 +1,331 @@
+//! Self-update from GitHub releases.
+//!
+//! Each release carries one binary per platform named `ollie-scraper-<arch>-<os>`
+//! plus a `<name>.sha256` checksum and a `<name>.minisig` minisign signature.
+//! The download must match the checksum and carry a signature from the release
+//! key compiled in below before it replaces the running executable. The
+//! signature's trusted comment must name the asset and the release's version
+//! (`file:<name>` and `version:<tag>`), so an older signed binary can't be
+//! served in place of the latest one.
+
+use minisign_verify::{PublicKey, Signature};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+const RELEASES_URL: &str = "https://api.github.com/repos/NikkeTryHard/ollie-scraper/releases/latest";
+const USER_AGENT: &str = concat!("ollie-scraper/", env!("CARGO_PKG_VERSION"));
+/// Minisign public key that release binaries are signed with.
+const RELEASE_PUBLIC_KEY: &str = "RWRqbYoVar7rr8ZaEwH8CnjYARxZ8a615aBlLwxYmvglEQMRoGjKgmfP";
+
+/// A GitHub release as returned by the releases API.
+#[derive(Debug, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    #[serde(default)]
+    pub assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+impl Release {
+    fn asset(&self, name: &str) -> Option<&ReleaseAsset> {
+        self.assets.iter().find(|a| a.name == name)
+    }
+}
+
+/// Version of the running binary.
+pub fn current_version() -> &'static str {
+    env!("CARGO_PKG_VERSION")
+}
+
+/// Release asset name for this platform, e.g. `ollie-scraper-x86_64-linux`.
+pub fn asset_name() -> String {
+    format!("ollie-scraper-{}-{}", std::env::consts::ARCH, std::env::consts::OS)
+}
+
+/// Parse `v1.2.3` or `1.2.3` into comparable parts.
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let version = version.trim().trim_start_matches('v');
+    // Ignore pre-release/build suffixes like `-rc.1` or `+abc`
+    let version = version.split(['-', '+']).next()?;
+    let mut parts = version.split('.').map(|p| p.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0))?;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    Some((major, minor, patch))
+}
+
+/// Check whether release `tag` is newer than `current`.
+pub fn is_newer(current: &str, tag: &str) -> bool {
+    match (parse_version(current), parse_version(tag)) {
+        (Some(current), Some(latest)) => latest > current,
+        _ => false,
+    }
+}
+
+/// Extract the hex digest from a `sha256sum`-style line (`<hex>  <file>`).
+fn parse_checksum(contents: &str) -> Option<String> {
+    let digest = contents.split_whitespace().next()?;
+    if digest.len() == 64 && digest.chars().all(|c| c.is_ascii_hexdigit()) {
+        Some(digest.to_lowercase())
+    } else {
+        None
+    }
+}
+
+/// Verify `bytes` against an expected SHA-256 hex digest.
+pub fn verify_checksum(bytes: &[u8], expected: &str) -> Result<(), String> {
+    let actual = format!("{:x}", Sha256::digest(bytes));
+    if actual == expected.to_lowercase() {
+        Ok(())
+    } else {
+        Err(format!("Checksum mismatch: expected {}, got {}", expected, actual))
+    }
+}
+
+/// The value of `key` in a trusted comment like `timestamp:1\tfile:name`.
+fn trusted_field<'a>(comment: &'a str, key: &str) -> Option<&'a str> {
+    comment
+        .split_whitespace()
+        .find_map(|field| field.strip_prefix(key)?.strip_prefix(':'))
+}
+
+/// Verify a minisign `signature` of `bytes` against `public_key` (base64),
+/// and that its trusted comment names `asset` at release `tag`.
+pub fn verify_signature(bytes: &[u8], signature: &str, public_key: &str, asset: &str, tag: &str) -> Result<(), String> {
+    let public_key = PublicKey::from_base64(public_key).map_err(|e| format!("Invalid public key: {}", e))?;
+    let signature = Signature::decode(signature).map_err(|e| format!("Malformed signature: {}", e))?;
+    public_key
+        .verify(bytes, &signature, false)
+        .map_err(|e| format!("Signature verification failed: {}", e))?;
+
+    let comment = signature.trusted_comment();
+    match trusted_field(comment, "file") {
+        Some(file) if file == asset => {}
+        file => return Err(format!("Signature is for {}, not {}", file.unwrap_or("an unnamed file"), asset)),
+    }
+    match trusted_field(comment, "version") {
+        Some(version) if version.trim_start_matches('v') == tag.trim_start_matches('v') => Ok(()),
+        version => Err(format!("Signature is for version {}, not {}", version.unwrap_or("(none)"), tag)),
+    }
+}
+
+/// Replace the binary at `exe_path` with `bytes`.
+///
+/// The new binary is written next to the old one first, so a failure part-way
+/// never leaves a truncated executable behind. The old binary is renamed aside
+/// before the new one takes its place, as Windows refuses to overwrite a
+/// running executable but allows renaming it; the old copy is then removed
+/// where possible and otherwise on the next upgrade.
+pub fn replace_binary(exe_path: &Path, bytes: &[u8]) -> Result<(), String> {
+    let staged = exe_path.with_extension("new");
+    let old = exe_path.with_extension("old");
+    let _ = std::fs::remove_file(&old);
+    std::fs::write(&staged, bytes).map_err(|e| format!("Failed to write {:?}: {}", staged, e))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o755))
+            .map_err(|e| format!("Failed to make {:?} executable: {}", staged, e))?;
+    }
+
+    std::fs::rename(exe_path, &old).map_err(|e| {
+        let _ = std::fs::remove_file(&staged);
+        format!("Failed to move {:?} aside: {}", exe_path, e)
+    })?;
+    if let Err(e) = std::fs::rename(&staged, exe_path) {
+        let _ = std::fs::rename(&old, exe_path);
+        let _ = std::fs::remove_file(&staged);
+        return Err(format!("Failed to replace {:?}: {}", exe_path, e));
+    }
+    // Fails on Windows while the old binary is still running
+    let _ = std::fs::remove_file(&old);
+    Ok(())
+}
+
+fn client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))
+}
+
+async fn download(client: &reqwest::Client, url: &str) -> Result<Vec<u8>, String> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Failed to download {}: {}", url, e))?;
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", url, e))?;
+    Ok(bytes.to_vec())
+}
+
+/// Fetch the latest release from GitHub.
+pub async fn fetch_latest_release() -> Result<Release, String> {
+    client()?
+        .get(RELEASES_URL)
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Failed to query releases: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid release response: {}", e))
+}
+
+/// Download this platform's binary from `release`, verify it, and install it
+/// over the running executable.
+pub async fn install(release: &Release) -> Result<(), String> {
+    let name = asset_name();
+    let binary = release
+        .asset(&name)
+        .ok_or_else(|| format!("Release {} has no build for this platform ({})", release.tag_name, name))?;
+    let checksum_name = format!("{}.sha256", name);
+    let checksum = release
+        .asset(&checksum_name)
+        .ok_or_else(|| format!("Release {} is missing {}; refusing to install", release.tag_name, checksum_name))?;
+    let signature_name = format!("{}.minisig", name);
+    let signature = release
+        .asset(&signature_name)
+        .ok_or_else(|| format!("Release {} is missing {}; refusing to install", release.tag_name, signature_name))?;
+
+    let client = client()?;
+    let expected = download(&client, &checksum.browser_download_url).await?;
+    let expected = parse_checksum(&String::from_utf8_lossy(&expected))
+        .ok_or_else(|| format!("Malformed checksum file {}", checksum_name))?;
+    let bytes = download(&client, &binary.browser_download_url).await?;
+    verify_checksum(&bytes, &expected)?;
+    let signature = download(&client, &signature.browser_download_url).await?;
+    verify_signature(
+        &bytes,
+        &String::from_utf8_lossy(&signature),
+        RELEASE_PUBLIC_KEY,
+        &name,
+        &release.tag_name,
+    )?;
+
+    let exe_path = std::env::current_exe().map_err(|e| format!("Failed to get executable path: {}", e))?;
+    replace_binary(&exe_path, &bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_newer() {
+        assert!(is_newer("0.1.0", "v0.2.0"));
+        assert!(is_newer("0.1.0", "0.1.1"));
+        assert!(is_newer("0.9.9", "v1.0"));
+        assert!(!is_newer("0.2.0", "v0.2.0"));
+        assert!(!is_newer("0.2.0", "v0.1.9"));
+        assert!(!is_newer("0.1.0", "nightly"));
+    }
+
+    #[test]
+    fn test_parse_version_ignores_suffixes() {
+        assert_eq!(parse_version("v1.2.3-rc.1"), Some((1, 2, 3)));
+        assert_eq!(parse_version("1.2.3+build"), Some((1, 2, 3)));
+        assert_eq!(parse_version("1.x"), None);
+    }
+
+    #[test]
+    fn test_parse_checksum() {
+        let digest = "a".repeat(64);
+        assert_eq!(parse_checksum(&format!("{}  ollie-scraper-x86_64-linux\n", digest)), Some(digest.clone()));
+        assert_eq!(parse_checksum(&digest.to_uppercase()), Some(digest));
+        assert_eq!(parse_checksum("not-a-digest  file"), None);
+        assert_eq!(parse_checksum(""), None);
+    }
+
+    #[test]
+    fn test_verify_checksum() {
+        // sha256("hello")
+        let expected = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+        assert!(verify_checksum(b"hello", expected).is_ok());
+        assert!(verify_checksum(b"hello", &expected.to_uppercase()).is_ok());
+        assert!(verify_checksum(b"tampered", expected).is_err());
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        // Signed with a throwaway key, not the release key
+        let public_key = "RWRi+nK0z/1T4qF1MieObRKjIHAqzrDBibM0Iw6xx8zXsJ89lZGIG8Ne";
+        let signature = "untrusted comment: signature from minisign secret key
+RURi+nK0z/1T4l2tJVySOulNbYPhRJEApZfuXtUW6RWWPIDbzO7zV5cLHhJ1pEIgj6rPTToZ/KDuI2yLFe1Zt1JDe1ngKZGwDwE=
+trusted comment: timestamp:1760000000\tfile:ollie-scraper-x86_64-linux\tversion:v0.2.0
+gANjQiVT4xgxdr1JXPODBvrIyyJha/gQip2Dhm7wK88rgmrlxQaxvUdpyn2B4FbUM7oi0zF9LhP/ppJiuiLUBg==
+";
+        let verify = |bytes: &[u8], signature, public_key| {
+            verify_signature(bytes, signature, public_key, "ollie-scraper-x86_64-linux", "v0.2.0")
+        };
+        assert!(verify(b"new binary", signature, public_key).is_ok());
+        assert!(verify(b"tampered", signature, public_key).is_err());
+        assert!(verify(b"new binary", signature, RELEASE_PUBLIC_KEY).is_err());
+        assert!(verify(b"new binary", "not a signature", public_key).is_err());
+    }
+
+    #[test]
+    fn test_verify_signature_checks_trusted_comment() {
+        let public_key = "RWRi+nK0z/1T4qF1MieObRKjIHAqzrDBibM0Iw6xx8zXsJ89lZGIG8Ne";
+        let signature = "untrusted comment: signature from minisign secret key
+RURi+nK0z/1T4l2tJVySOulNbYPhRJEApZfuXtUW6RWWPIDbzO7zV5cLHhJ1pEIgj6rPTToZ/KDuI2yLFe1Zt1JDe1ngKZGwDwE=
+trusted comment: timestamp:1760000000\tfile:ollie-scraper-x86_64-linux\tversion:v0.2.0
+gANjQiVT4xgxdr1JXPODBvrIyyJha/gQip2Dhm7wK88rgmrlxQaxvUdpyn2B4FbUM7oi0zF9LhP/ppJiuiLUBg==
+";
+        let verify = |asset, tag| verify_signature(b"new binary", signature, public_key, asset, tag);
+
+        assert!(verify("ollie-scraper-x86_64-linux", "0.2.0").is_ok());
+        // An older release's binary replayed as the latest one
+        let replayed = verify("ollie-scraper-x86_64-linux", "v0.3.0").unwrap_err();
+        assert_eq!(replayed, "Signature is for version v0.2.0, not v0.3.0");
+        let other = verify("ollie-scraper-aarch64-macos", "v0.2.0").unwrap_err();
+        assert_eq!(other, "Signature is for ollie-scraper-x86_64-linux, not ollie-scraper-aarch64-macos");
+        assert_eq!(trusted_field("timestamp:1\tfile:a", "version"), None);
+    }
+
+    #[test]
+    fn test_replace_binary_swaps_contents() {
+        let dir = std::env::temp_dir().join(format!("ollie-upgrade-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let exe = dir.join("ollie-scraper");
+        std::fs::write(&exe, b"old").unwrap();
+
+        replace_binary(&exe, b"new").expect("Replace failed");
+
+        assert_eq!(std::fs::read(&exe).unwrap(), b"new");
+        assert!(!exe.with_extension("new").exists());
+        assert!(!exe.with_extension("old").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_release_deserialization() {
+        let json = r#"{
+            "tag_name": "v0.2.0",
+            "assets": [
+                {"name": "ollie-scraper-x86_64-linux", "browser_download_url": "https://example.com/bin"},
+                {"name": "ollie-scraper-x86_64-linux.sha256", "browser_download_url": "https://example.com/sum"}
+            ]
+        }"#;
+
+        let release: Release = serde_json::from_str(json).unwrap();
+        assert_eq!(release.tag_name, "v0.2.0");
+        assert_eq!(
+            release.asset("ollie-scraper-x86_64-linux.sha256").unwrap().browser_download_url,
+            "https://example.com/sum"
+        );
+        assert!(release.asset("ollie-scraper-aarch64-macos").is_none());
+    }
+}
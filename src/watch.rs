@@ -0,0 +1,351 @@
+//! Live terminal dashboard for a running daemon.
+//!
+//! `ollie-scraper watch` asks the daemon for its status and counters over the
+//! control socket, reads the latest entries of the event history, and redraws
+//! them every refresh. Pressing `a` silences the ringing alarm.
+
+use crate::control::{self, ControlRequest};
+use crate::timezone;
+use ollie_scraper::monitor::history::{self, HistoryEvent};
+use ollie_scraper::monitor::{StatusSnapshot, Stats, STALE_POLL_AGE};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::path::Path;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// History entries kept for the event list.
+const RECENT_EVENTS: usize = 100;
+/// How often the key reader checks whether the dashboard has closed.
+const KEY_POLL: Duration = Duration::from_millis(200);
+
+/// What a keypress asks for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    /// Silence the ringing alarm.
+    Ack,
+    /// Refresh right away.
+    Refresh,
+    Quit,
+}
+
+/// Map a key to its action: `a`/`s` silence, `r` refreshes, `q`, Esc and Ctrl+C quit.
+pub fn action(key: &KeyEvent) -> Option<Action> {
+    match key.code {
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Some(Action::Quit),
+        KeyCode::Char('a') | KeyCode::Char('s') => Some(Action::Ack),
+        KeyCode::Char('r') => Some(Action::Refresh),
+        KeyCode::Char('q') | KeyCode::Esc => Some(Action::Quit),
+        _ => None,
+    }
+}
+
+/// Everything the dashboard shows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchState {
+    /// The daemon's live state, or why it could not be asked.
+    pub status: Result<StatusSnapshot, String>,
+    pub stats: Option<Stats>,
+    /// Latest history entries, oldest first.
+    pub events: Vec<HistoryEvent>,
+    /// Outcome of the last keypress, e.g. `Alarm silenced`.
+    pub message: Option<String>,
+}
+
+impl WatchState {
+    /// Ask the daemon at `socket` for its state and read the history at `history_path`.
+    pub async fn fetch(socket: &Path, history_path: &Path) -> Self {
+        let status = control::send_request(socket, &ControlRequest::Status)
+            .await
+            .and_then(|response| response.status.ok_or(response.message));
+        let stats = match status {
+            Ok(_) => control::send_request(socket, &ControlRequest::Stats)
+                .await
+                .ok()
+                .and_then(|response| response.stats),
+            Err(_) => None,
+        };
+        let mut events = history::read_events(history_path).unwrap_or_default();
+        events.drain(..events.len().saturating_sub(RECENT_EVENTS));
+        Self {
+            status,
+            stats,
+            events,
+            message: None,
+        }
+    }
+}
+
+/// One line describing a history entry, e.g. `2024-05-01 12:00:00  RENAME WS  a -> b`.
+pub fn describe(event: &HistoryEvent) -> String {
+    match event {
+        HistoryEvent::Alarm { at, title, body, .. } => format!("{}  ALARM  {}: {}", timezone::format(*at), title, body),
+        HistoryEvent::Ack { at, alarm_id, via } => {
+            format!("{}  ACK    alarm #{} via {}", timezone::format(*at), alarm_id, via.as_str())
+        }
+        HistoryEvent::Muted { at, channel, title, .. } => {
+            format!("{}  MUTED  {} ({})", timezone::format(*at), title, channel)
+        }
+        HistoryEvent::Change { at, source, old_name, new_name, alarm, .. } => format!(
+            "{}  RENAME {} {} -> {}{}",
+            timezone::format(*at),
+            source,
+            old_name.as_deref().unwrap_or("?"),
+            new_name,
+            if *alarm { " (alarm)" } else { "" }
+        ),
+    }
+}
+
+/// A `label: value` line with the value in `style`.
+fn field(label: &str, value: String, style: Style) -> Line<'static> {
+    Line::from(vec![Span::raw(format!("{:<10} ", label)).bold(), Span::styled(value, style)])
+}
+
+/// Lines of the state panel.
+fn state_lines(status: &StatusSnapshot) -> Vec<Line<'static>> {
+    let ok = Style::default().fg(Color::Green);
+    let bad = Style::default().fg(Color::Red);
+    let mut lines = Vec::new();
+
+    let channel = status.channel_name.clone().unwrap_or_else(|| "(not fetched yet)".to_string());
+    lines.push(field("Channel", channel, Style::default().add_modifier(Modifier::BOLD)));
+    if status.channels.len() > 1 {
+        for (id, name) in &status.channels {
+            lines.push(Line::from(format!("{:<10}   {} ({})", "", name, id)));
+        }
+    }
+
+    lines.push(match (status.ws_connected, &status.ws_error) {
+        (true, _) => field("Gateway", "connected".to_string(), ok),
+        (false, Some(e)) => field("Gateway", format!("disconnected: {}", e), bad),
+        (false, None) => field("Gateway", "disconnected".to_string(), bad),
+    });
+    lines.push(match status.heartbeat_latency_ms {
+        Some(ms) => field("Heartbeat", format!("{} ms", ms), ok),
+        None => field("Heartbeat", "no ACK yet".to_string(), Style::default().fg(Color::Yellow)),
+    });
+    lines.push(match (status.last_poll_secs, &status.initial_fetch_error) {
+        (Some(secs), _) if secs < STALE_POLL_AGE.as_secs() => field("Poller", format!("last poll {}s ago", secs), ok),
+        (Some(secs), _) => field("Poller", format!("stale, last poll {}s ago", secs), bad),
+        (None, Some(e)) => field("Poller", format!("initial fetch failed: {}", e), bad),
+        (None, None) => field("Poller", "no successful poll yet".to_string(), Style::default().fg(Color::Yellow)),
+    });
+
+    match status.alarms.first() {
+        Some(ringing) if status.alarm_active => {
+            lines.push(field("Alarm", format!("RINGING {}", ringing), bad.add_modifier(Modifier::BOLD)));
+            for queued in &status.alarms[1..] {
+                lines.push(Line::from(format!("{:<10}   queued: {}", "", queued)));
+            }
+        }
+        _ => lines.push(field("Alarm", "idle".to_string(), ok)),
+    }
+    match (status.paused, status.paused_secs_left) {
+        (true, Some(secs)) => lines.push(field("Paused", format!("{}m {}s left", secs / 60, secs % 60), bad)),
+        (true, None) => lines.push(field("Paused", "until resumed".to_string(), bad)),
+        (false, _) => {}
+    }
+    if !status.muted.is_empty() {
+        lines.push(field("Muted", status.muted.join(", "), Style::default().fg(Color::Yellow)));
+    }
+    lines
+}
+
+/// Draw the dashboard.
+pub fn draw(frame: &mut Frame, state: &WatchState) {
+    let lines = match &state.status {
+        Ok(status) => state_lines(status),
+        Err(e) => vec![Line::from(format!("Daemon not reachable: {}", e)).red()],
+    };
+    let [top, middle, bottom] = Layout::vertical([
+        Constraint::Length(lines.len() as u16 + 2),
+        Constraint::Min(3),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    frame.render_widget(Paragraph::new(lines).block(Block::bordered().title(" ollie-scraper ")), top);
+
+    // Newest at the top, as many as fit
+    let items: Vec<ListItem> = state
+        .events
+        .iter()
+        .rev()
+        .take(middle.height.saturating_sub(2) as usize)
+        .map(|event| {
+            let style = match event {
+                HistoryEvent::Alarm { .. } => Style::default().fg(Color::Red),
+                HistoryEvent::Change { alarm: true, .. } => Style::default().fg(Color::Yellow),
+                _ => Style::default(),
+            };
+            ListItem::new(describe(event)).style(style)
+        })
+        .collect();
+    frame.render_widget(List::new(items).block(Block::bordered().title(" Recent events ")), middle);
+
+    let mut footer = "a: silence alarm  r: refresh  q: quit".to_string();
+    if let Some(stats) = &state.stats {
+        footer.push_str(&format!("  |  polls {}  renames {}  alarms {}", stats.polls, stats.renames, stats.alarms));
+    }
+    if let Some(message) = &state.message {
+        footer.push_str(&format!("  |  {}", message));
+    }
+    frame.render_widget(Paragraph::new(footer).dim(), bottom);
+}
+
+/// Forward terminal events from a blocking reader thread until the receiver is dropped.
+fn read_terminal_events() -> mpsc::UnboundedReceiver<Event> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    std::thread::spawn(move || {
+        while !tx.is_closed() {
+            match event::poll(KEY_POLL) {
+                Ok(true) => match event::read() {
+                    Ok(event) => {
+                        if tx.send(event).is_err() {
+                            return;
+                        }
+                    }
+                    Err(_) => return,
+                },
+                Ok(false) => {}
+                Err(_) => return,
+            }
+        }
+    });
+    rx
+}
+
+/// Run the dashboard until the user quits, refreshing every `interval`.
+pub async fn run(interval: Duration) -> Result<(), String> {
+    let mut terminal = ratatui::try_init().map_err(|e| format!("Failed to set up the terminal: {}", e))?;
+    let result = watch_loop(&mut terminal, interval, &control::get_socket_path(), &crate::get_history_path()).await;
+    ratatui::restore();
+    result
+}
+
+async fn watch_loop(
+    terminal: &mut DefaultTerminal,
+    interval: Duration,
+    socket: &Path,
+    history_path: &Path,
+) -> Result<(), String> {
+    let mut input = read_terminal_events();
+    let mut message = None;
+    loop {
+        let mut state = WatchState::fetch(socket, history_path).await;
+        state.message = message.clone();
+        terminal
+            .draw(|frame| draw(frame, &state))
+            .map_err(|e| format!("Failed to draw: {}", e))?;
+
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            event = input.recv() => match event {
+                Some(Event::Key(key)) if key.kind == KeyEventKind::Press => match action(&key) {
+                    Some(Action::Quit) => return Ok(()),
+                    Some(Action::Ack) => {
+                        message = Some(match control::send_request(socket, &ControlRequest::Ack).await {
+                            Ok(response) => response.message,
+                            Err(e) => e,
+                        });
+                    }
+                    Some(Action::Refresh) | None => {}
+                },
+                // Redraw on resize and anything else
+                Some(_) => {}
+                None => return Ok(()),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+    use std::collections::BTreeMap;
+
+    fn status() -> StatusSnapshot {
+        StatusSnapshot {
+            channel_name: Some("start-order-✅".to_string()),
+            channels: BTreeMap::from([("100".to_string(), "start-order-✅".to_string())]),
+            ws_connected: false,
+            heartbeat_latency_ms: Some(42),
+            last_poll_secs: Some(3),
+            alarm_active: true,
+            alarms: vec!["CHANNEL OPEN: start-order-✅".to_string()],
+            alarm_ids: vec![1],
+            initial_fetch_done: true,
+            initial_fetch_error: None,
+            ws_error: Some("closed with 4004".to_string()),
+            muted: Vec::new(),
+            paused: false,
+            paused_secs_left: None,
+        }
+    }
+
+    fn render(state: &WatchState) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+        terminal.draw(|frame| draw(frame, state)).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..buffer.area.height)
+            .map(|y| (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_keys_map_to_actions() {
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        assert_eq!(action(&key(KeyCode::Char('a'))), Some(Action::Ack));
+        assert_eq!(action(&key(KeyCode::Esc)), Some(Action::Quit));
+        assert_eq!(action(&KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)), Some(Action::Quit));
+        assert_eq!(action(&key(KeyCode::Char('x'))), None);
+    }
+
+    #[test]
+    fn test_draws_state_and_newest_events_first() {
+        let at = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let change = |new_name: &str, alarm| HistoryEvent::Change {
+            at,
+            channel_id: "100".to_string(),
+            source: "WS".to_string(),
+            old_name: Some("start-order-❌".to_string()),
+            new_name: new_name.to_string(),
+            alarm,
+        };
+        let state = WatchState {
+            status: Ok(status()),
+            stats: None,
+            events: vec![change("first", false), change("second", true)],
+            message: Some("Alarm silenced".to_string()),
+        };
+
+        let screen = render(&state);
+        assert!(screen.contains("disconnected: closed with 4004"), "{}", screen);
+        assert!(screen.contains("42 ms"));
+        assert!(screen.contains("last poll 3s ago"));
+        assert!(screen.contains("RINGING CHANNEL OPEN"));
+        assert!(screen.contains("Alarm silenced"));
+        let second = screen.find("-> second (alarm)").unwrap();
+        assert!(second < screen.find("-> first").unwrap());
+    }
+
+    #[test]
+    fn test_draws_unreachable_daemon() {
+        let state = WatchState {
+            status: Err("Failed to connect".to_string()),
+            stats: None,
+            events: Vec::new(),
+            message: None,
+        };
+        assert!(render(&state).contains("Daemon not reachable: Failed to connect"));
+    }
+}